@@ -0,0 +1,33 @@
+# English status lines, the fallback for every locale. Reports and error
+# messages are not in the catalog.
+# Translations override these ids in <config dir>/localdoc/locales/<locale>.ftl.
+
+## Analysis checks
+
+baseline-recorded = Recorded { $count } finding(s) in { $path }
+check-failed = Check failed: { $count } finding(s), { $allowed } allowed
+
+## Lookups
+
+no-symbols-in-module = No symbols found in '{ $module }'
+no-search-results = No results found for '{ $query }'
+no-symbols-in-file = No symbols found in file matching '{ $file }'
+no-symbols-of-kind = No symbols found with kind matching '{ $kind }'
+available-kinds = Available kinds:
+no-symbol-match = No symbol found matching '{ $name }'
+searching-for = Searching for '{ $query }'
+searching-as-typed = { $reason }; searching for the text as typed
+warning = Warning:
+
+## Docpacks and the commons
+
+skipping = Skipping
+commons-searching = Searching for '{ $query }'...
+installing = Installing { $package }...
+fetching-from = Fetching from { $url }...
+downloading-from = Downloading docpack from: { $url }...
+no-docpacks-installed = No docpacks installed yet.
+checking-updates = Checking for updates...
+update-failed = Failed to download update
+not-in-commons = (not found in commons)
+reading-git-history = Reading git history...
@@ -0,0 +1,33 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Findings accepted when a check was adopted, so that only new ones fail it
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Keys identifying each finding, e.g. a symbol ID or "caller -> callee"
+    #[serde(default)]
+    pub findings: BTreeSet<String>,
+}
+
+/// Read a baseline file written by `--update-baseline`
+pub fn load(path: &Path) -> Result<Baseline> {
+    if !path.exists() {
+        anyhow::bail!(
+            "Baseline file {} does not exist; create it with --update-baseline",
+            path.display()
+        );
+    }
+    let content = std::fs::read_to_string(path)
+        .context(format!("Failed to read baseline file {}", path.display()))?;
+    serde_json::from_str(&content)
+        .context(format!("Failed to parse baseline file {}", path.display()))
+}
+
+impl Baseline {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .context(format!("Failed to write baseline file {}", path.display()))
+    }
+}
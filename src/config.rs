@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// User settings read from `config.toml` in localdoc's config directory
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub mcp: McpConfig,
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub ci: CiConfig,
+    #[serde(default)]
+    pub ignore: IgnoreConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// Command shorthands from the `[alias]` section, e.g. `big = "query mypack symbols --sort loc"`
+    #[serde(default)]
+    pub alias: BTreeMap<String, String>,
+}
+
+/// The `[search]` section
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct SearchConfig {
+    /// Groups of interchangeable search terms, e.g. `hashmap = ["dictionary", "map"]`.
+    /// Searching for any term in a group also searches for the others.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, Vec<String>>,
+}
+
+impl SearchConfig {
+    /// Other terms to search for along with `term`, from every alias group containing it
+    pub fn aliases_for(&self, term: &str) -> Vec<String> {
+        let term = term.to_lowercase();
+        let mut aliases: Vec<String> = Vec::new();
+        for (key, values) in &self.aliases {
+            let group: Vec<String> = std::iter::once(key)
+                .chain(values)
+                .map(|t| t.to_lowercase())
+                .collect();
+            if group.contains(&term) {
+                aliases.extend(group.into_iter().filter(|t| *t != term));
+            }
+        }
+        aliases.sort();
+        aliases.dedup();
+        aliases
+    }
+}
+
+/// The `[ci]` section: thresholds `localdoc ci` uses when not given on the command line
+#[derive(Debug, Default, Deserialize)]
+pub struct CiConfig {
+    pub min_test_coverage: Option<f64>,
+    pub min_doc_coverage: Option<f64>,
+    /// Docpack (path or name) to check API compatibility against
+    pub baseline: Option<String>,
+}
+
+/// The `[ignore]` section
+#[derive(Debug, Default, Deserialize)]
+pub struct IgnoreConfig {
+    /// Patterns in `.localdocignore` syntax, applied before that file's own
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+/// The `[ui]` section
+#[derive(Debug, Default, Deserialize)]
+pub struct UiConfig {
+    /// Language of status lines, e.g. "de" or "pt-BR", when `LOCALDOC_LANG` is unset
+    pub locale: Option<String>,
+    /// Always use `--ascii` output
+    #[serde(default)]
+    pub ascii: bool,
+}
+
+/// The `[encryption]` section
+#[derive(Debug, Default, Deserialize)]
+pub struct EncryptionConfig {
+    /// Password for encrypted docpacks, used when `LOCALDOC_KEY` is unset
+    pub key: Option<String>,
+}
+
+/// The `[mcp]` section
+#[derive(Debug, Default, Deserialize)]
+pub struct McpConfig {
+    /// Client profile used when `mcp` gets no `--profile`
+    pub profile: Option<McpProfile>,
+    /// Docpack directories served when `mcp` gets no `--packages-dir`, earlier ones
+    /// taking precedence; a leading `~/` is the home directory
+    #[serde(default)]
+    pub packages_dirs: Vec<PathBuf>,
+    /// Per-tool overrides keyed by the tool's built-in name, e.g. `[mcp.tools.get_symbol]`
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolOverride>,
+}
+
+impl McpConfig {
+    /// `packages_dirs` with `~/` expanded
+    pub fn packages_dirs(&self) -> Result<Vec<PathBuf>> {
+        self.packages_dirs
+            .iter()
+            .map(|dir| match dir.strip_prefix("~") {
+                Ok(rest) => {
+                    let home = dirs::home_dir().context("Could not determine home directory")?;
+                    Ok(home.join(rest))
+                }
+                Err(_) => Ok(dir.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Changes to how one MCP tool is advertised
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ToolOverride {
+    /// Name advertised to clients instead of the built-in one
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Leave the tool out of `tools/list` and refuse calls to it
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+/// MCP clients with different limits on how many tools they handle well
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum McpProfile {
+    /// Every tool
+    #[default]
+    Claude,
+    /// Navigation tools only; Cursor caps the total tools across servers
+    Cursor,
+    /// The basic package, symbol, example, and search tools
+    Generic,
+}
+
+impl McpProfile {
+    /// Whether the profile advertises the tool with this built-in name
+    pub fn includes(self, tool: &str) -> bool {
+        match self {
+            McpProfile::Claude => true,
+            McpProfile::Cursor => matches!(
+                tool,
+                "list_packages"
+                    | "list_symbols"
+                    | "get_symbol"
+                    | "list_modules"
+                    | "get_module"
+                    | "get_module_overview"
+                    | "search"
+            ),
+            McpProfile::Generic => matches!(
+                tool,
+                "list_packages" | "list_symbols" | "get_symbol" | "get_examples" | "search"
+            ),
+        }
+    }
+}
+
+/// Split an alias expansion into arguments at whitespace, keeping single-
+/// or double-quoted text together
+pub fn split_words(text: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            None => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        anyhow::bail!("Unclosed quote in alias '{}'", text);
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Default location of the config file
+pub fn default_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+    Ok(config_dir.join("localdoc").join("config.toml"))
+}
+
+/// Read the config at `path`; a missing default config is treated as empty
+pub fn load(path: Option<&Path>) -> Result<Config> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let path = default_path()?;
+            if !path.exists() {
+                return Ok(Config::default());
+            }
+            path
+        }
+    };
+
+    let content = std::fs::read_to_string(&path)
+        .context(format!("Failed to read config file {}", path.display()))?;
+    toml::from_str(&content).context(format!("Failed to parse config file {}", path.display()))
+}
+
+/// Environment variable holding the password for encrypted docpacks
+pub const KEY_ENV: &str = "LOCALDOC_KEY";
+
+/// The docpack encryption key from `LOCALDOC_KEY`, else from the default config
+pub fn docpack_key() -> Result<Option<String>> {
+    match std::env::var(KEY_ENV) {
+        Ok(key) if !key.is_empty() => Ok(Some(key)),
+        _ => Ok(load(None)?.encryption.key),
+    }
+}
@@ -0,0 +1,291 @@
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+
+/// Set by `--raw-ids` to print symbol IDs exactly as the docpack stores them
+static RAW_IDS: AtomicBool = AtomicBool::new(false);
+
+/// Set by `--ascii` (or `ascii` under `[ui]`) for plain, linear text output
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Width of the rules under report headings
+const RULE_WIDTH: usize = 50;
+
+/// Generic argument lists nested deeper than this are shown as `<..>`
+const GENERICS_DEPTH: usize = 1;
+
+static RUST_HASH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"::h[0-9a-f]{16}$").expect("valid regex"));
+static SUFFIX_HASH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[#@][0-9a-f]{6,}$").expect("valid regex"));
+static GO_RECEIVER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(\*?([A-Za-z_][A-Za-z0-9_]*)\)\.").expect("valid regex"));
+
+pub fn set_raw_ids(raw: bool) {
+    RAW_IDS.store(raw, Ordering::Relaxed);
+}
+
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// The glyphs text output draws with, chosen by `--ascii`
+#[derive(Clone, Copy)]
+enum Glyphs {
+    Unicode,
+    Ascii,
+}
+
+impl Glyphs {
+    fn current() -> Self {
+        if ASCII.load(Ordering::Relaxed) {
+            Glyphs::Ascii
+        } else {
+            Glyphs::Unicode
+        }
+    }
+
+    fn pick(self, unicode: &'static str, ascii: &'static str) -> &'static str {
+        match self {
+            Glyphs::Unicode => unicode,
+            Glyphs::Ascii => ascii,
+        }
+    }
+
+    fn arrow(self) -> &'static str {
+        self.pick("→", "->")
+    }
+
+    fn check_mark(self) -> &'static str {
+        self.pick("✓", "[ok]")
+    }
+
+    fn cross(self) -> &'static str {
+        self.pick("✗", "[failed]")
+    }
+
+    fn ellipsis(self) -> &'static str {
+        self.pick("…", "...")
+    }
+}
+
+/// Separates an old value from its replacement
+pub fn arrow() -> &'static str {
+    Glyphs::current().arrow()
+}
+
+/// Marks a passed check or completed step
+pub fn check_mark() -> &'static str {
+    Glyphs::current().check_mark()
+}
+
+/// Marks a failed check or step
+pub fn cross() -> &'static str {
+    Glyphs::current().cross()
+}
+
+/// Marks text cut short
+pub fn ellipsis() -> &'static str {
+    Glyphs::current().ellipsis()
+}
+
+/// Print the rule under a report heading. ASCII mode leaves it out, since
+/// screen readers announce every character of it.
+pub fn print_rule(c: char) {
+    if !ASCII.load(Ordering::Relaxed) {
+        println!("{}", c.to_string().repeat(RULE_WIDTH));
+    }
+}
+
+/// A symbol ID as text output shows it: Rust (and C++) `_ZN` names demangled,
+/// compiler and builder hashes stripped, Go receivers like `(*Server).Handle`
+/// shortened to `Server.Handle`, and deeply nested generics collapsed.
+/// Structured formats always carry the raw ID.
+pub fn name(id: &str) -> Cow<'_, str> {
+    if RAW_IDS.load(Ordering::Relaxed) {
+        return Cow::Borrowed(id);
+    }
+
+    let mut name = Cow::Borrowed(id);
+    if let Some(demangled) = demangle(&name) {
+        name = Cow::Owned(demangled);
+    }
+    for pattern in [&*RUST_HASH, &*SUFFIX_HASH] {
+        if pattern.is_match(&name) {
+            name = Cow::Owned(pattern.replace(&name, "").into_owned());
+        }
+    }
+    if GO_RECEIVER.is_match(&name) {
+        name = Cow::Owned(GO_RECEIVER.replace_all(&name, "$1.").into_owned());
+    }
+    if let Some(collapsed) = collapse_generics(&name) {
+        name = Cow::Owned(collapsed);
+    }
+    name
+}
+
+/// Decode a legacy-mangled `_ZN<len><ident>...E` name into `a::b::c`,
+/// dropping the trailing `h<hash>` segment
+fn demangle(id: &str) -> Option<String> {
+    let rest = id.strip_prefix("_ZN").or_else(|| id.strip_prefix("__ZN"))?;
+    let mut rest = rest.strip_suffix('E').unwrap_or(rest);
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let len: usize = rest[..digits].parse().ok()?;
+        let end = digits.checked_add(len)?;
+        segments.push(rest.get(digits..end)?);
+        rest = &rest[end..];
+    }
+    if segments
+        .last()
+        .is_some_and(|s| s.len() == 17 && s.starts_with('h'))
+    {
+        segments.pop();
+    }
+    if segments.is_empty() {
+        return None;
+    }
+
+    let name = segments
+        .iter()
+        .map(|s| {
+            unescape(
+                s.strip_prefix('_')
+                    .filter(|s| s.starts_with('$'))
+                    .unwrap_or(s),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("::");
+    Some(name)
+}
+
+/// Undo the `$..$` escapes legacy Rust mangling uses for punctuation
+fn unescape(segment: &str) -> String {
+    const ESCAPES: &[(&str, &str)] = &[
+        ("$LT$", "<"),
+        ("$GT$", ">"),
+        ("$RF$", "&"),
+        ("$BP$", "*"),
+        ("$LP$", "("),
+        ("$RP$", ")"),
+        ("$C$", ","),
+        ("$SP$", "@"),
+        ("$u20$", " "),
+        ("$u27$", "'"),
+        ("$u5b$", "["),
+        ("$u5d$", "]"),
+        ("$u7b$", "{"),
+        ("$u7d$", "}"),
+        ("$u7e$", "~"),
+    ];
+
+    let mut text = segment.replace("..", "::");
+    for (escape, c) in ESCAPES {
+        text = text.replace(escape, c);
+    }
+    text
+}
+
+/// Replace generic arguments nested past `GENERICS_DEPTH` with `..`,
+/// e.g. `Cache<HashMap<String, Vec<u8>>>::get` to `Cache<HashMap<..>>::get`
+fn collapse_generics(name: &str) -> Option<String> {
+    let mut output = String::with_capacity(name.len());
+    let mut depth = 0;
+    let mut collapsed = false;
+
+    for c in name.chars() {
+        match c {
+            '<' => {
+                depth += 1;
+                if depth <= GENERICS_DEPTH + 1 {
+                    output.push('<');
+                }
+                if depth == GENERICS_DEPTH + 1 {
+                    output.push_str("..");
+                    collapsed = true;
+                }
+            }
+            '>' if depth > 0 => {
+                if depth <= GENERICS_DEPTH + 1 {
+                    output.push('>');
+                }
+                depth -= 1;
+            }
+            _ if depth <= GENERICS_DEPTH => output.push(c),
+            _ => {}
+        }
+    }
+
+    collapsed.then_some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names() {
+        let cases = [
+            // Generics nested past one level collapse
+            ("Cache<String>::get", "Cache<String>::get"),
+            (
+                "Cache<HashMap<String, Vec<u8>>>::get",
+                "Cache<HashMap<..>>::get",
+            ),
+            ("Pair<Vec<u8>, Option<u8>>::new", "Pair<Vec<..>, Option<..>>::new"),
+            // Closures and hashes
+            (
+                "_ZN3app6Parser5parse28_$u7b$$u7b$closure$u7d$$u7d$17h0123456789abcdefE",
+                "app::Parser::parse::{{closure}}",
+            ),
+            ("app::Parser::parse::{{closure}}::h0123456789abcdef", "app::Parser::parse::{{closure}}"),
+            // Trait impl paths
+            (
+                "_ZN54_$LT$app..Config$u20$as$u20$core..default..Default$GT$7default17h0123456789abcdefE",
+                "<app::Config as core::default::Default>::default",
+            ),
+            (
+                "<Vec<Option<T>> as Extend<T>>::extend",
+                "<Vec<..> as Extend<..>>::extend",
+            ),
+            // Builder hashes and Go receivers
+            ("parse_node#a1b2c3d4", "parse_node"),
+            ("server.(*Server).Handle", "server.Server.Handle"),
+            ("plain::path", "plain::path"),
+        ];
+        for (id, expected) in cases {
+            assert_eq!(name(id), expected, "{}", id);
+        }
+    }
+
+    #[test]
+    fn unmangled_ids_are_borrowed() {
+        assert!(matches!(name("Parser::parse"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn mangled_lengths_past_the_end() {
+        assert_eq!(demangle("_ZN3app9ParserE"), None);
+        assert_eq!(demangle(&format!("_ZN1a{}bE", usize::MAX)), None);
+    }
+
+    type Glyph = fn(Glyphs) -> &'static str;
+
+    #[test]
+    fn ascii_glyphs() {
+        let glyphs: [(Glyph, &str, &str); 4] = [
+            (Glyphs::arrow, "→", "->"),
+            (Glyphs::check_mark, "✓", "[ok]"),
+            (Glyphs::cross, "✗", "[failed]"),
+            (Glyphs::ellipsis, "…", "..."),
+        ];
+        for (glyph, unicode, ascii) in glyphs {
+            assert_eq!(glyph(Glyphs::Ascii), ascii);
+            assert_eq!(glyph(Glyphs::Unicode), unicode);
+        }
+    }
+}
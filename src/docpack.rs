@@ -1,24 +1,98 @@
-use crate::models::{Documentation, Manifest, Symbol};
+use crate::config::{self, SearchConfig};
+use crate::index::PackIndex;
+use crate::metrics::{self, Metrics};
+use crate::models::{Asset, Documentation, Example, Manifest, Symbol};
+use crate::query::Query;
+use crate::text;
+use crate::trace::ProfileStats;
 use anyhow::{Context, Result};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
+use std::sync::{Mutex, MutexGuard, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use zip::read::ZipFile;
+use zip::result::{ZipError, ZipResult};
 use zip::ZipArchive;
 
 pub struct Docpack {
     pub manifest: Manifest,
     pub symbols: Vec<Symbol>,
-    docs_cache: HashMap<String, Documentation>,
-    archive: ZipArchive<File>,
+    /// Metrics cached in the docpack by `recompute-metrics`, keyed by symbol ID
+    metrics_cache: HashMap<String, Metrics>,
+    /// Runtime profile stored by `profile --save`, keyed by symbol ID
+    profile: HashMap<String, ProfileStats>,
+    docs_cache: RwLock<HashMap<String, Documentation>>,
+    /// Idle archive handles; concurrent readers each take one, opening more as needed
+    archives: Mutex<Vec<ZipArchive<File>>>,
+    path: String,
+    /// Password for encrypted entries
+    key: Option<String>,
+    /// Symbol indices and normalized IDs keyed by the last segment of the
+    /// normalized ID, for resolving frames and calls by name. Built on first
+    /// use, so `symbols` must not be added to or filtered after that.
+    frame_index: OnceLock<HashMap<String, Vec<(usize, String)>>>,
+    /// Index of each symbol ID's first symbol, built on first use like `frame_index`
+    id_index: OnceLock<HashMap<String, usize>>,
+    /// Indices of the test symbols, built on first use like `frame_index`
+    test_symbols: OnceLock<Vec<usize>>,
+    /// Calls between symbols, built on first use like `frame_index`
+    call_graph: OnceLock<CallGraph>,
 }
 
+/// Calls read from every symbol's snippet, as indices into `Docpack::symbols`
+#[derive(Default)]
+pub struct CallGraph {
+    /// The symbols each symbol calls
+    pub callees: Vec<Vec<CallEdge>>,
+    /// The symbols calling each symbol
+    pub callers: Vec<Vec<CallEdge>>,
+}
+
+/// One end of a call between two symbols
+#[derive(Debug, Clone)]
+pub struct CallEdge {
+    /// Index into `Docpack::symbols` of the symbol at the other end
+    pub symbol: usize,
+    /// Lines in the caller's file where the calls are made
+    pub lines: Vec<usize>,
+    /// Number of calls, counting each one on a shared line
+    pub count: usize,
+}
+
+/// A caller or callee of a symbol, with where the calls are made
+pub struct Call<'a> {
+    pub symbol: &'a Symbol,
+    /// As in `CallEdge`
+    pub lines: Vec<usize>,
+    pub count: usize,
+}
+
+/// A symbol found by `Docpack::search_symbols`
+pub struct SearchMatch {
+    pub symbol: Symbol,
+    pub doc: Documentation,
+    /// The alias that matched, when the keyword itself didn't
+    pub via_alias: Option<String>,
+    /// Documentation or source around the match
+    pub context: Option<MatchContext>,
+    /// Relevance; results are sorted by it, highest first
+    pub score: u32,
+}
+
+/// How much a search term found in each field adds to a match's score
+const NAME_WEIGHT: u32 = 8;
+const SIGNATURE_WEIGHT: u32 = 4;
+const DOC_WEIGHT: u32 = 2;
+const SNIPPET_WEIGHT: u32 = 1;
+
 impl Docpack {
     pub fn open(path: &str) -> Result<Self> {
         let file = File::open(path).context("Failed to open docpack file")?;
         let mut archive = ZipArchive::new(file).context("Failed to read docpack as ZIP archive")?;
 
-        // Read manifest
-        let manifest = {
+        // Read manifest; it stays readable in encrypted docpacks
+        let manifest: Manifest = {
             let mut manifest_file = archive
                 .by_name("manifest.json")
                 .context("manifest.json not found in docpack")?;
@@ -27,49 +101,191 @@ impl Docpack {
             serde_json::from_str(&content).context("Failed to parse manifest.json")?
         };
 
+        let key = config::docpack_key()?;
+        if manifest.encrypted && key.is_none() {
+            anyhow::bail!(
+                "{} is encrypted; set {} or `key` under [encryption] in {}",
+                path,
+                config::KEY_ENV,
+                config::default_path()?.display()
+            );
+        }
+        let key = key.as_deref();
+
         // Read symbols
         let symbols = {
-            let mut symbols_file = archive
-                .by_name("symbols.json")
-                .context("symbols.json not found in docpack")?;
+            let mut symbols_file = match read_entry(&mut archive, "symbols.json", key) {
+                Ok(file) => file,
+                Err(ZipError::InvalidPassword) => {
+                    anyhow::bail!("Wrong key for encrypted docpack {}", path)
+                }
+                Err(e) => return Err(e).context("symbols.json not found in docpack"),
+            };
             let mut content = String::new();
             symbols_file.read_to_string(&mut content)?;
             serde_json::from_str(&content).context("Failed to parse symbols.json")?
         };
 
+        // Read cached metrics, if the docpack has them
+        let metrics_cache = match read_entry(&mut archive, "metrics.json", key) {
+            Ok(mut metrics_file) => {
+                let mut content = String::new();
+                metrics_file.read_to_string(&mut content)?;
+                serde_json::from_str(&content).context("Failed to parse metrics.json")?
+            }
+            Err(ZipError::FileNotFound) => HashMap::new(),
+            Err(e) => return Err(e).context("Failed to read metrics.json"),
+        };
+
+        // Read the stored runtime profile, if the docpack has one
+        let profile = match read_entry(&mut archive, "profile.json", key) {
+            Ok(mut profile_file) => {
+                let mut content = String::new();
+                profile_file.read_to_string(&mut content)?;
+                serde_json::from_str(&content).context("Failed to parse profile.json")?
+            }
+            Err(ZipError::FileNotFound) => HashMap::new(),
+            Err(e) => return Err(e).context("Failed to read profile.json"),
+        };
+
         Ok(Docpack {
             manifest,
             symbols,
-            docs_cache: HashMap::new(),
-            archive,
+            metrics_cache,
+            profile,
+            docs_cache: RwLock::new(HashMap::new()),
+            archives: Mutex::new(vec![archive]),
+            path: path.to_string(),
+            key: key.map(str::to_string),
+            frame_index: OnceLock::new(),
+            id_index: OnceLock::new(),
+            test_symbols: OnceLock::new(),
+            call_graph: OnceLock::new(),
         })
     }
 
-    pub fn get_documentation(&mut self, doc_id: &str) -> Result<Documentation> {
-        if let Some(doc) = self.docs_cache.get(doc_id) {
+    pub fn get_documentation(&self, doc_id: &str) -> Result<Documentation> {
+        if let Some(doc) = read_lock(&self.docs_cache).get(doc_id) {
             return Ok(doc.clone());
         }
 
         let doc_path = format!("docs/{}.json", doc_id);
-        let mut doc_file = self
-            .archive
-            .by_name(&doc_path)
-            .context(format!("Documentation file {} not found", doc_path))?;
+        let content = match self.read_text(&doc_path) {
+            Ok(content) => content,
+            // Index-only packs made by `prune-docs` have no documentation entries
+            Err(ZipError::FileNotFound) if self.manifest.stripped.iter().any(|s| s == "docs") => {
+                return Ok(Documentation {
+                    symbol: doc_id.to_string(),
+                    ..Default::default()
+                })
+            }
+            Err(e) => return Err(e).context(format!("Documentation file {} not found", doc_path)),
+        };
 
-        let mut content = String::new();
-        doc_file.read_to_string(&mut content)?;
         let doc: Documentation =
             serde_json::from_str(&content).context(format!("Failed to parse {}", doc_path))?;
 
-        self.docs_cache.insert(doc_id.to_string(), doc.clone());
+        write_lock(&self.docs_cache).insert(doc_id.to_string(), doc.clone());
         Ok(doc)
     }
 
+    /// Read the usage examples stored for a symbol.
+    /// Returns an empty list when the docpack has no examples for it.
+    pub fn get_examples(&self, doc_id: &str) -> Result<Vec<Example>> {
+        let examples_path = format!("examples/{}.json", doc_id);
+        let content = match self.read_text(&examples_path) {
+            Ok(content) => content,
+            Err(ZipError::FileNotFound) => return Ok(Vec::new()),
+            Err(e) => return Err(e).context(format!("Failed to read {}", examples_path)),
+        };
+
+        serde_json::from_str(&content).context(format!("Failed to parse {}", examples_path))
+    }
+
+    /// Read the inventory of non-code files.
+    /// Returns an empty list when the docpack has no `assets.json`.
+    pub fn get_assets(&self) -> Result<Vec<Asset>> {
+        let content = match self.read_text("assets.json") {
+            Ok(content) => content,
+            Err(ZipError::FileNotFound) => return Ok(Vec::new()),
+            Err(e) => return Err(e).context("Failed to read assets.json"),
+        };
+
+        serde_json::from_str(&content).context("Failed to parse assets.json")
+    }
+
+    /// Read a source file embedded under `sources/` in the docpack.
+    /// Returns `None` when the docpack doesn't embed that file.
+    pub fn get_source_file(&self, file: &str) -> Result<Option<String>> {
+        let source_path = format!("sources/{}", file.trim_start_matches("./"));
+        match self.read_text(&source_path) {
+            Ok(content) => Ok(Some(content)),
+            Err(ZipError::FileNotFound) => Ok(None),
+            Err(e) => Err(e).context(format!("Failed to read {}", source_path)),
+        }
+    }
+
+    /// Read an archive entry as text using an idle archive handle, or a new one
+    /// when every handle is in use
+    fn read_text(&self, name: &str) -> ZipResult<String> {
+        let pooled = lock(&self.archives).pop();
+        let mut archive = match pooled {
+            Some(archive) => archive,
+            None => ZipArchive::new(File::open(&self.path)?)?,
+        };
+
+        let mut content = String::new();
+        let result = read_entry(&mut archive, name, self.key.as_deref())
+            .and_then(|mut file| Ok(file.read_to_string(&mut content)?));
+
+        lock(&self.archives).push(archive);
+        result.map(|_| content)
+    }
+
+    /// Metrics for a symbol, from the docpack's cache or computed from its snippet
+    pub fn metrics_for(&self, symbol: &Symbol) -> Option<Metrics> {
+        if let Some(cached) = self.metrics_cache.get(&symbol.id) {
+            return Some(cached.clone());
+        }
+        symbol.source_snippet.as_deref().map(metrics::compute)
+    }
+
+    /// Runtime profile figures stored for a symbol
+    pub fn profile_for(&self, symbol: &Symbol) -> Option<ProfileStats> {
+        self.profile.get(&symbol.id).cloned()
+    }
+
+    /// Symbols matching a name, ignoring case and treating `::`, `.`, and `/` alike.
+    /// Only the best tier of matches is returned: IDs equal to the name, else IDs
+    /// ending in it as a whole path segment, else IDs containing it.
     pub fn find_symbols_by_name(&self, name: &str) -> Vec<&Symbol> {
-        self.symbols
+        let name = normalize_name(name);
+        if name.is_empty() {
+            return Vec::new();
+        }
+        let suffix = format!("::{}", name);
+
+        let ids: Vec<(String, &Symbol)> = self
+            .symbols
             .iter()
-            .filter(|s| s.id.contains(name))
-            .collect()
+            .map(|s| (normalize_name(&s.id), s))
+            .collect();
+        let tier = |matches: &dyn Fn(&str) -> bool| -> Vec<&Symbol> {
+            ids.iter()
+                .filter(|(id, _)| matches(id))
+                .map(|(_, s)| *s)
+                .collect()
+        };
+
+        let exact = tier(&|id| id == name);
+        if !exact.is_empty() {
+            return exact;
+        }
+        let segment = tier(&|id| id.ends_with(&suffix));
+        if !segment.is_empty() {
+            return segment;
+        }
+        tier(&|id| id.contains(&name))
     }
 
     pub fn find_symbols_by_file(&self, file: &str) -> Vec<&Symbol> {
@@ -79,28 +295,355 @@ impl Docpack {
             .collect()
     }
 
-    pub fn search_symbols(&mut self, keyword: &str) -> Result<Vec<(Symbol, Documentation)>> {
-        let keyword_lower = keyword.to_lowercase();
-        let mut results = Vec::new();
+    /// Find the test functions that exercise a symbol.
+    /// Test functions whose snippets call it come first; without any, the links
+    /// stored in the docpack are used when present, and otherwise test functions
+    /// whose name mentions the symbol's name.
+    pub fn find_tests_for(&self, symbol: &Symbol) -> Vec<&Symbol> {
+        let calling: Vec<_> = self
+            .callers(symbol)
+            .into_iter()
+            .filter(|s| is_test_symbol(s))
+            .collect();
+        if !calling.is_empty() {
+            return calling;
+        }
 
-        // Clone symbols to avoid borrow checker issues
-        let symbols = self.symbols.clone();
+        if !symbol.tests.is_empty() {
+            return self
+                .symbols
+                .iter()
+                .filter(|s| symbol.tests.contains(&s.id))
+                .collect();
+        }
+
+        if is_test_symbol(symbol) {
+            return Vec::new();
+        }
 
-        for symbol in &symbols {
+        let name = short_name(&symbol.id).to_lowercase();
+        if name.len() < 3 {
+            return Vec::new();
+        }
+
+        let tests = self.test_symbols.get_or_init(|| {
+            (0..self.symbols.len())
+                .filter(|&i| is_test_symbol(&self.symbols[i]))
+                .collect()
+        });
+        tests
+            .iter()
+            .map(|&i| &self.symbols[i])
+            .filter(|s| short_name(&s.id).to_lowercase().contains(&name))
+            .collect()
+    }
+
+    /// Symbols matching a search query (see `Query`). Plain terms match a symbol
+    /// whose ID, signature, variants, or documentation contain them in some form,
+    /// ignoring case, or one of their aliases: those in `search`'s alias groups
+    /// and those the symbol's documentation lists.
+    pub fn search_symbols(&self, query: &str, search: &SearchConfig) -> Result<Vec<SearchMatch>> {
+        let query = Query::parse(query)?;
+        let aliases_for = |term: &str| search.aliases_for(term);
+        let mut results = Vec::new();
+
+        for symbol in &self.symbols {
             let doc = self.get_documentation(&symbol.doc_id)?;
 
-            if symbol.id.to_lowercase().contains(&keyword_lower)
-                || symbol.signature.to_lowercase().contains(&keyword_lower)
-                || doc.summary.to_lowercase().contains(&keyword_lower)
-                || doc.description.to_lowercase().contains(&keyword_lower)
-            {
-                results.push((symbol.clone(), doc));
+            let mut fields = vec![
+                symbol.id.as_str(),
+                symbol.signature.as_str(),
+                doc.summary.as_str(),
+                doc.description.as_str(),
+            ];
+            for variant in &symbol.variants {
+                fields.extend([variant.name.as_str(), variant.doc.as_str()]);
             }
+            let field_stems: Vec<Vec<String>> = fields.iter().map(|f| text::stems(f)).collect();
+
+            // A term matches a field containing it, or one containing every word of
+            // it in some form ("serialization" matches `NodeSerializer`)
+            let matches_text = |term: &str| {
+                let term_stems = text::stems(term);
+                fields
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(term))
+                    || (!term_stems.is_empty()
+                        && field_stems
+                            .iter()
+                            .any(|stems| term_stems.iter().all(|s| stems.contains(s))))
+            };
+            let documented_alias = |term: &str| {
+                doc.aliases
+                    .iter()
+                    .find(|alias| alias.to_lowercase().contains(term))
+                    .cloned()
+            };
+            // The alias a term matched through, if the term itself didn't match
+            let via_alias = |term: &str| {
+                let aliases = aliases_for(term);
+                std::iter::once(term)
+                    .chain(aliases.iter().map(String::as_str))
+                    .find_map(documented_alias)
+                    .or_else(|| aliases.into_iter().find(|alias| matches_text(alias)))
+            };
+
+            let matches_term = |term: &str| matches_text(term) || via_alias(term).is_some();
+            if !query.matches(symbol, &doc, &matches_term) {
+                continue;
+            }
+
+            // A lone term reports the alias it matched through and where it matched
+            let (via_alias, context) = match query.as_term() {
+                Some(term) if matches_text(term) => (None, match_context(symbol, &doc, term)),
+                Some(term) => {
+                    let alias = via_alias(term);
+                    let context = alias
+                        .as_deref()
+                        .and_then(|alias| match_context(symbol, &doc, alias));
+                    (alias, context)
+                }
+                None => (
+                    None,
+                    query
+                        .required_terms()
+                        .into_iter()
+                        .find_map(|term| match_context(symbol, &doc, term)),
+                ),
+            };
+
+            // Terms matched only through an alias count half
+            let score = query
+                .scored_terms()
+                .into_iter()
+                .map(|term| match term_score(symbol, &doc, term) {
+                    0 => aliases_for(term)
+                        .iter()
+                        .map(|alias| term_score(symbol, &doc, alias) / 2)
+                        .max()
+                        .unwrap_or(0),
+                    score => score,
+                })
+                .sum();
+
+            results.push(SearchMatch {
+                symbol: symbol.clone(),
+                doc,
+                via_alias,
+                context,
+                score,
+            });
         }
 
+        // Stable, so equally relevant symbols keep their docpack order
+        results.sort_by_key(|result| std::cmp::Reverse(result.score));
         Ok(results)
     }
 
+    /// Map a runtime frame (function name plus optional source location) to a symbol.
+    /// A known file and line wins; otherwise the most specific ID matching the name is used.
+    pub fn resolve_frame(
+        &self,
+        name: &str,
+        file: Option<&str>,
+        line: Option<usize>,
+    ) -> Option<&Symbol> {
+        self.resolve_frame_index(name, file, line)
+            .map(|i| &self.symbols[i])
+    }
+
+    /// Index into `symbols` of the symbol `resolve_frame` finds
+    fn resolve_frame_index(
+        &self,
+        name: &str,
+        file: Option<&str>,
+        line: Option<usize>,
+    ) -> Option<usize> {
+        if let (Some(file), Some(line)) = (file, line) {
+            let enclosing = self
+                .symbols
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| same_file(&s.file, file) && s.line <= line)
+                .filter(|(_, s)| s.end_line.is_none_or(|end| line <= end))
+                .max_by_key(|(_, s)| s.line);
+            if let Some((i, _)) = enclosing {
+                return Some(i);
+            }
+        }
+
+        let name = normalize_frame_name(name);
+        if name.is_empty() {
+            return None;
+        }
+
+        // Any match shares the last segment, so only those symbols are compared
+        let last = name.rsplit("::").next().unwrap_or(&name);
+        self.frame_index()
+            .get(last)?
+            .iter()
+            .filter(|(_, id)| {
+                id == &name
+                    || name
+                        .strip_suffix(id.as_str())
+                        .is_some_and(|rest| rest.ends_with("::"))
+                    || id
+                        .strip_suffix(name.as_str())
+                        .is_some_and(|rest| rest.ends_with("::"))
+            })
+            .map(|(i, _)| *i)
+            .filter(|&i| file.is_none_or(|f| same_file(&self.symbols[i].file, f)))
+            .max_by_key(|&i| self.symbols[i].id.len())
+    }
+
+    fn frame_index(&self) -> &HashMap<String, Vec<(usize, String)>> {
+        self.frame_index.get_or_init(|| {
+            let mut index: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+            for (i, symbol) in self.symbols.iter().enumerate() {
+                let id = normalize_frame_name(&symbol.id);
+                if let Some(last) = id.rsplit("::").next().filter(|last| !last.is_empty()) {
+                    index.entry(last.to_string()).or_default().push((i, id));
+                }
+            }
+            index
+        })
+    }
+
+    /// Symbols called or instantiated by a symbol's source snippet, resolved by name
+    pub fn callees(&self, symbol: &Symbol) -> Vec<&Symbol> {
+        self.calls_from(symbol)
+            .into_iter()
+            .map(|call| call.symbol)
+            .collect()
+    }
+
+    /// Symbols whose source snippets call or instantiate a symbol
+    pub fn callers(&self, symbol: &Symbol) -> Vec<&Symbol> {
+        self.calls_to(symbol)
+            .into_iter()
+            .map(|call| call.symbol)
+            .collect()
+    }
+
+    /// The calls a symbol's snippet makes, one per callee, in call order
+    pub fn calls_from(&self, symbol: &Symbol) -> Vec<Call<'_>> {
+        let edges = match self.position(symbol) {
+            Some(i) => self.call_graph().callees[i].clone(),
+            None => self.resolve_calls(symbol),
+        };
+        self.calls(edges)
+    }
+
+    /// The calls made to a symbol, one per caller, most frequent caller first
+    pub fn calls_to(&self, symbol: &Symbol) -> Vec<Call<'_>> {
+        let Some(i) = self.position(symbol) else {
+            return Vec::new();
+        };
+        let mut calls = self.calls(self.call_graph().callers[i].clone());
+        calls.sort_by_key(|call| std::cmp::Reverse(call.count));
+        calls
+    }
+
+    fn calls(&self, edges: Vec<CallEdge>) -> Vec<Call<'_>> {
+        edges
+            .into_iter()
+            .map(|edge| Call {
+                symbol: &self.symbols[edge.symbol],
+                lines: edge.lines,
+                count: edge.count,
+            })
+            .collect()
+    }
+
+    /// Calls between all symbols, resolved once per docpack
+    pub fn call_graph(&self) -> &CallGraph {
+        self.call_graph.get_or_init(|| {
+            let callees: Vec<_> = self.symbols.iter().map(|s| self.resolve_calls(s)).collect();
+            let mut callers = vec![Vec::new(); self.symbols.len()];
+            for (caller, calls) in callees.iter().enumerate() {
+                for call in calls {
+                    callers[call.symbol].push(CallEdge {
+                        symbol: caller,
+                        lines: call.lines.clone(),
+                        count: call.count,
+                    });
+                }
+            }
+            CallGraph { callees, callers }
+        })
+    }
+
+    /// The symbols a symbol's snippet calls, in call order, with the lines and number of their calls
+    fn resolve_calls(&self, symbol: &Symbol) -> Vec<CallEdge> {
+        let Some(snippet) = &symbol.source_snippet else {
+            return Vec::new();
+        };
+
+        let mut callees: Vec<CallEdge> = Vec::new();
+        for (offset, call) in metrics::call_sites(snippet) {
+            // Receivers like `self.parse()` resolve by method name alone
+            let call = ["self::", "Self::", "this::", "cls::"]
+                .iter()
+                .find_map(|receiver| call.strip_prefix(receiver))
+                .unwrap_or(&call);
+            let Some(i) = self.resolve_frame_index(call, None, None) else {
+                continue;
+            };
+            let callee = &self.symbols[i];
+            if callee.id == symbol.id {
+                continue;
+            }
+
+            let line = symbol.line + offset;
+            match callees
+                .iter_mut()
+                .find(|c| self.symbols[c.symbol].id == callee.id)
+            {
+                Some(edge) => {
+                    edge.count += 1;
+                    if !edge.lines.contains(&line) {
+                        edge.lines.push(line);
+                    }
+                }
+                None => callees.push(CallEdge {
+                    symbol: i,
+                    lines: vec![line],
+                    count: 1,
+                }),
+            }
+        }
+        callees
+    }
+
+    /// Index in `symbols` of the symbol with this symbol's ID; `None` for one from elsewhere
+    fn position(&self, symbol: &Symbol) -> Option<usize> {
+        self.id_index
+            .get_or_init(|| {
+                let mut index = HashMap::with_capacity(self.symbols.len());
+                for (i, symbol) in self.symbols.iter().enumerate() {
+                    index.entry(symbol.id.clone()).or_insert(i);
+                }
+                index
+            })
+            .get(&symbol.id)
+            .copied()
+    }
+
+    /// Types (structs, enums, traits, ...) named in a symbol's signature
+    pub fn signature_types(&self, symbol: &Symbol) -> Vec<&Symbol> {
+        let words: Vec<&str> = symbol
+            .signature
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        self.symbols
+            .iter()
+            .filter(|s| s.id != symbol.id && is_type_kind(&s.kind))
+            .filter(|s| words.contains(&short_name(&s.id)))
+            .collect()
+    }
+
     pub fn get_unique_files(&self) -> Vec<String> {
         let mut files: Vec<String> = self.symbols.iter().map(|s| s.file.clone()).collect();
         files.sort();
@@ -108,3 +651,243 @@ impl Docpack {
         files
     }
 }
+
+/// Whether the docpack at `path` may have symbols matching a search for `query`, judged
+/// from its `index.json` alone. Packs without a readable index are assumed to match.
+pub fn may_contain(path: &str, query: &str) -> bool {
+    let index = (|| -> Result<PackIndex> {
+        let file = File::open(path)?;
+        let mut archive = ZipArchive::new(file)?;
+        let key = config::docpack_key()?;
+        let mut content = String::new();
+        read_entry(&mut archive, "index.json", key.as_deref())?.read_to_string(&mut content)?;
+        Ok(serde_json::from_str(&content)?)
+    })();
+
+    index.map_or(true, |index| index.may_contain(query))
+}
+
+/// Characters of context kept on each side of a search match
+const MATCH_CONTEXT_CHARS: usize = 40;
+
+/// Text around a search match, split so the match can be highlighted.
+/// Displays with the match in brackets, e.g. "…checks the [token] stream…".
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchContext {
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+impl std::fmt::Display for MatchContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]{}", self.before, self.matched, self.after)
+    }
+}
+
+/// Weighted score of one search term: name > signature > documentation > source
+/// snippet, with an exact name match counting double. A field matches when it
+/// contains the term or every word of it in some form.
+fn term_score(symbol: &Symbol, doc: &Documentation, term: &str) -> u32 {
+    let term_stems = text::stems(term);
+    let matches = |field: &str| {
+        field.to_lowercase().contains(term) || {
+            let stems = text::stems(field);
+            !term_stems.is_empty() && term_stems.iter().all(|s| stems.contains(s))
+        }
+    };
+
+    let name = symbol.id.rsplit("::").next().unwrap_or(&symbol.id);
+    let mut score = 0;
+    if name.eq_ignore_ascii_case(term) {
+        score += 2 * NAME_WEIGHT;
+    } else if matches(name) {
+        score += NAME_WEIGHT;
+    }
+    if matches(&symbol.signature) {
+        score += SIGNATURE_WEIGHT;
+    }
+    if matches(&doc.summary) || matches(&doc.description) {
+        score += DOC_WEIGHT;
+    }
+    if symbol.source_snippet.as_deref().is_some_and(matches) {
+        score += SNIPPET_WEIGHT;
+    }
+    score
+}
+
+/// A short window of a symbol's documentation or source around the first match
+/// of `keyword`. Returns `None` when only the symbol's ID or signature matched.
+pub fn match_context(symbol: &Symbol, doc: &Documentation, keyword: &str) -> Option<MatchContext> {
+    let keyword = keyword.to_ascii_lowercase();
+    if keyword.is_empty() {
+        return None;
+    }
+
+    let texts = [
+        Some(doc.summary.as_str()),
+        Some(doc.description.as_str()),
+        symbol.source_snippet.as_deref(),
+    ];
+    texts.into_iter().flatten().find_map(|text| {
+        // ASCII folding keeps byte offsets the same in both strings
+        let start = text.to_ascii_lowercase().find(&keyword)?;
+        let end = start + keyword.len();
+
+        let before: String = text[..start]
+            .chars()
+            .rev()
+            .take(MATCH_CONTEXT_CHARS)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let after: String = text[end..].chars().take(MATCH_CONTEXT_CHARS).collect();
+        let ellipsis = |truncated: bool| {
+            if truncated {
+                crate::display::ellipsis()
+            } else {
+                ""
+            }
+        };
+
+        Some(MatchContext {
+            before: format!(
+                "{}{}",
+                ellipsis(before.len() < start),
+                squash_whitespace(&before).trim_start()
+            ),
+            matched: text[start..end].to_string(),
+            after: format!(
+                "{}{}",
+                squash_whitespace(&after).trim_end(),
+                ellipsis(end + after.len() < text.len())
+            ),
+        })
+    })
+}
+
+/// Replace each run of whitespace (including newlines) with a single space
+fn squash_whitespace(text: &str) -> String {
+    let mut squashed = String::new();
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                squashed.push(' ');
+            }
+            in_space = true;
+        } else {
+            squashed.push(c);
+            in_space = false;
+        }
+    }
+    squashed
+}
+
+// A panic while holding one of these locks leaves the data consistent
+// (a cache entry or an archive handle), so poisoning is ignored
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_lock<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_lock<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Open an archive entry, decrypting it with `key` when it is encrypted
+fn read_entry<'a>(
+    archive: &'a mut ZipArchive<File>,
+    name: &str,
+    key: Option<&str>,
+) -> ZipResult<ZipFile<'a, File>> {
+    match key {
+        Some(key) => archive.by_name_decrypt(name, key.as_bytes()),
+        None => archive.by_name(name),
+    }
+}
+
+fn is_type_kind(kind: &str) -> bool {
+    matches!(
+        kind,
+        "struct" | "class" | "enum" | "trait" | "interface" | "protocol" | "type"
+    )
+}
+
+/// Whether a symbol is a test function, judged by its file path and name
+pub fn is_test_symbol(symbol: &Symbol) -> bool {
+    let file = symbol.file.to_lowercase();
+    let name = short_name(&symbol.id).to_lowercase();
+
+    file.starts_with("tests/")
+        || file.contains("/tests/")
+        || file.contains("_test.")
+        || file.contains(".test.")
+        || file.contains("/test_")
+        || symbol.id.contains("tests::")
+        || name.starts_with("test_")
+}
+
+/// The last path segment of a symbol ID (e.g. "parse" for "Parser::parse")
+fn short_name(id: &str) -> &str {
+    id.rsplit([':', '.', '/']).next().unwrap_or(id)
+}
+
+/// Case-folded symbol path with `.`, `/`, and `\` separators written as `::`
+fn normalize_name(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .replace(['.', '/', '\\'], "::")
+        .split("::")
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Canonical form of a function name as it appears in traces and profiles:
+/// path separators become "::", and generics, closures, and Rust symbol
+/// hashes are dropped (e.g. "app::Parser<T>::parse::{{closure}}::h1a2b" -> "app::Parser::parse")
+fn normalize_frame_name(name: &str) -> String {
+    let mut plain = String::new();
+    let mut depth = 0usize;
+    for c in name.trim().chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => plain.push(c),
+            _ => {}
+        }
+    }
+
+    // Drop argument lists such as "parse(self, data)"
+    if let Some(paren) = plain.find('(') {
+        plain.truncate(paren);
+    }
+
+    plain
+        .replace(['.', '/', '\\'], "::")
+        .split("::")
+        .filter(|segment| {
+            !segment.is_empty() && !segment.starts_with("{{") && !is_symbol_hash(segment)
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Whether a path segment is a legacy Rust mangling hash like "h1a2b3c4d5e6f7a8b"
+fn is_symbol_hash(segment: &str) -> bool {
+    segment.len() == 17
+        && segment.starts_with('h')
+        && segment[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Whether two paths name the same file, allowing either to be relative to a different root
+fn same_file(a: &str, b: &str) -> bool {
+    let a = a.trim_start_matches("./");
+    let b = b.trim_start_matches("./");
+    a == b || a.ends_with(&format!("/{}", b)) || b.ends_with(&format!("/{}", a))
+}
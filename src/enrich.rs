@@ -0,0 +1,90 @@
+use crate::models::{LastModified, Symbol};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Command;
+
+/// Prefix of tags that record an issue or PR referenced by the symbol's history
+pub const REF_TAG_PREFIX: &str = "ref:";
+
+/// Issue and PR numbers referenced by commit messages that touched a symbol,
+/// e.g. "#123". Uses the symbol's line range when known, otherwise its whole file.
+pub fn issue_refs(repo: &Path, symbol: &Symbol) -> Result<Vec<String>> {
+    let messages = symbol_log(repo, symbol, &["--format=%s%n%b"])?;
+
+    let reference = Regex::new(r"(?:^|[^\w&/])(?:#|GH-)(\d+)\b|/(?:issues|pull)/(\d+)\b")?;
+    let numbers: BTreeSet<u64> = reference
+        .captures_iter(&messages)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .filter_map(|number| number.as_str().parse().ok())
+        .collect();
+    Ok(numbers.iter().map(|n| format!("#{}", n)).collect())
+}
+
+/// The most recent commit that touched a symbol's lines, or `None` if it has no history
+pub fn last_modified(repo: &Path, symbol: &Symbol) -> Result<Option<LastModified>> {
+    let output = symbol_log(repo, symbol, &["-1", "--format=%H%x00%an%x00%as%x00%at"])?;
+
+    let fields: Vec<_> = output
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split('\0')
+        .collect();
+    let [commit, author, date, timestamp] = fields[..] else {
+        return Ok(None);
+    };
+
+    Ok(Some(LastModified {
+        commit: commit.to_string(),
+        author: author.to_string(),
+        date: date.to_string(),
+        timestamp: timestamp.parse().unwrap_or_default(),
+    }))
+}
+
+/// Run `git log` over a symbol's line range when known, otherwise its whole file
+fn symbol_log(repo: &Path, symbol: &Symbol, options: &[&str]) -> Result<String> {
+    let mut args = vec!["log".to_string(), "--no-patch".to_string()];
+    args.extend(options.iter().map(|o| o.to_string()));
+    match symbol.end_line {
+        Some(end) if end >= symbol.line => {
+            args.push(format!("-L{},{}:{}", symbol.line, end, symbol.file))
+        }
+        _ => {
+            args.push("--".to_string());
+            args.push(symbol.file.clone());
+        }
+    }
+
+    git(repo, &args)
+}
+
+/// Replace a symbol's `ref:` tags with `refs`
+pub fn set_ref_tags(symbol: &mut Symbol, refs: &[String]) {
+    symbol.tags.retain(|tag| !tag.starts_with(REF_TAG_PREFIX));
+    symbol
+        .tags
+        .extend(refs.iter().map(|r| format!("{}{}", REF_TAG_PREFIX, r)));
+}
+
+/// Run git in `repo` and return its stdout
+fn git(repo: &Path, args: &[String]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,309 @@
+use crate::models::{Manifest, Symbol};
+use clap::ValueEnum;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Architecture diagram languages for `export-c4`
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum C4Dialect {
+    /// Structurizr DSL workspace
+    Structurizr,
+    /// C4-PlantUML component diagram
+    Plantuml,
+}
+
+/// A source file summarized as a C4 component
+struct Component<'a> {
+    file: &'a str,
+    technology: &'a str,
+    description: String,
+}
+
+/// Render the docpack as a C4 model: the project is the software system,
+/// top-level source directories are containers, and files are components
+pub fn c4(manifest: &Manifest, symbols: &[Symbol], dialect: C4Dialect) -> String {
+    let containers = group_components(symbols);
+    let name = &manifest.project.name;
+
+    let mut out = String::new();
+    match dialect {
+        C4Dialect::Structurizr => {
+            writeln!(out, "workspace \"{}\" {{", escape(name)).unwrap();
+            writeln!(out, "    model {{").unwrap();
+            writeln!(
+                out,
+                "        system = softwareSystem \"{}\" {{",
+                escape(name)
+            )
+            .unwrap();
+            for (container, components) in &containers {
+                writeln!(
+                    out,
+                    "            {} = container \"{}\" {{",
+                    identifier(container),
+                    escape(container)
+                )
+                .unwrap();
+                for component in components {
+                    writeln!(
+                        out,
+                        "                {} = component \"{}\" \"{}\" \"{}\"",
+                        identifier(component.file),
+                        escape(file_name(component.file)),
+                        escape(&component.description),
+                        escape(component.technology)
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "            }}").unwrap();
+            }
+            writeln!(out, "        }}").unwrap();
+            writeln!(out, "    }}").unwrap();
+            writeln!(out, "    views {{").unwrap();
+            writeln!(out, "        container system {{").unwrap();
+            writeln!(out, "            include *").unwrap();
+            writeln!(out, "            autolayout lr").unwrap();
+            writeln!(out, "        }}").unwrap();
+            for container in containers.keys() {
+                writeln!(out, "        component {} {{", identifier(container)).unwrap();
+                writeln!(out, "            include *").unwrap();
+                writeln!(out, "            autolayout lr").unwrap();
+                writeln!(out, "        }}").unwrap();
+            }
+            writeln!(out, "    }}").unwrap();
+            writeln!(out, "}}").unwrap();
+        }
+        C4Dialect::Plantuml => {
+            writeln!(out, "@startuml").unwrap();
+            writeln!(
+                out,
+                "!include https://raw.githubusercontent.com/plantuml-stdlib/C4-PlantUML/master/C4_Component.puml"
+            )
+            .unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "title {}", name).unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "System_Boundary(system, \"{}\") {{", escape(name)).unwrap();
+            for (container, components) in &containers {
+                writeln!(
+                    out,
+                    "    Container_Boundary({}, \"{}\") {{",
+                    identifier(container),
+                    escape(container)
+                )
+                .unwrap();
+                for component in components {
+                    writeln!(
+                        out,
+                        "        Component({}, \"{}\", \"{}\", \"{}\")",
+                        identifier(component.file),
+                        escape(file_name(component.file)),
+                        escape(component.technology),
+                        escape(&component.description)
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "    }}").unwrap();
+            }
+            writeln!(out, "}}").unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "@enduml").unwrap();
+        }
+    }
+
+    out
+}
+
+/// Render a PlantUML class diagram of the types defined in `module`'s files,
+/// with their fields, variants, methods, and inheritance or trait edges
+pub fn uml(symbols: &[Symbol], module: &str) -> String {
+    let in_module: Vec<_> = symbols.iter().filter(|s| s.file.contains(module)).collect();
+
+    let types: Vec<_> = in_module
+        .iter()
+        .filter(|s| TYPE_KINDS.contains(&s.kind.as_str()))
+        .collect();
+
+    let mut out = String::new();
+    writeln!(out, "@startuml").unwrap();
+    writeln!(out, "title {}", module).unwrap();
+    writeln!(out).unwrap();
+
+    for ty in &types {
+        let name = short_name(&ty.id);
+        let keyword = match ty.kind.as_str() {
+            "enum" => "enum",
+            "trait" | "interface" | "protocol" => "interface",
+            _ => "class",
+        };
+
+        writeln!(out, "{} {} {{", keyword, name).unwrap();
+        for variant in &ty.variants {
+            writeln!(out, "  {}", variant).unwrap();
+        }
+        for (public, field, field_type) in struct_fields(ty) {
+            let visibility = if public { '+' } else { '-' };
+            writeln!(out, "  {} {} : {}", visibility, field, field_type).unwrap();
+        }
+        for method in in_module
+            .iter()
+            .filter(|s| !TYPE_KINDS.contains(&s.kind.as_str()) && parent_name(&s.id) == Some(name))
+        {
+            let visibility = if method.is_public() { '+' } else { '-' };
+            writeln!(out, "  {} {}()", visibility, short_name(&method.id)).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    let mut edges: Vec<_> = in_module.iter().flat_map(|s| inheritance(s)).collect();
+    edges.sort();
+    edges.dedup();
+    if !edges.is_empty() {
+        writeln!(out).unwrap();
+    }
+    for edge in edges {
+        writeln!(out, "{}", edge).unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "@enduml").unwrap();
+    out
+}
+
+/// Symbol kinds drawn as classes in UML diagrams
+const TYPE_KINDS: &[&str] = &[
+    "struct",
+    "class",
+    "enum",
+    "trait",
+    "interface",
+    "protocol",
+    "union",
+];
+
+/// Named fields of a Rust struct as (is `pub`, name, type), read from its source snippet
+fn struct_fields(symbol: &Symbol) -> Vec<(bool, String, String)> {
+    if symbol.kind != "struct" {
+        return Vec::new();
+    }
+    let Some(snippet) = &symbol.source_snippet else {
+        return Vec::new();
+    };
+
+    let field = Regex::new(r"^\s*(pub(?:\([^)]*\))?\s+)?(\w+)\s*:\s*(.+?),?\s*$").unwrap();
+    snippet
+        .lines()
+        .filter_map(|line| field.captures(line))
+        .map(|caps| {
+            let public = caps.get(1).is_some_and(|vis| vis.as_str().trim() == "pub");
+            (public, caps[2].to_string(), caps[3].to_string())
+        })
+        .collect()
+}
+
+/// Inheritance and trait-implementation edges declared by a symbol's signature:
+/// `impl Trait for Type`, `class Child(Base)`, and `class A extends B implements C`
+fn inheritance(symbol: &Symbol) -> Vec<String> {
+    let signature = symbol.signature.trim();
+    let mut edges = Vec::new();
+
+    let rust_impl = Regex::new(r"^impl(?:<.*?>)?\s+([\w:]+)(?:<.*?>)?\s+for\s+([\w:]+)").unwrap();
+    let python_class = Regex::new(r"^class\s+(\w+)\s*\(([^)]*)\)").unwrap();
+    let java_class = Regex::new(
+        r"\bclass\s+(\w+)(?:<.*?>)?(?:\s+extends\s+([\w.]+))?(?:\s+implements\s+([\w.,\s]+))?",
+    )
+    .unwrap();
+
+    if let Some(caps) = rust_impl.captures(signature) {
+        edges.push(format!(
+            "{} <|.. {}",
+            short_name(&caps[1]),
+            short_name(&caps[2])
+        ));
+    } else if let Some(caps) = python_class.captures(signature) {
+        for base in caps[2].split(',').map(str::trim) {
+            if !base.is_empty() && base != "object" && !base.contains('=') {
+                edges.push(format!("{} <|-- {}", short_name(base), &caps[1]));
+            }
+        }
+    } else if let Some(caps) = java_class.captures(signature) {
+        if let Some(base) = caps.get(2) {
+            edges.push(format!("{} <|-- {}", short_name(base.as_str()), &caps[1]));
+        }
+        if let Some(interfaces) = caps.get(3) {
+            for interface in interfaces.as_str().split(',').map(str::trim) {
+                if !interface.is_empty() {
+                    edges.push(format!("{} <|.. {}", short_name(interface), &caps[1]));
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+/// The last path segment of an ID, e.g. "parse" for "Parser::parse"
+fn short_name(id: &str) -> &str {
+    id.rsplit([':', '.']).next().unwrap_or(id)
+}
+
+/// The segment before the last one, e.g. "Parser" for "app::Parser::parse"
+fn parent_name(id: &str) -> Option<&str> {
+    let mut segments = id.rsplit([':', '.']).filter(|s| !s.is_empty());
+    segments.next();
+    segments.next()
+}
+
+/// Group files by their top-level directory, describing each by its symbol kinds
+fn group_components(symbols: &[Symbol]) -> BTreeMap<&str, Vec<Component<'_>>> {
+    let mut kinds_by_file: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+    for symbol in symbols {
+        *kinds_by_file
+            .entry(&symbol.file)
+            .or_default()
+            .entry(&symbol.kind)
+            .or_default() += 1;
+    }
+
+    let mut containers: BTreeMap<&str, Vec<Component>> = BTreeMap::new();
+    for (file, kinds) in kinds_by_file {
+        let container = match file.split_once('/') {
+            Some((dir, _)) => dir,
+            None => ".",
+        };
+        let description = kinds
+            .iter()
+            .map(|(kind, count)| format!("{}: {}", kind, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        containers.entry(container).or_default().push(Component {
+            file,
+            technology: file.rsplit_once('.').map_or("", |(_, ext)| ext),
+            description,
+        });
+    }
+
+    containers
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// A diagram identifier derived from a path, e.g. "src/parser.rs" -> "src_parser_rs"
+fn identifier(path: &str) -> String {
+    let id: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.starts_with(|c: char| c.is_ascii_digit()) || id.chars().all(|c| c == '_') {
+        format!("c_{}", id)
+    } else {
+        id
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,89 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Messages shipped with localdoc; other locales fall back to these
+const ENGLISH: &str = include_str!("../locales/en.ftl");
+
+/// Environment variable choosing the status-line language, ahead of config and `LANG`
+pub const LANG_ENV: &str = "LOCALDOC_LANG";
+
+static CATALOG: OnceLock<FluentBundle<FluentResource>> = OnceLock::new();
+
+/// Format a catalog message, e.g. `t!("no-symbol-match", name = query)`
+macro_rules! t {
+    ($id:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::message(
+            $id,
+            &[$((stringify!($name), fluent_bundle::FluentValue::from($value))),*],
+        )
+    };
+}
+pub(crate) use t;
+
+/// The message `id` in the user's locale, or in English if it isn't translated
+pub fn message(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let bundle = CATALOG.get_or_init(load);
+    let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) else {
+        return id.to_string();
+    };
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    let mut errors = Vec::new();
+    bundle
+        .format_pattern(pattern, Some(&fluent_args), &mut errors)
+        .into_owned()
+}
+
+/// English, overridden by `locales/<locale>.ftl` in the config directory when present
+fn load() -> FluentBundle<FluentResource> {
+    let locale = requested_locale();
+    let langid: LanguageIdentifier = locale
+        .as_deref()
+        .and_then(|l| l.parse().ok())
+        .unwrap_or_else(|| "en".parse().expect("valid language tag"));
+
+    let mut bundle = FluentBundle::new_concurrent(vec![langid.clone()]);
+    // Unicode isolation marks around arguments show up as stray glyphs in terminals
+    bundle.set_use_isolating(false);
+    let english = FluentResource::try_new(ENGLISH.to_string()).expect("valid English catalog");
+    bundle
+        .add_resource(english)
+        .expect("English catalog has unique ids");
+
+    if let Some(translation) = translation(&langid) {
+        bundle.add_resource_overriding(translation);
+    }
+    bundle
+}
+
+/// The user's catalog for a locale, trying e.g. `pt-BR.ftl` then `pt.ftl`.
+/// Entries that fail to parse are skipped so the rest still apply.
+fn translation(langid: &LanguageIdentifier) -> Option<FluentResource> {
+    let dir = dirs::config_dir()?.join("localdoc").join("locales");
+    [langid.to_string(), langid.language.to_string()]
+        .iter()
+        .map(|name| dir.join(format!("{}.ftl", name)))
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|source| FluentResource::try_new(source).unwrap_or_else(|(partial, _)| partial))
+}
+
+/// `LOCALDOC_LANG`, then `locale` under `[ui]` in the config, then the POSIX
+/// locale variables, e.g. `de_DE.UTF-8` as `de-DE`
+fn requested_locale() -> Option<String> {
+    let from_env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let locale = from_env(LANG_ENV)
+        .or_else(|| crate::config::load(None).ok()?.ui.locale)
+        .or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .into_iter()
+                .find_map(from_env)
+        })?;
+
+    let tag = locale.split(['.', '@']).next()?.replace('_', "-");
+    (!tag.is_empty() && tag != "C" && tag != "POSIX").then_some(tag)
+}
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::Path;
+
+/// Ignore file read from the current directory
+pub const FILE_NAME: &str = ".localdocignore";
+
+/// Source paths left out of analyses, e.g. generated code and vendored dependencies.
+/// Patterns follow `.gitignore`: `*` and `?` stay within a path segment, `**` spans
+/// segments, a leading or inner `/` anchors the pattern to the repository root, a
+/// trailing `/` matches only directories, and `!` re-includes what an earlier
+/// pattern excluded.
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    /// Compiled patterns in file order, each with whether it re-includes
+    patterns: Vec<(Regex, bool)>,
+}
+
+impl IgnoreRules {
+    /// Parse ignore-file lines; blank lines and `#` comments are skipped
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut patterns = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (pattern, negated) = match line.strip_prefix('!') {
+                Some(pattern) => (pattern, true),
+                None => (line, false),
+            };
+            patterns.push((compile(pattern)?, negated));
+        }
+        Ok(Self { patterns })
+    }
+
+    /// Whether a symbol's file (relative to the repository root) is excluded
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        let mut ignored = false;
+        for (pattern, negated) in &self.patterns {
+            if pattern.is_match(path) {
+                ignored = !negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Translate one gitignore-style pattern into a regex over relative paths
+fn compile(pattern: &str) -> Result<Regex> {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+
+    let mut regex = String::from(if anchored { "^" } else { "(^|/)" });
+    let chars: Vec<char> = trimmed.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                // "**/" also matches no directories at all
+                if chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    // The pattern names the path itself or a directory above it
+    regex.push_str(if dir_only { "/" } else { "($|/)" });
+
+    Regex::new(&regex).context(format!("Invalid ignore pattern '{}'", pattern))
+}
+
+/// Rules from `paths` under `[ignore]` in the config, then `.localdocignore`
+pub fn load() -> Result<IgnoreRules> {
+    let mut lines = crate::config::load(None)?.ignore.paths;
+
+    let path = Path::new(FILE_NAME);
+    if path.exists() {
+        let content =
+            std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        lines.extend(content.lines().map(str::to_string));
+    }
+
+    IgnoreRules::parse(lines.iter().map(String::as_str))
+}
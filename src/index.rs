@@ -0,0 +1,206 @@
+use crate::models::{Documentation, Symbol};
+use crate::text;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Bloom filter of the trigrams in a docpack's searchable text, stored as `index.json`.
+/// Lets a search across many docpacks skip those that cannot match without
+/// loading their symbols or documentation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackIndex {
+    /// Number of hash functions
+    pub hashes: u32,
+    /// Filter bits, hex encoded, least significant bit of each byte first
+    pub bits: String,
+}
+
+/// Target false positive rate of the filter
+const FALSE_POSITIVE_RATE: f64 = 0.01;
+
+impl PackIndex {
+    /// Index the text `Docpack::search_symbols` matches against: symbol IDs,
+    /// signatures, variants, and documentation summaries, descriptions, and aliases
+    pub fn build<'a>(
+        symbols: &[Symbol],
+        docs: impl IntoIterator<Item = &'a Documentation>,
+    ) -> PackIndex {
+        let mut trigrams = BTreeSet::new();
+        // Stems too, so queries matching another form of a word aren't ruled out
+        let mut add = |text: &str| {
+            trigrams.extend(trigrams_of(&text.to_lowercase()));
+            for stem in text::stems(text) {
+                trigrams.extend(trigrams_of(&stem));
+            }
+        };
+
+        for symbol in symbols {
+            add(&symbol.id);
+            add(&symbol.signature);
+            for variant in &symbol.variants {
+                add(&variant.name);
+                add(&variant.doc);
+            }
+        }
+        for doc in docs {
+            add(&doc.summary);
+            add(&doc.description);
+            for alias in &doc.aliases {
+                add(alias);
+            }
+        }
+
+        // Optimal size and hash count for the target false positive rate
+        let n = trigrams.len().max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = (-n * FALSE_POSITIVE_RATE.ln() / (ln2 * ln2)).ceil() as usize;
+        let mut bytes = vec![0u8; bit_count.div_ceil(8).max(1)];
+        let hashes = ((bytes.len() * 8) as f64 / n * ln2)
+            .round()
+            .clamp(1.0, 16.0) as u32;
+
+        for trigram in &trigrams {
+            for bit in bit_positions(trigram, hashes, bytes.len() * 8) {
+                bytes[bit / 8] |= 1 << (bit % 8);
+            }
+        }
+
+        PackIndex {
+            hashes,
+            bits: bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+        }
+    }
+
+    /// Whether the docpack may have a symbol matching `query`. A `false` is certain;
+    /// queries shorter than three characters always return `true`.
+    pub fn may_contain(&self, query: &str) -> bool {
+        let Some(bytes) = decode_hex(&self.bits) else {
+            return true;
+        };
+        if bytes.is_empty() {
+            return true;
+        }
+
+        let contains = |text: &str| {
+            trigrams_of(text).iter().all(|trigram| {
+                bit_positions(trigram, self.hashes, bytes.len() * 8)
+                    .all(|bit| bytes[bit / 8] & (1 << (bit % 8)) != 0)
+            })
+        };
+
+        // Search matches the query as written or any form of each of its words
+        let stems = text::stems(query);
+        contains(&query.to_lowercase()) || (!stems.is_empty() && stems.iter().all(|s| contains(s)))
+    }
+}
+
+fn trigrams_of(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+/// Bit positions for an item via double hashing of two FNV-1a variants,
+/// stable across platforms and Rust versions
+fn bit_positions(item: &str, hashes: u32, bit_count: usize) -> impl Iterator<Item = usize> {
+    let h1 = fnv1a(item.as_bytes(), 0xcbf29ce484222325);
+    let h2 = fnv1a(item.as_bytes(), 0x84222325cbf29ce4) | 1;
+    (0..hashes as u64)
+        .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count as u64) as usize)
+}
+
+fn fnv1a(bytes: &[u8], seed: u64) -> u64 {
+    bytes.iter().fold(seed, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(id: &str, signature: &str) -> Symbol {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "kind": "function",
+            "file": "src/lib.rs",
+            "line": 1,
+            "signature": signature,
+            "doc_id": id,
+        }))
+        .expect("valid symbol")
+    }
+
+    fn index() -> PackIndex {
+        let symbols = [
+            symbol("Parser::parse_node", "pub fn parse_node(&mut self) -> Node"),
+            symbol("HttpClient::send", "pub fn send(&self, request: Request)"),
+        ];
+        let docs = [Documentation {
+            summary: "Serialize a configuration file".to_string(),
+            aliases: vec!["dictionary".to_string()],
+            ..Default::default()
+        }];
+        PackIndex::build(&symbols, &docs)
+    }
+
+    #[test]
+    fn every_indexed_substring_may_match() {
+        let index = index();
+        let indexed = [
+            "parser::parse_node",
+            "pub fn parse_node(&mut self) -> node",
+            "httpclient::send",
+            "serialize a configuration file",
+            "dictionary",
+        ];
+        for text in indexed {
+            let chars: Vec<char> = text.chars().collect();
+            for start in 0..chars.len() {
+                for end in start + 3..=chars.len() {
+                    let query: String = chars[start..end].iter().collect();
+                    assert!(index.may_contain(&query), "'{}' was ruled out", query);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn other_case_and_word_forms_may_match() {
+        let index = index();
+        for query in [
+            "PARSE_NODE",
+            "HttpClient",
+            "serialization",
+            "configurations",
+        ] {
+            assert!(index.may_contain(query), "'{}' was ruled out", query);
+        }
+    }
+
+    #[test]
+    fn terms_not_indexed_are_ruled_out() {
+        let index = index();
+        for query in ["websocket", "zebra", "quaternion"] {
+            assert!(!index.may_contain(query), "'{}' may match", query);
+        }
+    }
+
+    #[test]
+    fn short_queries_and_unreadable_filters_may_match() {
+        assert!(index().may_contain("zq"));
+        let broken = PackIndex {
+            hashes: 3,
+            bits: "not hex".to_string(),
+        };
+        assert!(broken.may_contain("websocket"));
+    }
+}
@@ -1,12 +1,35 @@
+mod baseline;
+mod config;
+mod display;
 mod docpack;
+mod enrich;
+mod export;
+mod i18n;
+mod ignore;
+mod index;
 mod mcp;
+mod metrics;
 mod models;
+mod packer;
+mod picker;
+mod query;
+mod render;
+mod reports;
+mod scan;
+mod shell;
+mod state;
+mod text;
+mod trace;
 
 use anyhow::Result;
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Shell};
 use colored::*;
-use docpack::Docpack;
+use docpack::{Call, Docpack};
+use i18n::t;
+use render::OutputFormat;
+use reports::*;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -14,6 +37,18 @@ use std::path::PathBuf;
 #[command(about = "Query and inspect docpack documentation", long_about = None)]
 #[command(version)]
 struct Cli {
+    /// Output format for command results
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    /// Print symbol IDs as stored, without demangling or shortening them
+    #[arg(long, global = true)]
+    raw_ids: bool,
+
+    /// Plain ASCII text output without decorative rules, for screen readers and limited fonts
+    #[arg(long, global = true)]
+    ascii: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -32,6 +67,22 @@ enum Commands {
         #[command(subcommand)]
         query_type: QueryType,
     },
+    /// Explore a docpack interactively, loading it once for many commands
+    Shell {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+    },
+    /// Pick a symbol with an interactive fuzzy finder and print its ID
+    Pick {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Show the chosen symbol's documentation instead of its ID
+        #[arg(long)]
+        show: bool,
+        /// Text to start the search with
+        #[arg(long, default_value = "")]
+        query: String,
+    },
     /// Install a docpack from the commons
     Install {
         /// Docpack identifier in format username:reponame
@@ -61,28 +112,343 @@ enum Commands {
         /// Second docpack path or name
         docpack2: String,
     },
+    /// Run pipeline checks on a docpack and report them together; exits with 1 if any fail
+    Ci {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Fail below this percentage of symbols exercised by tests
+        #[arg(long, value_name = "PERCENT")]
+        min_test_coverage: Option<f64>,
+        /// Fail below this percentage of public symbols with a documented summary
+        #[arg(long, value_name = "PERCENT")]
+        min_doc_coverage: Option<f64>,
+        /// Fail if public symbols were removed or changed signature since this docpack
+        #[arg(long)]
+        baseline: Option<String>,
+    },
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
         shell: Shell,
     },
-    /// Start an MCP server for AI agent access
-    Serve,
+    /// Start an MCP server on stdin/stdout (or HTTP) for AI agent access
+    #[command(visible_alias = "serve")]
+    Mcp {
+        /// Serve over Streamable HTTP at this address (e.g. 127.0.0.1:8080) instead of stdio
+        #[arg(long)]
+        http: Option<String>,
+        /// Serve docpacks from this directory instead of the default ones;
+        /// repeat to merge several, with earlier directories taking precedence
+        #[arg(long)]
+        packages_dir: Vec<PathBuf>,
+        /// Append a JSON line per request (tool, arguments, latency, result size) to this file
+        #[arg(long)]
+        log_file: Option<PathBuf>,
+        /// Tool set to advertise for a particular client (default from config, else claude)
+        #[arg(long, value_enum)]
+        profile: Option<config::McpProfile>,
+        /// Config file with tool overrides (default: config.toml in localdoc's config directory)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Summarize an MCP request log written by `mcp --log-file`
+    McpStats {
+        /// Log file written by `mcp --log-file`
+        log_file: PathBuf,
+        /// Number of most frequent arguments to show
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// List TODO/FIXME/HACK markers found in source snippets
+    Todos {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+    },
+    /// Overlay OpenTelemetry span counts and latencies onto symbols
+    TraceMap {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// OTLP/JSON trace export to read spans from
+        #[arg(long)]
+        otlp: String,
+    },
+    /// Find the functions whose string literals could have produced a log line
+    Whence {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Log message to look up
+        message: String,
+    },
+    /// Annotate a stack trace read from stdin with symbol documentation
+    ResolveTrace {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+    },
+    /// Map a sampling profile onto symbols to show self and total time
+    Profile {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Profile in collapsed-stack format ("main;run;parse 42" per line)
+        profile: String,
+        /// Store the overlay in the docpack so `query symbol` shows it
+        #[arg(long)]
+        save: bool,
+    },
+    /// List non-code files (configs, schemas, protos, ...) in a docpack
+    Assets {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Only list files in this category
+        #[arg(long)]
+        category: Option<String>,
+    },
+    /// Show license and dependency metadata for all installed docpacks
+    Licenses,
+    /// List Rust functions that use `unsafe` and the unsafe surface per file
+    Unsafe {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        #[command(flatten)]
+        check: CheckArgs,
+    },
+    /// Tag symbols whose snippets match security-sensitive patterns
+    Scan {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// JSON file with extra rules ([{"tag", "pattern", "description"}])
+        #[arg(long)]
+        rules: Option<String>,
+        /// Print findings as a SARIF log instead of a report
+        #[arg(long)]
+        sarif: bool,
+        /// Report findings without writing tags into the docpack (implied by
+        /// --check and --max-findings)
+        #[arg(long)]
+        dry_run: bool,
+        #[command(flatten)]
+        check: CheckArgs,
+    },
+    /// Record last-modified commits and issue/PR references from git history
+    Enrich {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Path to a checkout of the documented repository
+        #[arg(long)]
+        repo: PathBuf,
+    },
+    /// Rank old, complex, and undocumented code (requires `localdoc enrich`)
+    Stale {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Maximum number of symbols to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Rank public-API-reachable functions by panic and exception sources
+    Panics {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        #[command(flatten)]
+        check: CheckArgs,
+    },
+    /// Trace how errors propagate to the public API and flag swallowed errors
+    Errors {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        #[command(flatten)]
+        check: CheckArgs,
+    },
+    /// Map async entry points, sync/async boundaries, and unawaited async calls
+    AsyncMap {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        #[command(flatten)]
+        check: CheckArgs,
+    },
+    /// Show which types construct which other types in their constructors
+    Constructors {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+    },
+    /// Export the docpack's structure as a C4 architecture model
+    #[command(name = "export-c4")]
+    ExportC4 {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Diagram language to emit
+        #[arg(long, value_enum, default_value_t = export::C4Dialect::Structurizr)]
+        dialect: export::C4Dialect,
+    },
+    /// Export a PlantUML class diagram of the types in a module
+    #[command(name = "export-uml")]
+    ExportUml {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// File or directory path (or part of one) selecting the module
+        #[arg(long)]
+        module: String,
+    },
+    /// Encrypt a docpack's contents with the key from LOCALDOC_KEY or config
+    Encrypt {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Write the encrypted pack here instead of rewriting the docpack in place
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Decrypt an encrypted docpack with the key from LOCALDOC_KEY or config
+    Decrypt {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Write the decrypted pack here instead of rewriting the docpack in place
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Replace likely secrets (API keys, tokens, private keys) in stored snippets
+    Redact {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Report what would be redacted without modifying the docpack
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Strip heavy sections from a docpack to make a slim, index-only pack
+    PruneDocs {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+        /// Section to strip; repeatable (default: docs, examples, and snippets)
+        #[arg(long = "section", value_enum)]
+        sections: Vec<PruneSection>,
+        /// Write the slimmed pack here instead of rewriting the docpack in place
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Compute metrics from source snippets and cache them in the docpack
+    RecomputeMetrics {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+    },
+    /// Rebuild the index that lets cross-pack search skip non-matching docpacks
+    Reindex {
+        /// Path or name (e.g., "xandwr:localdoc") of the docpack
+        docpack: String,
+    },
+}
+
+/// Options that make an analysis usable as a CI gate
+#[derive(Args)]
+struct CheckArgs {
+    /// Exit with status 1 if the analysis finds anything
+    #[arg(long)]
+    check: bool,
+    /// Exit with status 1 if there are more than N findings (implies --check)
+    #[arg(long, value_name = "N")]
+    max_findings: Option<usize>,
+    /// Hide and don't count findings recorded in this file, so only new ones fail
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+    /// Record the current findings in the --baseline file instead of checking them
+    #[arg(long, requires = "baseline")]
+    update_baseline: bool,
+}
+
+impl CheckArgs {
+    /// Whether the run gates on its findings rather than only reporting them
+    fn is_gate(&self) -> bool {
+        self.check || self.max_findings.is_some()
+    }
+
+    /// Findings to suppress: those in the baseline file, unless it is being rewritten
+    fn known_findings(&self) -> Result<BTreeSet<String>> {
+        match &self.baseline {
+            Some(path) if !self.update_baseline => Ok(baseline::load(path)?.findings),
+            _ => Ok(BTreeSet::new()),
+        }
+    }
+
+    /// Save `findings` with --update-baseline; otherwise exit with status 1 when
+    /// more of them are new than allowed
+    fn enforce(&self, known: &BTreeSet<String>, findings: Vec<String>) -> Result<()> {
+        if let (Some(path), true) = (&self.baseline, self.update_baseline) {
+            let baseline = baseline::Baseline {
+                findings: findings.into_iter().collect(),
+            };
+            baseline.save(path)?;
+            eprintln!(
+                "{}",
+                t!(
+                    "baseline-recorded",
+                    count = baseline.findings.len(),
+                    path = path.display().to_string()
+                )
+                .green()
+            );
+            return Ok(());
+        }
+
+        let allowed = match (self.check, self.max_findings) {
+            (_, Some(max)) => max,
+            (true, None) => 0,
+            (false, None) => return Ok(()),
+        };
+        let new = findings.iter().filter(|f| !known.contains(*f)).count();
+        if new > allowed {
+            eprintln!(
+                "{}",
+                t!("check-failed", count = new, allowed = allowed).red()
+            );
+            std::process::exit(EXIT_FINDINGS);
+        }
+        Ok(())
+    }
 }
 
+/// Exit status of a `--check` that found more than it allows
+const EXIT_FINDINGS: i32 = 1;
+
+/// Exit status of any failed command, distinct from findings
+const EXIT_ERROR: i32 = 2;
+
 #[derive(Subcommand)]
 enum QueryType {
     /// List all symbol names
-    Symbols,
+    Symbols {
+        /// Only show symbols with an attribute containing this text (e.g. "deprecated")
+        #[arg(long)]
+        attr: Option<String>,
+        /// Only show symbols gated behind this cargo feature
+        #[arg(long)]
+        feature: Option<String>,
+        /// Only show symbols carrying this tag (e.g. "security:eval")
+        #[arg(long)]
+        tag: Option<String>,
+        /// Sort symbols instead of listing them in docpack order
+        #[arg(long, value_enum)]
+        sort: Option<SymbolSort>,
+    },
     /// Get full JSON entry for a specific symbol
     Symbol {
         /// Name or ID of the symbol to look up
         name: String,
+        /// Also show the symbols it calls and is called by, with their summaries
+        #[arg(long)]
+        with_callers: bool,
     },
     /// Full-text search across summary/description
     Search {
-        /// Keyword to search for
-        keyword: String,
+        /// Keyword to search for; `!!` repeats the last search of this docpack
+        /// and `!N` the Nth most recent
+        #[arg(required_unless_present = "history")]
+        keyword: Option<String>,
+        /// List this docpack's recent searches instead of searching
+        #[arg(long, conflicts_with = "keyword")]
+        history: bool,
+        /// Only show symbols gated behind this cargo feature
+        #[arg(long)]
+        feature: Option<String>,
+        /// Show each result's relevance score
+        #[arg(long)]
+        debug_score: bool,
     },
     /// List all source files referenced in the docpack
     Files,
@@ -96,7 +462,7 @@ enum QueryType {
         /// Symbol kind to filter by
         kind: String,
     },
-    /// Show only usage examples for a symbol
+    /// Show usage examples for a symbol, including any stored in the docpack
     Examples {
         /// Name or ID of the symbol
         name: String,
@@ -105,43 +471,306 @@ enum QueryType {
     Deps {
         /// Name or ID of the symbol
         name: String,
+        /// Also show the symbols it calls and is called by, with their summaries
+        #[arg(long)]
+        with_callers: bool,
+    },
+    /// Show the test functions that exercise a symbol
+    TestsFor {
+        /// Name or ID of the symbol
+        name: String,
     },
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Removable docpack sections, as recorded in the manifest's `stripped` list
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum PruneSection {
+    /// Generated documentation (`docs/`)
+    Docs,
+    /// Usage examples (`examples/`)
+    Examples,
+    /// Source snippets embedded in symbols.json
+    Snippets,
+    /// Cached metrics (`metrics.json`)
+    Metrics,
+    /// Profile overlay (`profile.json`)
+    Profile,
+    /// Non-code file inventory (`assets.json`)
+    Assets,
+}
+
+impl PruneSection {
+    fn name(self) -> &'static str {
+        match self {
+            PruneSection::Docs => "docs",
+            PruneSection::Examples => "examples",
+            PruneSection::Snippets => "snippets",
+            PruneSection::Metrics => "metrics",
+            PruneSection::Profile => "profile",
+            PruneSection::Assets => "assets",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SymbolSort {
+    /// Alphabetically by ID
+    Name,
+    /// Largest first, by lines of code
+    Loc,
+    /// Least recently modified first (requires `localdoc enrich`)
+    Stale,
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(EXIT_ERROR);
+    }
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse_from(expand_alias(std::env::args_os().collect())?);
+    let format = cli.output_format;
+    display::set_raw_ids(cli.raw_ids);
+    display::set_ascii(cli.ascii || config::load(None).is_ok_and(|c| c.ui.ascii));
 
     match cli.command {
         Commands::Inspect { docpack } => {
             let path = resolve_docpack_path(&docpack)?;
-            inspect_docpack(&path)?
+            inspect_docpack(&path, format)?
         }
         Commands::Query {
             docpack,
             query_type,
         } => {
             let path = resolve_docpack_path(&docpack)?;
-            handle_query(&path, query_type)?
+            handle_query(&path, query_type, format)?
+        }
+        Commands::Shell { docpack } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let docpack = Docpack::open(&path)?;
+            shell::run(&docpack, &config::load(None)?.search, format)?
+        }
+        Commands::Pick {
+            docpack,
+            show,
+            query,
+        } => {
+            let path = resolve_docpack_path(&docpack)?;
+            pick_symbol(&path, show, &query, format)?
         }
-        Commands::Install { package } => install_docpack(&package)?,
-        Commands::List => list_docpacks()?,
-        Commands::Search { query } => search_commons(&query)?,
-        Commands::Remove { package } => remove_docpack(&package)?,
-        Commands::Update { package } => update_docpacks(package.as_deref())?,
+        Commands::Install { package } => install_docpack(&package, format)?,
+        Commands::List => list_docpacks(format)?,
+        Commands::Licenses => list_licenses(format)?,
+        Commands::Search { query } => search_commons(&query, format)?,
+        Commands::Remove { package } => remove_docpack(&package, format)?,
+        Commands::Update { package } => update_docpacks(package.as_deref(), format)?,
         Commands::Compare { docpack1, docpack2 } => {
             let path1 = resolve_docpack_path(&docpack1)?;
             let path2 = resolve_docpack_path(&docpack2)?;
-            compare_docpacks(&path1, &path2)?
+            compare_docpacks(&path1, &path2, format)?
+        }
+        Commands::Ci {
+            docpack,
+            min_test_coverage,
+            min_doc_coverage,
+            baseline,
+        } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let settings = config::load(None)?.ci;
+            let baseline = match baseline.or(settings.baseline) {
+                Some(baseline) => Some(resolve_docpack_path(&baseline)?),
+                None => None,
+            };
+            let failed = run_ci(
+                &path,
+                min_test_coverage.or(settings.min_test_coverage),
+                min_doc_coverage.or(settings.min_doc_coverage),
+                baseline.as_deref(),
+                format,
+            )?;
+            if failed > 0 {
+                std::process::exit(EXIT_FINDINGS);
+            }
         }
         Commands::Completions { shell } => {
             generate_completions(shell);
         }
-        Commands::Serve => serve_mcp()?,
+        Commands::Mcp {
+            http,
+            packages_dir,
+            log_file,
+            profile,
+            config,
+        } => serve_mcp(
+            http.as_deref(),
+            packages_dir,
+            log_file.as_deref(),
+            profile,
+            config.as_deref(),
+        )?,
+        Commands::McpStats { log_file, top } => mcp_stats(&log_file, top, format)?,
+        Commands::Todos { docpack } => {
+            let path = resolve_docpack_path(&docpack)?;
+            list_todos(&path, format)?
+        }
+        Commands::TraceMap { docpack, otlp } => {
+            let path = resolve_docpack_path(&docpack)?;
+            trace_map(&path, &otlp, format)?
+        }
+        Commands::Whence { docpack, message } => {
+            let path = resolve_docpack_path(&docpack)?;
+            whence(&path, &message, format)?
+        }
+        Commands::ResolveTrace { docpack } => {
+            let path = resolve_docpack_path(&docpack)?;
+            resolve_trace(&path, format)?
+        }
+        Commands::Profile {
+            docpack,
+            profile,
+            save,
+        } => {
+            let path = resolve_docpack_path(&docpack)?;
+            profile_docpack(&path, &profile, save, format)?
+        }
+        Commands::Assets { docpack, category } => {
+            let path = resolve_docpack_path(&docpack)?;
+            list_assets(&path, category.as_deref(), format)?
+        }
+        Commands::Unsafe { docpack, check } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let known = check.known_findings()?;
+            check.enforce(&known, list_unsafe(&path, &known, format)?)?
+        }
+        Commands::Scan {
+            docpack,
+            rules,
+            sarif,
+            dry_run,
+            check,
+        } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let known = check.known_findings()?;
+            // A CI gate only reads the docpack
+            let dry_run = dry_run || check.is_gate();
+            let findings = scan_docpack(&path, rules.as_deref(), sarif, dry_run, &known, format)?;
+            check.enforce(&known, findings)?
+        }
+        Commands::Enrich { docpack, repo } => {
+            let path = resolve_docpack_path(&docpack)?;
+            enrich_docpack(&path, &repo, format)?
+        }
+        Commands::Stale { docpack, limit } => {
+            let path = resolve_docpack_path(&docpack)?;
+            list_stale(&path, limit, format)?
+        }
+        Commands::Panics { docpack, check } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let known = check.known_findings()?;
+            check.enforce(&known, list_panics(&path, &known, format)?)?
+        }
+        Commands::Errors { docpack, check } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let known = check.known_findings()?;
+            check.enforce(&known, error_paths(&path, &known, format)?)?
+        }
+        Commands::AsyncMap { docpack, check } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let known = check.known_findings()?;
+            check.enforce(&known, async_map(&path, &known, format)?)?
+        }
+        Commands::Constructors { docpack } => {
+            let path = resolve_docpack_path(&docpack)?;
+            construction_graph(&path, format)?
+        }
+        Commands::ExportC4 { docpack, dialect } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let docpack = Docpack::open(&path)?;
+            print!(
+                "{}",
+                export::c4(&docpack.manifest, &docpack.symbols, dialect)
+            );
+        }
+        Commands::ExportUml { docpack, module } => {
+            let path = resolve_docpack_path(&docpack)?;
+            let docpack = Docpack::open(&path)?;
+            if docpack.find_symbols_by_file(&module).is_empty() {
+                eprintln!(
+                    "{}",
+                    t!("no-symbols-in-module", module = module.as_str()).red()
+                );
+                std::process::exit(EXIT_ERROR);
+            }
+            print!("{}", export::uml(&docpack.symbols, &module));
+        }
+        Commands::Encrypt { docpack, output } => {
+            let path = resolve_docpack_path(&docpack)?;
+            set_encryption(&path, true, output.as_deref(), format)?
+        }
+        Commands::Decrypt { docpack, output } => {
+            let path = resolve_docpack_path(&docpack)?;
+            set_encryption(&path, false, output.as_deref(), format)?
+        }
+        Commands::Redact { docpack, dry_run } => {
+            let path = resolve_docpack_path(&docpack)?;
+            redact_docpack(&path, dry_run, format)?
+        }
+        Commands::PruneDocs {
+            docpack,
+            sections,
+            output,
+        } => {
+            let path = resolve_docpack_path(&docpack)?;
+            prune_docs(&path, sections, output.as_deref(), format)?
+        }
+        Commands::RecomputeMetrics { docpack } => {
+            let path = resolve_docpack_path(&docpack)?;
+            recompute_metrics(&path, format)?
+        }
+        Commands::Reindex { docpack } => {
+            let path = resolve_docpack_path(&docpack)?;
+            reindex(&path, format)?
+        }
     }
 
     Ok(())
 }
 
+/// Replace a subcommand named in the config's `[alias]` section with its
+/// expansion, like git aliases. Built-in commands can't be redefined, and
+/// expansions aren't expanded again.
+fn expand_alias(mut args: Vec<std::ffi::OsString>) -> Result<Vec<std::ffi::OsString>> {
+    // The subcommand is the first argument that isn't an option or an option's value
+    let mut i = 1;
+    while let Some(arg) = args.get(i).and_then(|arg| arg.to_str()) {
+        match arg {
+            "--output-format" => i += 2,
+            _ if arg.starts_with('-') => i += 1,
+            _ => break,
+        }
+    }
+
+    let Some(name) = args.get(i).and_then(|arg| arg.to_str()) else {
+        return Ok(args);
+    };
+    if name == "help" || Cli::command().find_subcommand(name).is_some() {
+        return Ok(args);
+    }
+
+    let config = config::load(None)?;
+    let Some(expansion) = config.alias.get(name) else {
+        return Ok(args);
+    };
+    let words = config::split_words(expansion)?;
+    if words.is_empty() {
+        anyhow::bail!("Alias '{}' is empty", name);
+    }
+    args.splice(i..=i, words.into_iter().map(Into::into));
+    Ok(args)
+}
+
 /// Get the directory where docpacks are installed
 fn get_packages_dir() -> Result<PathBuf> {
     let data_dir = dirs::data_dir()
@@ -149,6 +778,26 @@ fn get_packages_dir() -> Result<PathBuf> {
     Ok(data_dir.join("localdoc").join("packages"))
 }
 
+/// Shared docpacks for every user of the machine
+const SYSTEM_PACKAGES_DIR: &str = "/usr/share/localdoc/packages";
+
+/// Directories the MCP server reads by default, most specific first: the project's
+/// `.localdoc/`, the user's installed packages, and the system-wide directory.
+/// The project and system directories are only included when they exist.
+fn default_packages_dirs() -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let project = PathBuf::from(".localdoc");
+    if project.is_dir() {
+        dirs.push(project);
+    }
+    dirs.push(get_packages_dir()?);
+    let system = PathBuf::from(SYSTEM_PACKAGES_DIR);
+    if cfg!(unix) && system.is_dir() {
+        dirs.push(system);
+    }
+    Ok(dirs)
+}
+
 /// Resolve a docpack identifier to a file path.
 /// Accepts either:
 /// - A full file path (e.g., "/path/to/file.docpack")
@@ -177,42 +826,24 @@ fn resolve_docpack_path(identifier: &str) -> Result<String> {
 }
 
 /// List all installed docpacks
-fn list_docpacks() -> Result<()> {
+fn list_docpacks(format: OutputFormat) -> Result<()> {
     let packages_dir = get_packages_dir()?;
 
-    if !packages_dir.exists() {
-        println!("{}", "No docpacks installed yet.".yellow());
-        println!();
-        println!(
-            "Install one with: {}",
-            "localdoc install <username:reponame>".cyan()
-        );
-        return Ok(());
-    }
-
-    let entries: Vec<_> = std::fs::read_dir(&packages_dir)?
-        .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.path()
-                .extension()
-                .map(|ext| ext == "docpack")
-                .unwrap_or(false)
-        })
-        .collect();
-
-    if entries.is_empty() {
-        println!("{}", "No docpacks installed yet.".yellow());
-        println!();
-        println!(
-            "Install one with: {}",
-            "localdoc install <username:reponame>".cyan()
-        );
-        return Ok(());
-    }
+    let entries: Vec<_> = if packages_dir.exists() {
+        std::fs::read_dir(&packages_dir)?
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .map(|ext| ext == "docpack")
+                    .unwrap_or(false)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-    println!("{}", "Installed Docpacks".bold().cyan());
-    println!("{}", "=".repeat(50));
-    println!();
+    let mut packages = Vec::new();
 
     for entry in &entries {
         let path = entry.path();
@@ -222,47 +853,79 @@ fn list_docpacks() -> Result<()> {
         let name = filename.replacen('_', ":", 1);
 
         // Try to read manifest for additional info
-        match Docpack::open(&path.to_string_lossy()) {
-            Ok(docpack) => {
-                let manifest = &docpack.manifest;
-                println!(
-                    "{} {} {}",
-                    name.green().bold(),
-                    format!("v{}", manifest.project.version).dimmed(),
-                    format!("({} symbols)", manifest.stats.symbols_extracted).dimmed()
-                );
-            }
-            Err(_) => {
-                println!(
-                    "{} {}",
-                    name.green().bold(),
-                    "(unable to read metadata)".dimmed()
-                );
-            }
-        }
+        let (version, symbols) = match Docpack::open(&path.to_string_lossy()) {
+            Ok(docpack) => (
+                Some(docpack.manifest.project.version.clone()),
+                Some(docpack.manifest.stats.symbols_extracted),
+            ),
+            Err(_) => (None, None),
+        };
+
+        packages.push(InstalledPackage {
+            name,
+            version,
+            symbols,
+        });
     }
 
-    println!();
-    println!("Total: {} docpack(s)", entries.len());
-    println!();
-    println!("{}", "Usage:".bold());
-    println!("  {} {}", "localdoc inspect".dimmed(), "<name>".cyan());
-    println!(
-        "  {} {} {}",
-        "localdoc query".dimmed(),
-        "<name>".cyan(),
-        "symbols".dimmed()
-    );
+    format.render(&PackageListReport { packages })
+}
 
-    Ok(())
+/// Collect license and dependency metadata from every installed docpack
+fn list_licenses(format: OutputFormat) -> Result<()> {
+    let packages_dir = get_packages_dir()?;
+
+    let mut entries: Vec<_> = if packages_dir.exists() {
+        std::fs::read_dir(&packages_dir)?
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .map(|ext| ext == "docpack")
+                    .unwrap_or(false)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    entries.sort_by_key(|e| e.path());
+
+    let mut packages = Vec::new();
+
+    for entry in &entries {
+        let path = entry.path();
+        let filename = path.file_stem().unwrap_or_default().to_string_lossy();
+        let name = filename.replacen('_', ":", 1);
+
+        let docpack = match Docpack::open(&path.to_string_lossy()) {
+            Ok(docpack) => docpack,
+            Err(e) => {
+                eprintln!("{} {}: {}", t!("skipping").yellow(), name, e);
+                continue;
+            }
+        };
+        let project = docpack.manifest.project;
+
+        packages.push(PackageLicense {
+            name,
+            version: project.version,
+            repo: project.repo,
+            license: project.license,
+            dependencies: project.dependencies,
+        });
+    }
+
+    format.render(&LicensesReport { packages })
 }
 
 /// Search the Commons for docpacks by fuzzy matching names
-fn search_commons(query: &str) -> Result<()> {
+fn search_commons(query: &str, format: OutputFormat) -> Result<()> {
     use strsim::jaro_winkler;
 
-    println!("{}", format!("Searching for '{}'...", query).dimmed());
-    println!();
+    if format.is_text() {
+        println!("{}", t!("commons-searching", query = query).dimmed());
+        println!();
+    }
 
     // Fetch the docpack list from the commons API
     let api_url = std::env::var("DOCTOWN_API_URL")
@@ -303,8 +966,8 @@ fn search_commons(query: &str) -> Result<()> {
             // Calculate score using Jaro-Winkler similarity
             // Also check against just the repo name (after the /)
             let repo_name = full_name
-                .split('/')
-                .last()
+                .rsplit('/')
+                .next()
                 .unwrap_or(full_name)
                 .to_lowercase();
 
@@ -367,272 +1030,345 @@ fn search_commons(query: &str) -> Result<()> {
     // Sort by score descending
     scored_results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
 
-    if scored_results.is_empty() {
-        println!(
-            "{}",
-            format!("No docpacks found matching '{}'", query).yellow()
-        );
-        return Ok(());
-    }
-
-    println!("{}", "Search Results".bold().cyan());
-    println!("{}", "=".repeat(50));
-    println!();
+    let results = scored_results
+        .into_iter()
+        .map(|(score, dp)| CommonsResult {
+            name: dp["full_name"]
+                .as_str()
+                .unwrap_or("unknown")
+                .replace('/', ":"),
+            score,
+            description: dp["description"].as_str().unwrap_or("").to_string(),
+            symbol_count: dp["symbol_count"].as_i64().unwrap_or(0),
+        })
+        .collect();
 
-    for (score, dp) in &scored_results {
-        let full_name = dp["full_name"].as_str().unwrap_or("unknown");
-        let name = full_name.replace('/', ":");
-        let description = dp["description"].as_str().unwrap_or("");
-        let symbol_count = dp["symbol_count"].as_i64().unwrap_or(0);
+    format.render(&CommonsSearchReport {
+        query: query.to_string(),
+        results,
+    })
+}
 
-        // Convert score to percentage for display
-        let score_pct = (score * 100.0) as u32;
+fn inspect_docpack(path: &str, format: OutputFormat) -> Result<()> {
+    let docpack = open_for_analysis(path)?;
 
-        println!(
-            "{} {} {}",
-            name.green().bold(),
-            format!("({}%)", score_pct).yellow(),
-            format!("{} symbols", symbol_count).dimmed()
-        );
+    let testable: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter(|s| !docpack::is_test_symbol(s))
+        .collect();
+    let linked: Vec<_> = testable
+        .iter()
+        .filter_map(|s| {
+            let tests = docpack.find_tests_for(s);
+            (!tests.is_empty()).then(|| LinkedTests {
+                symbol: s.id.clone(),
+                tests: tests.into_iter().map(|t| t.id.clone()).collect(),
+            })
+        })
+        .collect();
+    let test_coverage = TestCoverage {
+        tested: linked.len(),
+        total: testable.len(),
+        linked,
+    };
 
-        if !description.is_empty() {
-            // Truncate description if too long
-            let desc = if description.len() > 60 {
-                format!("{}...", &description[..57])
-            } else {
-                description.to_string()
-            };
-            println!("  {}", desc.dimmed());
+    // Sizes derived from symbol spans; older docpacks don't record end lines
+    let sizes: Vec<_> = docpack.symbols.iter().filter_map(|s| s.loc()).collect();
+    let size = (!sizes.is_empty()).then(|| SizeStats {
+        measured_symbols: sizes.len(),
+        total_loc: sizes.iter().sum(),
+        max_loc: sizes.iter().copied().max().unwrap_or(0),
+    });
+
+    let mut measured: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter_map(|s| {
+            docpack.metrics_for(s).map(|metrics| SymbolMetrics {
+                id: s.id.clone(),
+                metrics,
+            })
+        })
+        .collect();
+    let maintainability = (!measured.is_empty()).then(|| {
+        let average_index = measured
+            .iter()
+            .map(|m| m.metrics.maintainability_index)
+            .sum::<f64>()
+            / measured.len() as f64;
+        let measured_symbols = measured.len();
+        measured.sort_by(|a, b| {
+            a.metrics
+                .maintainability_index
+                .total_cmp(&b.metrics.maintainability_index)
+        });
+        measured.truncate(5);
+        MaintainabilityStats {
+            measured_symbols,
+            average_index,
+            lowest: measured,
         }
-        println!();
-    }
+    });
 
-    println!("Found {} result(s)", scored_results.len());
-    println!();
-    println!("{}", "To install:".bold());
-    println!(
-        "  {} {}",
-        "localdoc install".dimmed(),
-        "<username:reponame>".cyan()
-    );
+    let history = docpack
+        .symbols
+        .iter()
+        .filter_map(|s| s.last_modified.as_ref())
+        .fold(None, |range: Option<HistoryRange>, modified| {
+            Some(match range {
+                None => HistoryRange {
+                    dated_symbols: 1,
+                    oldest: modified.date.clone(),
+                    newest: modified.date.clone(),
+                },
+                Some(mut range) => {
+                    range.dated_symbols += 1;
+                    if modified.date < range.oldest {
+                        range.oldest = modified.date.clone();
+                    }
+                    if modified.date > range.newest {
+                        range.newest = modified.date.clone();
+                    }
+                    range
+                }
+            })
+        });
 
-    Ok(())
+    let mut related: Vec<_> = docpack
+        .symbols
+        .iter()
+        .flat_map(|s| s.related_refs())
+        .map(str::to_string)
+        .collect();
+    related.sort_by_key(|r| r.trim_start_matches('#').parse::<u64>().unwrap_or(0));
+    related.dedup();
+
+    format.render(&InspectReport {
+        manifest: docpack.manifest,
+        test_coverage,
+        size,
+        maintainability,
+        history,
+        related,
+    })
 }
 
-fn inspect_docpack(path: &str) -> Result<()> {
+/// Choose a symbol interactively, then print its ID or documentation
+fn pick_symbol(path: &str, show: bool, query: &str, format: OutputFormat) -> Result<()> {
     let docpack = Docpack::open(path)?;
-    let manifest = &docpack.manifest;
-
-    println!("{}", "Docpack Metadata".bold().cyan());
-    println!("{}", "=".repeat(50));
-    println!();
 
-    println!("{}: {}", "Format Version".bold(), manifest.docpack_format);
-    println!();
-
-    println!("{}", "Project Information:".bold().green());
-    println!("  {}: {}", "Name".bold(), manifest.project.name);
-    println!("  {}: {}", "Version".bold(), manifest.project.version);
-    if !manifest.project.repo.is_empty() {
-        println!("  {}: {}", "Repository".bold(), manifest.project.repo);
-    }
-    if !manifest.project.commit.is_empty() {
-        println!("  {}: {}", "Commit".bold(), manifest.project.commit);
+    let mut candidates = Vec::new();
+    for symbol in &docpack.symbols {
+        let doc = docpack.get_documentation(&symbol.doc_id)?;
+        candidates.push(picker::Candidate {
+            label: symbol.id.clone(),
+            detail: format!("[{}] {}", symbol.kind, doc.summary),
+        });
     }
-    println!();
 
-    println!("{}: {}", "Generated At".bold(), manifest.generated_at);
-    println!();
+    let Some(index) = picker::pick(&candidates, query)? else {
+        // Cancelled, like fzf
+        std::process::exit(130);
+    };
+    let symbol = docpack.symbols[index].clone();
 
-    println!("{}", "Language Summary:".bold().yellow());
-    for (lang, count) in &manifest.language_summary {
-        println!("  {}: {}", lang, count);
+    if !show {
+        println!("{}", symbol.id);
+        return Ok(());
     }
-    println!();
 
-    println!("{}", "Statistics:".bold().magenta());
-    println!(
-        "  {}: {}",
-        "Symbols Extracted".bold(),
-        manifest.stats.symbols_extracted
-    );
-    println!(
-        "  {}: {}",
-        "Docs Generated".bold(),
-        manifest.stats.docs_generated
-    );
-
-    Ok(())
+    let documentation = docpack.get_documentation(&symbol.doc_id)?;
+    let metrics = docpack.metrics_for(&symbol);
+    let profile = docpack.profile_for(&symbol);
+    format.render(&SymbolDocsReport {
+        matches: vec![SymbolDoc {
+            symbol,
+            documentation,
+            metrics,
+            profile,
+            calls: None,
+        }],
+    })
 }
 
-fn handle_query(path: &str, query_type: QueryType) -> Result<()> {
-    let mut docpack = Docpack::open(path)?;
+fn handle_query(path: &str, query_type: QueryType, format: OutputFormat) -> Result<()> {
+    let docpack = Docpack::open(path)?;
 
     match query_type {
-        QueryType::Symbols => {
-            println!("{}", "All Symbols".bold().cyan());
-            println!("{}", "=".repeat(50));
-            println!();
-
-            for symbol in &docpack.symbols {
-                println!(
-                    "{} {} {}",
-                    format!("[{}]", symbol.kind).yellow(),
-                    symbol.id.green(),
-                    format!("({}:{})", symbol.file, symbol.line).dimmed()
-                );
-            }
-
-            println!();
-            println!("Total: {} symbols", docpack.symbols.len());
-        }
-
-        QueryType::Symbol { name } => {
-            let matches: Vec<_> = docpack
-                .find_symbols_by_name(&name)
-                .into_iter()
+        QueryType::Symbols {
+            attr,
+            feature,
+            tag,
+            sort,
+        } => {
+            let mut symbols: Vec<_> = docpack
+                .symbols
+                .iter()
+                .filter(|s| attr.as_deref().is_none_or(|a| s.has_attribute(a)))
+                .filter(|s| feature.as_deref().is_none_or(|f| s.requires_feature(f)))
+                .filter(|s| tag.as_ref().is_none_or(|t| s.tags.contains(t)))
                 .cloned()
                 .collect();
 
-            if matches.is_empty() {
-                eprintln!("{}", format!("No symbol found matching '{}'", name).red());
-                std::process::exit(1);
+            match sort {
+                Some(SymbolSort::Name) => symbols.sort_by(|a, b| a.id.cmp(&b.id)),
+                Some(SymbolSort::Loc) => symbols.sort_by_key(|s| std::cmp::Reverse(s.loc())),
+                Some(SymbolSort::Stale) => symbols.sort_by_key(|s| {
+                    // Symbols without history go last
+                    s.last_modified
+                        .as_ref()
+                        .map_or(i64::MAX, |modified| modified.timestamp)
+                }),
+                None => {}
             }
 
-            for symbol in matches {
-                let doc = docpack.get_documentation(&symbol.doc_id)?;
+            format.render(&SymbolListReport { symbols })?
+        }
 
-                println!("{}", "Symbol Information".bold().cyan());
-                println!("{}", "=".repeat(50));
-                println!();
+        QueryType::Symbol { name, with_callers } => {
+            let matches = find_symbols_or_exit(&docpack, &name);
 
-                println!("{}: {}", "ID".bold(), symbol.id.green());
-                println!("{}: {}", "Kind".bold(), symbol.kind.yellow());
-                println!(
-                    "{}: {}",
-                    "File".bold(),
-                    format!("{}:{}", symbol.file, symbol.line)
-                );
-                println!("{}: {}", "Signature".bold(), symbol.signature);
-                println!();
-
-                println!("{}", "Documentation".bold().cyan());
-                println!("{}", "-".repeat(50));
-                println!();
-                println!("{}: {}", "Summary".bold(), doc.summary);
-                println!();
-                println!("{}", "Description:".bold());
-                println!("{}", doc.description);
-                println!();
-
-                if !doc.parameters.is_empty() {
-                    println!("{}", "Parameters:".bold().green());
-                    for param in &doc.parameters {
-                        println!(
-                            "  {} {} - {}",
-                            param.name.bold(),
-                            format!("({})", param.param_type).dimmed(),
-                            param.description
-                        );
-                    }
-                    println!();
-                }
+            let mut entries = Vec::new();
+            for symbol in matches {
+                let documentation = docpack.get_documentation(&symbol.doc_id)?;
+                let metrics = docpack.metrics_for(&symbol);
+                let profile = docpack.profile_for(&symbol);
+                let calls = with_callers
+                    .then(|| call_context(&docpack, &symbol))
+                    .transpose()?;
+                entries.push(SymbolDoc {
+                    symbol,
+                    documentation,
+                    metrics,
+                    profile,
+                    calls,
+                });
+            }
+
+            format.render(&SymbolDocsReport { matches: entries })?
+        }
 
-                println!("{}: {}", "Returns".bold(), doc.returns);
-                println!();
+        QueryType::Search {
+            keyword,
+            history,
+            feature,
+            debug_score,
+        } => {
+            // History is keyed by the docpack's full path, however it was named
+            let pack = std::fs::canonicalize(path)
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| path.to_string());
+            let mut state = state::load()?;
+
+            if history {
+                let queries = state
+                    .searches(&pack)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+                format.render(&SearchHistoryReport {
+                    docpack: pack,
+                    queries,
+                })?;
+                return Ok(());
+            }
 
-                if !doc.example.is_empty() {
-                    println!("{}", "Example:".bold().yellow());
-                    println!("{}", doc.example);
-                    println!();
+            let mut keyword = keyword.unwrap_or_default();
+            if keyword.starts_with('!') {
+                keyword = state.recall_search(&pack, &keyword)?;
+                if format.is_text() {
+                    eprintln!("{}", t!("searching-for", query = keyword.as_str()).dimmed());
                 }
+            }
 
-                if !doc.notes.is_empty() {
-                    println!("{}", "Notes:".bold().magenta());
-                    for note in &doc.notes {
-                        println!("  - {}", note);
-                    }
-                    println!();
+            if let Some(reason) = query::Query::syntax_error(&keyword) {
+                if format.is_text() {
+                    let note = t!("searching-as-typed", reason = reason.as_str());
+                    eprintln!("{}", note.dimmed());
                 }
             }
-        }
 
-        QueryType::Search { keyword } => {
-            let results = docpack.search_symbols(&keyword)?;
+            let search = config::load(None)?.search;
+            let mut results = docpack.search_symbols(&keyword, &search)?;
 
-            if results.is_empty() {
-                eprintln!("{}", format!("No results found for '{}'", keyword).red());
-                std::process::exit(1);
+            state.record_search(&pack, &keyword);
+            if let Err(e) = state.save() {
+                eprintln!("{} {:#}", t!("warning").yellow(), e);
+            }
+            if let Some(feature) = &feature {
+                results.retain(|result| result.symbol.requires_feature(feature));
             }
 
-            println!(
-                "{}",
-                format!("Search Results for '{}'", keyword).bold().cyan()
-            );
-            println!("{}", "=".repeat(50));
-            println!();
-
-            for (symbol, doc) in results {
-                println!(
-                    "{} {}",
-                    format!("[{}]", symbol.kind).yellow(),
-                    symbol.id.green()
-                );
-                println!(
-                    "  {}: {}",
-                    "Location".dimmed(),
-                    format!("{}:{}", symbol.file, symbol.line).dimmed()
+            if results.is_empty() {
+                eprintln!(
+                    "{}",
+                    t!("no-search-results", query = keyword.as_str()).red()
                 );
-                println!("  {}: {}", "Summary".bold(), doc.summary);
-                println!();
+                std::process::exit(EXIT_ERROR);
             }
+
+            let results = results
+                .into_iter()
+                .map(|result| SearchHit {
+                    context: result.context,
+                    via_alias: result.via_alias,
+                    score: debug_score.then_some(result.score),
+                    entry: SymbolDoc {
+                        metrics: docpack.metrics_for(&result.symbol),
+                        profile: docpack.profile_for(&result.symbol),
+                        calls: None,
+                        symbol: result.symbol,
+                        documentation: result.doc,
+                    },
+                })
+                .collect();
+
+            format.render(&SearchReport { keyword, results })?
         }
 
         QueryType::Files => {
-            let files = docpack.get_unique_files();
-
-            println!("{}", "Source Files".bold().cyan());
-            println!("{}", "=".repeat(50));
-            println!();
-
-            for file in &files {
-                let count = docpack.symbols.iter().filter(|s| &s.file == file).count();
-                println!(
-                    "{} {}",
-                    file.green(),
-                    format!("({} symbols)", count).dimmed()
-                );
-            }
+            let files = docpack
+                .get_unique_files()
+                .into_iter()
+                .map(|file| {
+                    let in_file: Vec<_> =
+                        docpack.symbols.iter().filter(|s| s.file == file).collect();
+                    let snippets: Vec<_> = in_file
+                        .iter()
+                        .filter_map(|s| s.source_snippet.as_deref())
+                        .collect();
+                    let todos = snippets
+                        .iter()
+                        .map(|snippet| metrics::find_markers(snippet).len())
+                        .sum();
+                    let comment_density = (!snippets.is_empty())
+                        .then(|| metrics::comment_density(&snippets.join("\n")));
+                    FileEntry {
+                        file,
+                        symbols: in_file.len(),
+                        todos,
+                        comment_density,
+                    }
+                })
+                .collect();
 
-            println!();
-            println!("Total: {} files", files.len());
+            format.render(&FilesReport { files })?
         }
 
         QueryType::File { file } => {
-            let symbols = docpack.find_symbols_by_file(&file);
+            let symbols: Vec<_> = docpack
+                .find_symbols_by_file(&file)
+                .into_iter()
+                .cloned()
+                .collect();
 
             if symbols.is_empty() {
-                eprintln!(
-                    "{}",
-                    format!("No symbols found in file matching '{}'", file).red()
-                );
-                std::process::exit(1);
+                eprintln!("{}", t!("no-symbols-in-file", file = file.as_str()).red());
+                std::process::exit(EXIT_ERROR);
             }
 
-            println!("{}", format!("Symbols in '{}'", file).bold().cyan());
-            println!("{}", "=".repeat(50));
-            println!();
-
-            for symbol in symbols {
-                println!(
-                    "{} {} {}",
-                    format!("[{}]", symbol.kind).yellow(),
-                    symbol.id.green(),
-                    format!("(line {})", symbol.line).dimmed()
-                );
-                println!("  {}", symbol.signature.dimmed());
-                println!();
-            }
+            format.render(&FileSymbolsReport { file, symbols })?
         }
 
         QueryType::Kind { kind } => {
@@ -641,15 +1377,13 @@ fn handle_query(path: &str, query_type: QueryType) -> Result<()> {
                 .symbols
                 .iter()
                 .filter(|s| s.kind.to_lowercase().contains(&kind_lower))
+                .cloned()
                 .collect();
 
             if filtered.is_empty() {
-                eprintln!(
-                    "{}",
-                    format!("No symbols found with kind matching '{}'", kind).red()
-                );
-                println!();
-                println!("{}", "Available kinds:".bold());
+                eprintln!("{}", t!("no-symbols-of-kind", kind = kind.as_str()).red());
+                eprintln!();
+                eprintln!("{}", t!("available-kinds").bold());
                 let mut kinds: Vec<_> = docpack
                     .symbols
                     .iter()
@@ -659,145 +1393,148 @@ fn handle_query(path: &str, query_type: QueryType) -> Result<()> {
                     .collect();
                 kinds.sort();
                 for k in kinds {
-                    println!("  - {}", k.yellow());
+                    eprintln!("  - {}", k.yellow());
                 }
-                std::process::exit(1);
+                std::process::exit(EXIT_ERROR);
             }
 
-            println!(
-                "{}",
-                format!("Symbols of kind '{}'", kind).bold().cyan()
-            );
-            println!("{}", "=".repeat(50));
-            println!();
-
-            for symbol in &filtered {
-                println!(
-                    "{} {} {}",
-                    format!("[{}]", symbol.kind).yellow(),
-                    symbol.id.green(),
-                    format!("({}:{})", symbol.file, symbol.line).dimmed()
-                );
-            }
-
-            println!();
-            println!("Total: {} symbols", filtered.len());
+            format.render(&KindReport {
+                kind,
+                symbols: filtered,
+            })?
         }
 
         QueryType::Examples { name } => {
-            let matches: Vec<_> = docpack
-                .find_symbols_by_name(&name)
-                .into_iter()
-                .cloned()
-                .collect();
-
-            if matches.is_empty() {
-                eprintln!("{}", format!("No symbol found matching '{}'", name).red());
-                std::process::exit(1);
-            }
+            let matches = find_symbols_or_exit(&docpack, &name);
 
+            let mut examples = Vec::new();
             for symbol in matches {
                 let doc = docpack.get_documentation(&symbol.doc_id)?;
-
-                println!("{}", format!("Examples for '{}'", symbol.id).bold().cyan());
-                println!("{}", "=".repeat(50));
-                println!();
-
-                if doc.example.is_empty() {
-                    println!("{}", "No examples available for this symbol.".yellow());
-                } else {
-                    println!("{}", doc.example);
-                }
-                println!();
+                let stored = docpack.get_examples(&symbol.doc_id)?;
+                examples.push(ExampleEntry {
+                    symbol: symbol.id,
+                    example: doc.example,
+                    examples: stored,
+                });
             }
-        }
 
-        QueryType::Deps { name } => {
-            let matches: Vec<_> = docpack
-                .find_symbols_by_name(&name)
-                .into_iter()
-                .cloned()
-                .collect();
+            format.render(&ExamplesReport { examples })?
+        }
 
-            if matches.is_empty() {
-                eprintln!("{}", format!("No symbol found matching '{}'", name).red());
-                std::process::exit(1);
-            }
+        QueryType::Deps { name, with_callers } => {
+            let matches = find_symbols_or_exit(&docpack, &name);
 
+            let mut dependencies = Vec::new();
             for symbol in matches {
                 let doc = docpack.get_documentation(&symbol.doc_id)?;
 
-                println!(
-                    "{}",
-                    format!("Dependencies for '{}'", symbol.id).bold().cyan()
-                );
-                println!("{}", "=".repeat(50));
-                println!();
-
-                // Extract type references from signature and parameters
-                println!("{}", "Type References:".bold().green());
-
-                // Show parameter types
-                if !doc.parameters.is_empty() {
-                    println!("  {}", "Parameters:".bold());
-                    for param in &doc.parameters {
-                        println!(
-                            "    {} → {}",
-                            param.name.dimmed(),
-                            param.param_type.yellow()
-                        );
-                    }
-                }
-
-                // Show return type
-                if !doc.returns.is_empty() && doc.returns != "void" && doc.returns != "None" {
-                    println!("  {}", "Returns:".bold());
-                    println!("    → {}", doc.returns.yellow());
-                }
+                let returns =
+                    if !doc.returns.is_empty() && doc.returns != "void" && doc.returns != "None" {
+                        Some(doc.returns)
+                    } else {
+                        None
+                    };
 
                 // Try to find related symbols in the same file
-                let file_symbols: Vec<_> = docpack
+                let related = docpack
                     .symbols
                     .iter()
                     .filter(|s| s.file == symbol.file && s.id != symbol.id)
+                    .cloned()
                     .collect();
 
-                if !file_symbols.is_empty() {
-                    println!();
-                    println!("{}", "Related Symbols (same file):".bold().green());
-                    for s in file_symbols.iter().take(10) {
-                        println!(
-                            "    {} {}",
-                            format!("[{}]", s.kind).dimmed(),
-                            s.id.cyan()
-                        );
-                    }
-                    if file_symbols.len() > 10 {
-                        println!("    ... and {} more", file_symbols.len() - 10);
-                    }
-                }
-
-                println!();
+                let calls = with_callers
+                    .then(|| call_context(&docpack, &symbol))
+                    .transpose()?;
+
+                dependencies.push(DepsEntry {
+                    symbol: symbol.id,
+                    parameters: doc.parameters,
+                    returns,
+                    related,
+                    calls,
+                });
             }
+
+            format.render(&DepsReport { dependencies })?
+        }
+
+        QueryType::TestsFor { name } => {
+            let matches = find_symbols_or_exit(&docpack, &name);
+
+            let entries = matches
+                .iter()
+                .map(|symbol| TestsForEntry {
+                    symbol: symbol.id.clone(),
+                    tests: docpack
+                        .find_tests_for(symbol)
+                        .into_iter()
+                        .cloned()
+                        .collect(),
+                })
+                .collect();
+
+            format.render(&TestsForReport { entries })?
         }
     }
 
     Ok(())
 }
 
-fn install_docpack(package: &str) -> Result<()> {
+/// A symbol's direct callers and callees as `--with-callers` shows them
+fn call_context(docpack: &Docpack, symbol: &models::Symbol) -> Result<CallContext> {
+    let neighbors = |calls: Vec<Call>| -> Result<Vec<CallNeighbor>> {
+        calls
+            .into_iter()
+            .map(|call| {
+                let s = call.symbol;
+                Ok(CallNeighbor {
+                    symbol: s.id.clone(),
+                    kind: s.kind.clone(),
+                    file: s.file.clone(),
+                    line: s.line,
+                    summary: docpack.get_documentation(&s.doc_id)?.summary,
+                    call_lines: call.lines,
+                    call_count: call.count,
+                })
+            })
+            .collect()
+    };
+    Ok(CallContext {
+        callers: neighbors(docpack.calls_to(symbol))?,
+        callees: neighbors(docpack.calls_from(symbol))?,
+    })
+}
+
+/// Find symbols matching a name, exiting with an error message if there are none
+fn find_symbols_or_exit(docpack: &Docpack, name: &str) -> Vec<models::Symbol> {
+    let matches: Vec<_> = docpack
+        .find_symbols_by_name(name)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    if matches.is_empty() {
+        eprintln!("{}", t!("no-symbol-match", name = name).red());
+        std::process::exit(EXIT_ERROR);
+    }
+
+    matches
+}
+
+fn install_docpack(package: &str, format: OutputFormat) -> Result<()> {
     use std::fs;
     use std::io::Write;
 
-    println!("{}", format!("Installing {}...", package).bold().cyan());
+    if format.is_text() {
+        println!("{}", t!("installing", package = package).bold().cyan());
+    }
 
     // Parse the package identifier (username:reponame)
     let full_name = package.replace(':', "/");
 
     // Get or create the localdoc directory in user's data directory
-    let data_dir = dirs::data_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine user data directory"))?;
-    let localdoc_dir = data_dir.join("localdoc").join("packages");
+    let localdoc_dir = get_packages_dir()?;
 
     fs::create_dir_all(&localdoc_dir)?;
 
@@ -806,7 +1543,9 @@ fn install_docpack(package: &str) -> Result<()> {
     let api_url = std::env::var("DOCTOWN_API_URL")
         .unwrap_or_else(|_| "https://www.doctown.dev/api/docpacks?public=true".to_string());
 
-    println!("{}", format!("Fetching from {}...", api_url).dimmed());
+    if format.is_text() {
+        println!("{}", t!("fetching-from", url = api_url.as_str()).dimmed());
+    }
 
     let response = reqwest::blocking::get(api_url)
         .map_err(|e| anyhow::anyhow!("Failed to fetch from commons: {}", e))?;
@@ -866,10 +1605,9 @@ fn install_docpack(package: &str) -> Result<()> {
         .ok_or_else(|| anyhow::anyhow!("Docpack does not have a download URL"))?;
 
     // Download the docpack file
-    println!(
-        "{}",
-        format!("Downloading docpack from: {}...", file_url).dimmed()
-    );
+    if format.is_text() {
+        println!("{}", t!("downloading-from", url = file_url).dimmed());
+    }
 
     let file_response = reqwest::blocking::get(file_url)
         .map_err(|e| anyhow::anyhow!("Failed to download docpack: {}", e))?;
@@ -897,35 +1635,14 @@ fn install_docpack(package: &str) -> Result<()> {
     let mut file = fs::File::create(&dest_path)?;
     file.write_all(&bytes)?;
 
-    println!();
-    println!("{}", "Installation complete!".green().bold());
-    println!();
-    println!("{}: {}", "Package".bold(), package.green());
-    println!(
-        "{}: {}",
-        "Location".bold(),
-        dest_path.display().to_string().dimmed()
-    );
-    println!();
-    println!("{}", "Usage:".bold());
-    println!(
-        "  {} {} {}",
-        "localdoc inspect".dimmed(),
-        package.cyan(),
-        "# View metadata".dimmed()
-    );
-    println!(
-        "  {} {} {}",
-        "localdoc query".dimmed(),
-        package.cyan(),
-        "symbols # List all symbols".dimmed()
-    );
-
-    Ok(())
+    format.render(&InstallReport {
+        package: package.to_string(),
+        location: dest_path.display().to_string(),
+    })
 }
 
 /// Remove an installed docpack
-fn remove_docpack(package: &str) -> Result<()> {
+fn remove_docpack(package: &str, format: OutputFormat) -> Result<()> {
     use std::fs;
 
     let packages_dir = get_packages_dir()?;
@@ -941,39 +1658,39 @@ fn remove_docpack(package: &str) -> Result<()> {
 
     fs::remove_file(&path)?;
 
-    println!("{}", "Docpack removed!".green().bold());
-    println!();
-    println!("{}: {}", "Package".bold(), package.yellow());
-
-    Ok(())
+    format.render(&RemoveReport {
+        package: package.to_string(),
+    })
 }
 
 /// Update installed docpacks to their latest versions
-fn update_docpacks(package: Option<&str>) -> Result<()> {
+fn update_docpacks(package: Option<&str>, format: OutputFormat) -> Result<()> {
     use std::fs;
     use std::io::Write;
 
     let packages_dir = get_packages_dir()?;
 
-    if !packages_dir.exists() {
-        println!("{}", "No docpacks installed yet.".yellow());
-        return Ok(());
-    }
-
     // Get list of installed packages
-    let entries: Vec<_> = fs::read_dir(&packages_dir)?
-        .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.path()
-                .extension()
-                .map(|ext| ext == "docpack")
-                .unwrap_or(false)
-        })
-        .collect();
+    let entries: Vec<_> = if packages_dir.exists() {
+        fs::read_dir(&packages_dir)?
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .map(|ext| ext == "docpack")
+                    .unwrap_or(false)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
 
     if entries.is_empty() {
-        println!("{}", "No docpacks installed yet.".yellow());
-        return Ok(());
+        if format.is_text() {
+            println!("{}", t!("no-docpacks-installed").yellow());
+            return Ok(());
+        }
+        return format.render(&UpdateReport::default());
     }
 
     // Filter to specific package if requested
@@ -1001,7 +1718,9 @@ fn update_docpacks(package: Option<&str>) -> Result<()> {
     let api_url = std::env::var("DOCTOWN_API_URL")
         .unwrap_or_else(|_| "https://www.doctown.dev/api/docpacks?public=true".to_string());
 
-    println!("{}", "Checking for updates...".dimmed());
+    if format.is_text() {
+        println!("{}", t!("checking-updates").dimmed());
+    }
 
     let response = reqwest::blocking::get(&api_url)
         .map_err(|e| anyhow::anyhow!("Failed to fetch from commons: {}", e))?;
@@ -1018,8 +1737,7 @@ fn update_docpacks(package: Option<&str>) -> Result<()> {
         .as_array()
         .ok_or_else(|| anyhow::anyhow!("Invalid API response format"))?;
 
-    let mut updated_count = 0;
-    let mut up_to_date_count = 0;
+    let mut report = UpdateReport::default();
 
     for entry in packages_to_update {
         let path = entry.path();
@@ -1042,12 +1760,15 @@ fn update_docpacks(package: Option<&str>) -> Result<()> {
             let remote_version = remote_doc["version"].as_str().unwrap_or("unknown");
 
             if remote_version != current_version {
-                println!(
-                    "{} {} → {}",
-                    name.green().bold(),
-                    current_version.dimmed(),
-                    remote_version.cyan()
-                );
+                if format.is_text() {
+                    println!(
+                        "{} {} {} {}",
+                        name.green().bold(),
+                        current_version.dimmed(),
+                        display::arrow(),
+                        remote_version.cyan()
+                    );
+                }
 
                 // Download and update
                 if let Some(file_url) = remote_doc["file_url"].as_str() {
@@ -1058,165 +1779,1343 @@ fn update_docpacks(package: Option<&str>) -> Result<()> {
                         let bytes = file_response.bytes()?;
                         let mut file = fs::File::create(&path)?;
                         file.write_all(&bytes)?;
-                        updated_count += 1;
+                        report.updated.push(UpdatedPackage {
+                            name,
+                            from: current_version,
+                            to: remote_version.to_string(),
+                        });
                     } else {
-                        eprintln!(
-                            "  {} Failed to download update",
-                            "✗".red()
-                        );
+                        eprintln!("  {} {}", display::cross().red(), t!("update-failed"));
+                        report.failed.push(name);
                     }
                 }
             } else {
-                up_to_date_count += 1;
+                report.up_to_date.push(name);
             }
         } else {
-            println!(
-                "{} {}",
-                name.yellow(),
-                "(not found in commons)".dimmed()
-            );
+            if format.is_text() {
+                println!("{} {}", name.yellow(), t!("not-in-commons").dimmed());
+            }
+            report.not_found.push(name);
         }
     }
 
-    println!();
-    if updated_count > 0 {
-        println!(
-            "{}",
-            format!("Updated {} docpack(s)", updated_count).green().bold()
+    format.render(&report)
+}
+
+/// Compare two docpacks to find differences
+fn compare_docpacks(path1: &str, path2: &str, format: OutputFormat) -> Result<()> {
+    let docpack1 = Docpack::open(path1)?;
+    let docpack2 = Docpack::open(path2)?;
+
+    format.render(&build_compare_report(&docpack1, &docpack2))
+}
+
+/// Open a docpack for an analysis, leaving out symbols in files excluded by
+/// `.localdocignore` or the config's `[ignore]` section
+fn open_for_analysis(path: &str) -> Result<Docpack> {
+    let mut docpack = Docpack::open(path)?;
+    let rules = ignore::load()?;
+    docpack.symbols.retain(|s| !rules.is_ignored(&s.file));
+    Ok(docpack)
+}
+
+/// Run the CI checks that apply and render them as one report.
+/// Returns the number of failed checks.
+fn run_ci(
+    path: &str,
+    min_test_coverage: Option<f64>,
+    min_doc_coverage: Option<f64>,
+    baseline: Option<&str>,
+    format: OutputFormat,
+) -> Result<usize> {
+    use std::collections::{HashMap, HashSet};
+
+    let docpack = open_for_analysis(path)?;
+    let mut checks = Vec::new();
+
+    // The pack is internally consistent: unique IDs and readable documentation
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+    for symbol in &docpack.symbols {
+        if !seen.insert(&symbol.id) {
+            problems.push(format!("duplicate symbol ID {}", symbol.id));
+        }
+        if let Err(e) = docpack.get_documentation(&symbol.doc_id) {
+            problems.push(format!("{}: {:#}", symbol.id, e));
+        }
+    }
+    checks.push(CiCheck {
+        name: "validate".to_string(),
+        passed: problems.is_empty(),
+        detail: format!(
+            "{} symbols, {} problem(s)",
+            docpack.symbols.len(),
+            problems.len()
+        ),
+        problems,
+    });
+
+    let percent = |part: usize, total: usize| {
+        if total == 0 {
+            100.0
+        } else {
+            part as f64 * 100.0 / total as f64
+        }
+    };
+
+    if let Some(minimum) = min_test_coverage {
+        let testable: Vec<_> = docpack
+            .symbols
+            .iter()
+            .filter(|s| !docpack::is_test_symbol(s))
+            .collect();
+        let tested = testable
+            .iter()
+            .filter(|s| !docpack.find_tests_for(s).is_empty())
+            .count();
+        let coverage = percent(tested, testable.len());
+        checks.push(CiCheck {
+            name: "test-coverage".to_string(),
+            passed: coverage >= minimum,
+            detail: format!(
+                "{:.1}% ({}/{}), minimum {}%",
+                coverage,
+                tested,
+                testable.len(),
+                minimum
+            ),
+            problems: Vec::new(),
+        });
+    }
+
+    if let Some(minimum) = min_doc_coverage {
+        let mut undocumented = Vec::new();
+        let mut public = 0;
+        for symbol in docpack
+            .symbols
+            .iter()
+            .filter(|s| s.is_public() && !docpack::is_test_symbol(s))
+        {
+            public += 1;
+            let documented = docpack
+                .get_documentation(&symbol.doc_id)
+                .is_ok_and(|doc| !doc.summary.trim().is_empty());
+            if !documented {
+                undocumented.push(format!("{} is undocumented", symbol.id));
+            }
+        }
+        let coverage = percent(public - undocumented.len(), public);
+        checks.push(CiCheck {
+            name: "doc-coverage".to_string(),
+            passed: coverage >= minimum,
+            detail: format!(
+                "{:.1}% ({}/{}), minimum {}%",
+                coverage,
+                public - undocumented.len(),
+                public,
+                minimum
+            ),
+            problems: if coverage >= minimum {
+                Vec::new()
+            } else {
+                undocumented
+            },
+        });
+    }
+
+    if let Some(baseline_path) = baseline {
+        let old = open_for_analysis(baseline_path)?;
+        let comparison = build_compare_report(&old, &docpack);
+        let old_public: HashMap<_, _> = old
+            .symbols
+            .iter()
+            .filter(|s| s.is_public())
+            .map(|s| (s.id.as_str(), s))
+            .collect();
+
+        let mut problems: Vec<_> = comparison
+            .only_in_a
+            .iter()
+            .filter(|r| old_public.contains_key(r.id.as_str()))
+            .map(|r| format!("{} was removed", r.id))
+            .collect();
+        problems.extend(
+            comparison
+                .signature_changed
+                .iter()
+                .filter(|c| old_public.contains_key(c.id.as_str()))
+                .map(|c| format!("{} changed: {} -> {}", c.id, c.a, c.b)),
         );
+        checks.push(CiCheck {
+            name: "api-compatibility".to_string(),
+            passed: problems.is_empty(),
+            detail: format!(
+                "against {} v{}, {} breaking change(s)",
+                old.manifest.project.name,
+                old.manifest.project.version,
+                problems.len()
+            ),
+            problems,
+        });
     }
-    if up_to_date_count > 0 {
+
+    let failed = checks.iter().filter(|c| !c.passed).count();
+    format.render(&CiReport {
+        docpack: path.to_string(),
+        checks,
+    })?;
+    Ok(failed)
+}
+
+/// Build the structured comparison between two docpacks
+fn build_compare_report(docpack1: &Docpack, docpack2: &Docpack) -> CompareReport {
+    use std::collections::{BTreeSet, HashMap, HashSet};
+
+    // Get symbol IDs
+    let ids1: HashSet<_> = docpack1.symbols.iter().map(|s| &s.id).collect();
+    let ids2: HashSet<_> = docpack2.symbols.iter().map(|s| &s.id).collect();
+
+    let symbols_only_in = |docpack: &Docpack, other: &HashSet<&String>| {
+        let mut refs: Vec<_> = docpack
+            .symbols
+            .iter()
+            .filter(|s| !other.contains(&s.id))
+            .map(|s| SymbolRef {
+                id: s.id.clone(),
+                kind: s.kind.clone(),
+            })
+            .collect();
+        refs.sort_by(|a, b| a.id.cmp(&b.id));
+        refs.dedup_by(|a, b| a.id == b.id);
+        refs
+    };
+
+    // Compare language summaries
+    let mut all_langs: BTreeSet<_> = docpack1.manifest.language_summary.keys().collect();
+    all_langs.extend(docpack2.manifest.language_summary.keys());
+
+    let languages = all_langs
+        .into_iter()
+        .map(|lang| LanguageDelta {
+            language: lang.clone(),
+            a: *docpack1.manifest.language_summary.get(lang).unwrap_or(&0),
+            b: *docpack2.manifest.language_summary.get(lang).unwrap_or(&0),
+        })
+        .collect();
+
+    let summary = |docpack: &Docpack| PackageSummary {
+        name: docpack.manifest.project.name.clone(),
+        version: docpack.manifest.project.version.clone(),
+        symbols: docpack.symbols.len(),
+    };
+
+    // Compare generics of common symbols. Packs generated before generics
+    // were recorded have none, so only compare when both sides carry them.
+    let generics2: HashMap<_, _> = docpack2
+        .symbols
+        .iter()
+        .filter_map(|s| s.generics.as_ref().map(|g| (&s.id, g)))
+        .collect();
+    let mut generics_changed: Vec<_> = docpack1
+        .symbols
+        .iter()
+        .filter_map(|s| {
+            let a = s.generics.as_ref()?;
+            let b = generics2.get(&s.id)?;
+            (a != *b).then(|| GenericsChange {
+                id: s.id.clone(),
+                a: a.clone(),
+                b: (*b).clone(),
+            })
+        })
+        .collect();
+    generics_changed.sort_by(|a, b| a.id.cmp(&b.id));
+
+    // Symbols that still exist but are now behind a cfg gate are not removed,
+    // just conditionally compiled
+    let cfg1: HashMap<_, _> = docpack1
+        .symbols
+        .iter()
+        .map(|s| (&s.id, s.cfg.as_ref()))
+        .collect();
+    let mut newly_gated: Vec<_> = docpack2
+        .symbols
+        .iter()
+        .filter_map(|s| {
+            let cfg = s.cfg.as_ref()?;
+            (cfg1.get(&s.id)? != &Some(cfg)).then(|| GatedSymbol {
+                id: s.id.clone(),
+                cfg: cfg.clone(),
+            })
+        })
+        .collect();
+    newly_gated.sort_by(|a, b| a.id.cmp(&b.id));
+
+    // Signatures and cyclomatic complexity of symbols in both packs
+    let symbols2: HashMap<_, _> = docpack2.symbols.iter().map(|s| (&s.id, s)).collect();
+    let mut signature_changed = Vec::new();
+    let mut complexity_changed = Vec::new();
+    for a in &docpack1.symbols {
+        let Some(b) = symbols2.get(&a.id) else {
+            continue;
+        };
+        if a.signature != b.signature {
+            signature_changed.push(SignatureChange {
+                id: a.id.clone(),
+                a: a.signature.clone(),
+                b: b.signature.clone(),
+            });
+        }
+        if let (Some(ma), Some(mb)) = (docpack1.metrics_for(a), docpack2.metrics_for(b)) {
+            if ma.cyclomatic_complexity != mb.cyclomatic_complexity {
+                complexity_changed.push(ComplexityChange {
+                    id: a.id.clone(),
+                    a: ma.cyclomatic_complexity,
+                    b: mb.cyclomatic_complexity,
+                });
+            }
+        }
+    }
+    signature_changed.sort_by(|a, b| a.id.cmp(&b.id));
+    signature_changed.dedup_by(|a, b| a.id == b.id);
+    complexity_changed.sort_by(|a, b| a.id.cmp(&b.id));
+    complexity_changed.dedup_by(|a, b| a.id == b.id);
+
+    CompareReport {
+        a: summary(docpack1),
+        b: summary(docpack2),
+        common: ids1.intersection(&ids2).count(),
+        only_in_a: symbols_only_in(docpack1, &ids2),
+        only_in_b: symbols_only_in(docpack2, &ids1),
+        signature_changed,
+        generics_changed,
+        complexity_changed,
+        newly_gated,
+        languages,
+    }
+}
+
+/// List TODO-style markers from every symbol's source snippet
+fn list_todos(path: &str, format: OutputFormat) -> Result<()> {
+    let docpack = open_for_analysis(path)?;
+
+    let mut todos: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter_map(|s| s.source_snippet.as_deref().map(|snippet| (s, snippet)))
+        .flat_map(|(symbol, snippet)| {
+            metrics::find_markers(snippet)
+                .into_iter()
+                .map(move |marker| TodoEntry {
+                    file: symbol.file.clone(),
+                    line: symbol.line + marker.offset,
+                    symbol: symbol.id.clone(),
+                    kind: marker.kind,
+                    text: marker.text,
+                })
+        })
+        .collect();
+    todos.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+
+    format.render(&TodosReport { todos })
+}
+
+/// Match OTLP spans to symbols and aggregate their call counts and latencies
+fn trace_map(path: &str, otlp_path: &str, format: OutputFormat) -> Result<()> {
+    use std::collections::{BTreeSet, HashMap};
+
+    let docpack = open_for_analysis(path)?;
+    let spans = trace::load_otlp_spans(otlp_path)?;
+
+    let mut durations: HashMap<&str, Vec<f64>> = HashMap::new();
+    let mut unmatched = BTreeSet::new();
+
+    for span in &spans {
+        match docpack.resolve_frame(span.frame_name(), span.file.as_deref(), span.line) {
+            Some(symbol) => durations
+                .entry(&symbol.id)
+                .or_default()
+                .push(span.duration_ms),
+            None => {
+                unmatched.insert(span.name.clone());
+            }
+        }
+    }
+
+    let matched_spans = durations.values().map(Vec::len).sum();
+
+    let mut symbols: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter_map(|symbol| {
+            let times = durations.get(symbol.id.as_str())?;
+            let total_ms: f64 = times.iter().sum();
+            Some(TraceMapEntry {
+                symbol: symbol.id.clone(),
+                file: symbol.file.clone(),
+                line: symbol.line,
+                calls: times.len(),
+                total_ms,
+                mean_ms: total_ms / times.len() as f64,
+                max_ms: times.iter().copied().fold(0.0, f64::max),
+            })
+        })
+        .collect();
+    symbols.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+
+    format.render(&TraceMapReport {
+        spans: spans.len(),
+        matched_spans,
+        symbols,
+        unmatched: unmatched.into_iter().collect(),
+    })
+}
+
+/// Find snippet string literals that match a log message, treating format
+/// placeholders ({}, {name}, %s, %(name)s, ${name}, ...) as wildcards
+fn whence(path: &str, message: &str, format: OutputFormat) -> Result<()> {
+    // Literals with fewer fixed characters than this match too many messages
+    const MIN_FIXED_CHARS: usize = 4;
+
+    let placeholder = regex::Regex::new(
+        r"\{[^{}]*\}|\$\{[^}]*\}|%\([^)]*\)[a-z]|%[-+ #0]*\d*(?:\.\d+)?[a-zA-Z%]",
+    )?;
+
+    let docpack = open_for_analysis(path)?;
+    let mut candidates = Vec::new();
+
+    for symbol in &docpack.symbols {
+        let Some(snippet) = &symbol.source_snippet else {
+            continue;
+        };
+
+        for (offset, literal) in metrics::string_literals(snippet) {
+            let fixed: Vec<_> = placeholder.split(literal).collect();
+            let score = fixed.iter().map(|part| part.trim().len()).sum::<usize>();
+            if score < MIN_FIXED_CHARS {
+                continue;
+            }
+
+            if contains_in_order(message, &fixed) {
+                candidates.push((symbol.clone(), offset, literal.to_string(), score));
+            }
+        }
+    }
+
+    let mut matches = Vec::new();
+    for (symbol, offset, literal, score) in candidates {
+        let summary = docpack
+            .get_documentation(&symbol.doc_id)
+            .map(|doc| doc.summary)
+            .unwrap_or_default();
+        matches.push(WhenceMatch {
+            line: symbol.line + offset,
+            symbol: symbol.id,
+            kind: symbol.kind,
+            file: symbol.file,
+            literal,
+            score,
+            summary,
+        });
+    }
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+
+    format.render(&WhenceReport {
+        message: message.to_string(),
+        matches,
+    })
+}
+
+/// Whether `text` contains each of `parts` in order, with anything between them
+fn contains_in_order(text: &str, parts: &[&str]) -> bool {
+    let mut rest = text;
+    parts.iter().all(|part| match rest.find(part) {
+        Some(start) => {
+            rest = &rest[start + part.len()..];
+            true
+        }
+        None => false,
+    })
+}
+
+/// Map each frame of a stack trace on stdin to a symbol and its summary
+fn resolve_trace(path: &str, format: OutputFormat) -> Result<()> {
+    use std::io::Read;
+
+    let docpack = open_for_analysis(path)?;
+
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+
+    let mut frames = Vec::new();
+    for (index, frame) in trace::parse_stack_trace(&input).into_iter().enumerate() {
+        let symbol = docpack
+            .resolve_frame(&frame.function, frame.file.as_deref(), frame.line)
+            .cloned();
+
+        let symbol = match symbol {
+            Some(symbol) => {
+                let summary = docpack
+                    .get_documentation(&symbol.doc_id)
+                    .map(|doc| doc.summary)
+                    .unwrap_or_default();
+                let module = match symbol.id.rsplit_once("::") {
+                    Some((module, _)) => module.to_string(),
+                    None => symbol.file.clone(),
+                };
+                Some(ResolvedSymbol {
+                    id: symbol.id,
+                    kind: symbol.kind,
+                    file: symbol.file,
+                    line: symbol.line,
+                    module,
+                    summary,
+                })
+            }
+            None => None,
+        };
+
+        let location = frame.file.map(|file| match frame.line {
+            Some(line) => format!("{}:{}", file, line),
+            None => file,
+        });
+
+        frames.push(ResolvedFrame {
+            index,
+            function: frame.function,
+            location,
+            symbol,
+        });
+    }
+
+    format.render(&ResolveTraceReport { frames })
+}
+
+/// Attribute profile samples to symbols as self and total time
+fn profile_docpack(path: &str, profile_path: &str, save: bool, format: OutputFormat) -> Result<()> {
+    use std::collections::{BTreeMap, HashSet};
+
+    let docpack = open_for_analysis(path)?;
+    let stacks = trace::load_collapsed_stacks(profile_path)?;
+
+    let samples: u64 = stacks.iter().map(|s| s.count).sum();
+    let mut unattributed_samples = 0;
+    let mut stats: BTreeMap<String, trace::ProfileStats> = BTreeMap::new();
+
+    for stack in &stacks {
+        let resolved: Vec<_> = stack
+            .frames
+            .iter()
+            .map(|frame| docpack.resolve_frame(frame, None, None))
+            .collect();
+
+        match resolved.last() {
+            Some(Some(leaf)) => {
+                stats.entry(leaf.id.clone()).or_default().self_samples += stack.count;
+            }
+            _ => unattributed_samples += stack.count,
+        }
+
+        // Count each symbol once per stack so recursion doesn't inflate totals
+        let on_stack: HashSet<_> = resolved.iter().flatten().map(|s| &s.id).collect();
+        for id in on_stack {
+            stats.entry(id.clone()).or_default().total_samples += stack.count;
+        }
+    }
+
+    let percent = |count: u64| {
+        if samples == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / samples as f64
+        }
+    };
+    for entry in stats.values_mut() {
+        entry.total_percent = percent(entry.total_samples);
+    }
+
+    if save {
+        let mut writer = packer::PackWriter::from_docpack(path)?;
+        writer.set_json("profile.json", &stats)?;
+        writer.write(std::path::Path::new(path))?;
+    }
+
+    let mut symbols: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter_map(|symbol| {
+            let stats = stats.get(&symbol.id)?.clone();
+            Some(ProfileEntry {
+                symbol: symbol.id.clone(),
+                file: symbol.file.clone(),
+                line: symbol.line,
+                self_percent: percent(stats.self_samples),
+                stats,
+            })
+        })
+        .collect();
+    symbols.sort_by(|a, b| {
+        b.stats
+            .self_samples
+            .cmp(&a.stats.self_samples)
+            .then(b.stats.total_samples.cmp(&a.stats.total_samples))
+    });
+
+    format.render(&ProfileReport {
+        samples,
+        unattributed_samples,
+        symbols,
+        saved: save,
+    })
+}
+
+/// List the docpack's non-code files, grouped by category
+fn list_assets(path: &str, category: Option<&str>, format: OutputFormat) -> Result<()> {
+    let docpack = Docpack::open(path)?;
+
+    let mut assets: Vec<_> = docpack
+        .get_assets()?
+        .into_iter()
+        .filter(|a| category.is_none_or(|c| a.category.eq_ignore_ascii_case(c)))
+        .collect();
+    assets.sort_by(|a, b| a.category.cmp(&b.category).then(a.path.cmp(&b.path)));
+
+    format.render(&AssetsReport { assets })
+}
+
+/// Report Rust functions whose snippets use `unsafe`, grouped per file, leaving
+/// out those in `known`. Returns the IDs of all such functions.
+fn list_unsafe(path: &str, known: &BTreeSet<String>, format: OutputFormat) -> Result<Vec<String>> {
+    let docpack = open_for_analysis(path)?;
+
+    let mut functions = Vec::new();
+    let mut files: std::collections::BTreeMap<&str, (usize, usize)> = Default::default();
+
+    for symbol in &docpack.symbols {
+        if symbol.kind != "function" || !symbol.file.ends_with(".rs") {
+            continue;
+        }
+        let Some(snippet) = &symbol.source_snippet else {
+            continue;
+        };
+
+        let unsafe_count = metrics::unsafe_count(snippet);
+        let counts = files.entry(&symbol.file).or_default();
+        counts.0 += 1;
+
+        if unsafe_count > 0 {
+            counts.1 += 1;
+            functions.push(UnsafeEntry {
+                symbol: symbol.id.clone(),
+                file: symbol.file.clone(),
+                line: symbol.line,
+                public: symbol.is_public(),
+                unsafe_count,
+            });
+        }
+    }
+
+    let files = files
+        .into_iter()
+        .map(|(file, (total, unsafe_functions))| UnsafeFileStats {
+            file: file.to_string(),
+            functions: total,
+            unsafe_functions,
+            surface_percent: unsafe_functions as f64 * 100.0 / total as f64,
+        })
+        .collect();
+
+    let findings = functions.iter().map(|f| f.symbol.clone()).collect();
+    functions.retain(|f| !known.contains(&f.symbol));
+    format.render(&UnsafeReport { functions, files })?;
+    Ok(findings)
+}
+
+/// Run the security pattern scan and tag matching symbols, reporting findings not
+/// in `known`. Returns the "symbol: tag" keys of all findings.
+fn scan_docpack(
+    path: &str,
+    rules_path: Option<&str>,
+    sarif: bool,
+    dry_run: bool,
+    known: &BTreeSet<String>,
+    format: OutputFormat,
+) -> Result<Vec<String>> {
+    let mut docpack = Docpack::open(path)?;
+
+    let mut rules = scan::default_rules();
+    if let Some(rules_path) = rules_path {
+        rules.extend(scan::load_rules(rules_path)?);
+    }
+
+    // Tags are still written to every symbol, but ignored files aren't scanned
+    let ignored = ignore::load()?;
+    let owned: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter(|s| !ignored.is_ignored(&s.file))
+        .cloned()
+        .collect();
+    let mut findings = scan::scan(&owned, &rules)?;
+
+    if !dry_run {
+        scan::apply_tags(&mut docpack.symbols, &findings);
+        let mut writer = packer::PackWriter::from_docpack(path)?;
+        writer.set_json("symbols.json", &docpack.symbols)?;
+        writer.write(std::path::Path::new(path))?;
+    }
+
+    let key = |f: &scan::Finding| format!("{}: {}", f.symbol, f.tag);
+    let keys = findings.iter().map(key).collect();
+    findings.retain(|f| !known.contains(&key(f)));
+
+    if sarif {
         println!(
             "{}",
-            format!("{} docpack(s) already up to date", up_to_date_count).dimmed()
+            serde_json::to_string_pretty(&scan::to_sarif(&findings, &rules))?
         );
+        return Ok(keys);
     }
 
-    Ok(())
+    let tagged_symbols = findings
+        .iter()
+        .map(|f| &f.symbol)
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+
+    format.render(&ScanReport {
+        findings,
+        tagged_symbols,
+        written: !dry_run,
+    })?;
+    Ok(keys)
 }
 
-/// Compare two docpacks to find differences
-fn compare_docpacks(path1: &str, path2: &str) -> Result<()> {
-    use std::collections::HashSet;
+/// Replace secrets in snippets with placeholders before a docpack is shared
+fn redact_docpack(path: &str, dry_run: bool, format: OutputFormat) -> Result<()> {
+    let mut docpack = Docpack::open(path)?;
 
-    let docpack1 = Docpack::open(path1)?;
-    let docpack2 = Docpack::open(path2)?;
+    let redactions = scan::redact_secrets(&mut docpack.symbols);
 
-    println!("{}", "Docpack Comparison".bold().cyan());
-    println!("{}", "=".repeat(50));
-    println!();
-
-    // Basic info
-    println!("{}", "Package Information:".bold().green());
-    println!(
-        "  {} {} (v{})",
-        "A:".bold(),
-        docpack1.manifest.project.name.cyan(),
-        docpack1.manifest.project.version
-    );
-    println!(
-        "  {} {} (v{})",
-        "B:".bold(),
-        docpack2.manifest.project.name.cyan(),
-        docpack2.manifest.project.version
-    );
-    println!();
-
-    // Symbol counts
-    println!("{}", "Symbol Counts:".bold().green());
-    println!(
-        "  A: {} symbols",
-        docpack1.symbols.len()
-    );
-    println!(
-        "  B: {} symbols",
-        docpack2.symbols.len()
-    );
-    println!();
+    let written = !dry_run && !redactions.is_empty();
+    if written {
+        let mut writer = packer::PackWriter::from_docpack(path)?;
+        writer.set_json("symbols.json", &docpack.symbols)?;
+        writer.write(std::path::Path::new(path))?;
+    }
 
-    // Get symbol IDs
-    let ids1: HashSet<_> = docpack1.symbols.iter().map(|s| &s.id).collect();
-    let ids2: HashSet<_> = docpack2.symbols.iter().map(|s| &s.id).collect();
+    format.render(&RedactReport {
+        redactions,
+        written,
+    })
+}
+
+/// Record each symbol's last-modifying commit and the issue/PR references
+/// in the commit messages that touched it
+fn enrich_docpack(path: &str, repo: &std::path::Path, format: OutputFormat) -> Result<()> {
+    use std::collections::BTreeSet;
+
+    let mut docpack = Docpack::open(path)?;
+
+    if format.is_text() {
+        println!("{}", t!("reading-git-history").dimmed());
+    }
 
-    // Find differences
-    let only_in_a: Vec<_> = ids1.difference(&ids2).collect();
-    let only_in_b: Vec<_> = ids2.difference(&ids1).collect();
-    let common: Vec<_> = ids1.intersection(&ids2).collect();
-
-    println!("{}", "Symbol Differences:".bold().green());
-    println!(
-        "  Common symbols: {}",
-        common.len().to_string().cyan()
-    );
-    println!(
-        "  Only in A: {}",
-        only_in_a.len().to_string().yellow()
-    );
-    println!(
-        "  Only in B: {}",
-        only_in_b.len().to_string().yellow()
-    );
-    println!();
-
-    // Show symbols only in A (limit to 20)
-    if !only_in_a.is_empty() {
-        println!("{}", "Symbols only in A:".bold().yellow());
-        for (i, id) in only_in_a.iter().enumerate() {
-            if i >= 20 {
-                println!("  ... and {} more", only_in_a.len() - 20);
-                break;
+    let mut references = BTreeSet::new();
+    let mut referenced_symbols = 0;
+    let mut skipped_symbols = 0;
+
+    let mut dated_symbols = 0;
+
+    for symbol in &mut docpack.symbols {
+        let history = enrich::issue_refs(repo, symbol)
+            .and_then(|refs| Ok((refs, enrich::last_modified(repo, symbol)?)));
+        let Ok((refs, last_modified)) = history else {
+            skipped_symbols += 1;
+            continue;
+        };
+
+        if !refs.is_empty() {
+            referenced_symbols += 1;
+        }
+        references.extend(refs.iter().cloned());
+        enrich::set_ref_tags(symbol, &refs);
+
+        if last_modified.is_some() {
+            dated_symbols += 1;
+        }
+        symbol.last_modified = last_modified;
+    }
+
+    let mut writer = packer::PackWriter::from_docpack(path)?;
+    writer.set_json("symbols.json", &docpack.symbols)?;
+    writer.write(std::path::Path::new(path))?;
+
+    format.render(&EnrichReport {
+        docpack: path.to_string(),
+        referenced_symbols,
+        references: references.len(),
+        dated_symbols,
+        skipped_symbols,
+    })
+}
+
+/// Public functions listed as reaching each panicking function
+const MAX_PANIC_ROOTS: usize = 5;
+
+/// Count panic/exception sources in functions reachable from the public API,
+/// ranked by how many they contain and leaving out those in `known`. Returns the
+/// IDs of all functions with any.
+fn list_panics(path: &str, known: &BTreeSet<String>, format: OutputFormat) -> Result<Vec<String>> {
+    use std::collections::VecDeque;
+
+    let docpack = open_for_analysis(path)?;
+    let graph = docpack.call_graph();
+
+    // One pass from every public function at once. Each symbol collects a few of
+    // the roots reaching it: MAX_PANIC_ROOTS to list, one to tell there are more,
+    // and one for a root itself. A symbol is queued again only when its collection
+    // grows, so it is visited at most `limit` times.
+    let limit = MAX_PANIC_ROOTS + 2;
+    let mut reached = vec![false; docpack.symbols.len()];
+    let mut roots: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); docpack.symbols.len()];
+    let mut queue = VecDeque::new();
+    for (i, symbol) in docpack.symbols.iter().enumerate() {
+        if symbol.is_public() {
+            reached[i] = true;
+            roots[i].insert(i);
+            queue.push_back(i);
+        }
+    }
+    while let Some(current) = queue.pop_front() {
+        let from: Vec<usize> = roots[current].iter().copied().collect();
+        for callee in graph.callees[current].iter().map(|edge| edge.symbol) {
+            let to = &mut roots[callee];
+            let before = to.len();
+            for &root in &from {
+                if to.len() >= limit {
+                    break;
+                }
+                to.insert(root);
             }
-            if let Some(sym) = docpack1.symbols.iter().find(|s| &s.id == **id) {
-                println!(
-                    "  {} {}",
-                    format!("[{}]", sym.kind).dimmed(),
-                    id.green()
-                );
+            if !reached[callee] || to.len() > before {
+                reached[callee] = true;
+                queue.push_back(callee);
             }
         }
-        println!();
     }
 
-    // Show symbols only in B (limit to 20)
-    if !only_in_b.is_empty() {
-        println!("{}", "Symbols only in B:".bold().yellow());
-        for (i, id) in only_in_b.iter().enumerate() {
-            if i >= 20 {
-                println!("  ... and {} more", only_in_b.len() - 20);
-                break;
+    let mut functions: Vec<_> = docpack
+        .symbols
+        .iter()
+        .enumerate()
+        .filter_map(|(i, symbol)| {
+            if !reached[i] {
+                return None;
             }
-            if let Some(sym) = docpack2.symbols.iter().find(|s| &s.id == **id) {
-                println!(
-                    "  {} {}",
-                    format!("[{}]", sym.kind).dimmed(),
-                    id.green()
-                );
+            let sources = metrics::panic_sources(symbol.source_snippet.as_deref()?);
+            if sources.is_empty() {
+                return None;
+            }
+            let others: Vec<usize> = roots[i]
+                .iter()
+                .copied()
+                .filter(|&root| docpack.symbols[root].id != symbol.id)
+                .collect();
+            Some(PanicEntry {
+                symbol: symbol.id.clone(),
+                file: symbol.file.clone(),
+                line: symbol.line,
+                public: symbol.is_public(),
+                reachable_from: others
+                    .iter()
+                    .take(MAX_PANIC_ROOTS)
+                    .map(|&root| docpack.symbols[root].id.clone())
+                    .collect(),
+                more_roots: others.len() > MAX_PANIC_ROOTS,
+                total: sources.values().sum(),
+                sources: sources
+                    .into_iter()
+                    .map(|(kind, count)| (kind.to_string(), count))
+                    .collect(),
+            })
+        })
+        .collect();
+    functions.sort_by_key(|f| std::cmp::Reverse(f.total));
+
+    let findings = functions.iter().map(|f| f.symbol.clone()).collect();
+    functions.retain(|f| !known.contains(&f.symbol));
+    format.render(&PanicsReport { functions })?;
+    Ok(findings)
+}
+
+/// Find fallible functions, the public functions their errors surface in, and
+/// non-fallible callers that swallow their errors, leaving out those in `known`.
+/// Returns the "caller -> callee" keys of all swallowed errors.
+fn error_paths(path: &str, known: &BTreeSet<String>, format: OutputFormat) -> Result<Vec<String>> {
+    use std::collections::VecDeque;
+
+    let docpack = open_for_analysis(path)?;
+    let graph = docpack.call_graph();
+    let is_fallible: Vec<bool> = docpack.symbols.iter().map(|s| s.is_fallible()).collect();
+
+    // One pass from every public fallible function at once, carrying each down
+    // through fallible callees, which pass the error on to it. `pending` holds the
+    // roots a symbol has yet to pass to its callees, so each root crosses an edge once.
+    let mut surfaces: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); docpack.symbols.len()];
+    let mut pending: Vec<Vec<usize>> = vec![Vec::new(); docpack.symbols.len()];
+    let mut queue = VecDeque::new();
+    for (i, symbol) in docpack.symbols.iter().enumerate() {
+        if is_fallible[i] && symbol.is_public() {
+            pending[i].push(i);
+            queue.push_back(i);
+        }
+    }
+    while let Some(current) = queue.pop_front() {
+        let roots = std::mem::take(&mut pending[current]);
+        for callee in graph.callees[current].iter().map(|edge| edge.symbol) {
+            if !is_fallible[callee] {
+                continue;
+            }
+            let idle = pending[callee].is_empty();
+            for &root in &roots {
+                if root != callee && surfaces[callee].insert(root) {
+                    pending[callee].push(root);
+                }
+            }
+            if idle && !pending[callee].is_empty() {
+                queue.push_back(callee);
             }
         }
-        println!();
     }
 
-    // Compare language summaries
-    println!("{}", "Language Comparison:".bold().green());
-    let mut all_langs: HashSet<_> = docpack1.manifest.language_summary.keys().collect();
-    all_langs.extend(docpack2.manifest.language_summary.keys());
+    let mut fallible = Vec::new();
+    let mut swallowed = Vec::new();
 
-    for lang in all_langs {
-        let count1 = docpack1.manifest.language_summary.get(lang).unwrap_or(&0);
-        let count2 = docpack2.manifest.language_summary.get(lang).unwrap_or(&0);
-        if count1 != count2 {
-            println!(
-                "  {}: {} → {}",
-                lang,
-                count1.to_string().dimmed(),
-                count2.to_string().cyan()
-            );
-        } else {
-            println!("  {}: {}", lang, count1);
+    for (i, symbol) in docpack.symbols.iter().enumerate() {
+        if !is_fallible[i] {
+            continue;
+        }
+        let surfaces_in: BTreeSet<String> = surfaces[i]
+            .iter()
+            .map(|&root| docpack.symbols[root].id.clone())
+            .collect();
+
+        fallible.push(ErrorPath {
+            symbol: symbol.id.clone(),
+            file: symbol.file.clone(),
+            line: symbol.line,
+            public: symbol.is_public(),
+            surfaces_in: surfaces_in.into_iter().collect(),
+        });
+
+        for caller in graph.callers[i]
+            .iter()
+            .map(|edge| &docpack.symbols[edge.symbol])
+        {
+            if caller.is_fallible() {
+                continue;
+            }
+            let snippet = caller.source_snippet.as_deref().unwrap_or_default();
+            let handling = if snippet.contains(".unwrap()") || snippet.contains(".expect(") {
+                "unwrap"
+            } else if snippet.contains("let _ =")
+                || snippet.contains(".ok()")
+                || snippet.contains(".is_ok()")
+                || snippet.contains(".is_err()")
+                || snippet.contains(".unwrap_or")
+            {
+                "discarded"
+            } else if snippet.contains("catch") || snippet.contains("except") {
+                "caught"
+            } else {
+                "handled"
+            };
+            swallowed.push(SwallowedError {
+                caller: caller.id.clone(),
+                callee: symbol.id.clone(),
+                file: caller.file.clone(),
+                line: caller.line,
+                handling: handling.to_string(),
+            });
         }
     }
 
-    Ok(())
+    let key = |s: &SwallowedError| format!("{} -> {}", s.caller, s.callee);
+    let findings = swallowed.iter().map(key).collect();
+    swallowed.retain(|s| !known.contains(&key(s)));
+    format.render(&ErrorsReport {
+        fallible,
+        swallowed,
+    })?;
+    Ok(findings)
+}
+
+/// Report async entry points, sync functions that drive async code, and sync
+/// functions that call async ones without driving them. Calls are read from snippets.
+/// Undriven calls in `known` are left out. Returns the "caller calls async callee"
+/// keys of all of them.
+fn async_map(path: &str, known: &BTreeSet<String>, format: OutputFormat) -> Result<Vec<String>> {
+    /// Calls that run async code to completion from a sync context. Spawns count
+    /// only from an async runtime, not a bare `spawn` like `std::thread::spawn`.
+    const DRIVERS: &[&str] = &[
+        "block_on",
+        "run_until_complete",
+        "asyncio::run",
+        "tokio::spawn",
+        "tokio::task::spawn",
+        "tokio::task::spawn_local",
+        "async_std::task::spawn",
+        "smol::spawn",
+        "create_task",
+        "ensure_future",
+    ];
+
+    let docpack = open_for_analysis(path)?;
+    let graph = docpack.call_graph();
+
+    let site = |symbol: &models::Symbol, detail: String| AsyncSite {
+        symbol: symbol.id.clone(),
+        file: symbol.file.clone(),
+        line: symbol.line,
+        detail,
+    };
+
+    let mut entry_points = Vec::new();
+    for (i, symbol) in docpack.symbols.iter().enumerate() {
+        if !symbol.is_async() {
+            continue;
+        }
+        let runtime = symbol.attributes.iter().find(|a| {
+            a.contains("::main") || a.contains("::test") || a.contains("actix_web::main")
+        });
+        if let Some(attr) = runtime {
+            entry_points.push(site(symbol, attr.clone()));
+        } else if graph.callers[i].is_empty() {
+            entry_points.push(site(symbol, "not called within the docpack".to_string()));
+        }
+    }
+
+    let mut boundaries = Vec::new();
+    let mut sync_callers = Vec::new();
+    for symbol in docpack.symbols.iter().filter(|s| !s.is_async()) {
+        // Drivers come from runtimes outside the docpack, so they are matched by path
+        let calls = symbol
+            .source_snippet
+            .as_deref()
+            .map(metrics::call_paths)
+            .unwrap_or_default();
+        let driver = calls.iter().find(|call| {
+            DRIVERS
+                .iter()
+                .any(|d| *call == d || call.ends_with(&format!("::{}", d)))
+        });
+        if let Some(driver) = driver {
+            boundaries.push(site(symbol, format!("calls {}", driver)));
+            continue;
+        }
+
+        for callee in docpack.callees(symbol) {
+            if callee.is_async() {
+                sync_callers.push(site(symbol, format!("calls async {}", callee.id)));
+            }
+        }
+    }
+
+    let key = |site: &AsyncSite| format!("{} {}", site.symbol, site.detail);
+    let findings = sync_callers.iter().map(key).collect();
+    sync_callers.retain(|site| !known.contains(&key(site)));
+    format.render(&AsyncMapReport {
+        async_functions: docpack.symbols.iter().filter(|s| s.is_async()).count(),
+        entry_points,
+        boundaries,
+        sync_callers,
+    })?;
+    Ok(findings)
+}
+
+/// Find constructor functions (`new`, `build`, `from_*`, factories, ...) and the
+/// known types their snippets instantiate
+fn construction_graph(path: &str, format: OutputFormat) -> Result<()> {
+    use std::collections::{BTreeSet, HashSet};
+
+    const TYPE_KINDS: &[&str] = &["struct", "class", "enum", "union"];
+
+    let docpack = open_for_analysis(path)?;
+
+    let segments = |id: &str| -> Vec<String> {
+        id.split([':', '.'])
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let types: HashSet<String> = docpack
+        .symbols
+        .iter()
+        .filter(|s| TYPE_KINDS.contains(&s.kind.as_str()))
+        .filter_map(|s| segments(&s.id).pop())
+        .collect();
+
+    let mut constructors = Vec::new();
+    for symbol in &docpack.symbols {
+        let path = segments(&symbol.id);
+        let Some(name) = path.last() else {
+            continue;
+        };
+        let is_constructor = symbol.kind == "constructor"
+            || matches!(
+                name.as_str(),
+                "new" | "build" | "create" | "default" | "__init__" | "constructor"
+            )
+            || ["new_", "from_", "with_", "build_", "create_", "make_"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix));
+        if !is_constructor {
+            continue;
+        }
+
+        // Methods belong to their parent type; free factories to the type they return
+        let owner = path
+            .len()
+            .checked_sub(2)
+            .map(|i| path[i].clone())
+            .filter(|parent| types.contains(parent))
+            .or_else(|| {
+                let returned = symbol.signature.rsplit_once("->")?.1;
+                types
+                    .iter()
+                    .find(|t| {
+                        returned
+                            .split(|c: char| !c.is_alphanumeric() && c != '_')
+                            .any(|w| w == t.as_str())
+                    })
+                    .cloned()
+            });
+        let Some(owner) = owner else {
+            continue;
+        };
+
+        let constructs: BTreeSet<String> = symbol
+            .source_snippet
+            .as_deref()
+            .map(metrics::call_paths)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|call| {
+                // "Lexer::new" or "Lexer" (struct literal, tuple struct, class call)
+                let parts: Vec<_> = call.split("::").collect();
+                let candidate = match parts.len() {
+                    1 => parts[0],
+                    n => parts[n - 2],
+                };
+                (types.contains(candidate) && candidate != owner).then(|| candidate.to_string())
+            })
+            .collect();
+
+        constructors.push(ConstructionEntry {
+            owner,
+            constructor: symbol.id.clone(),
+            file: symbol.file.clone(),
+            line: symbol.line,
+            constructs: constructs.into_iter().collect(),
+        });
+    }
+
+    constructors.sort_by(|a, b| {
+        a.owner
+            .cmp(&b.owner)
+            .then(a.constructor.cmp(&b.constructor))
+    });
+
+    format.render(&ConstructionReport { constructors })
+}
+
+/// Rank symbols by age since their last change, weighted up for high
+/// complexity and missing documentation
+fn list_stale(path: &str, limit: usize, format: OutputFormat) -> Result<()> {
+    const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+    let docpack = open_for_analysis(path)?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs() as i64;
+
+    let dated: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter(|s| s.last_modified.is_some())
+        .cloned()
+        .collect();
+
+    let mut entries = Vec::new();
+    for symbol in dated {
+        let Some(last_modified) = symbol.last_modified.clone() else {
+            continue;
+        };
+        let age_days = ((now - last_modified.timestamp) / SECONDS_PER_DAY).max(0) as u64;
+        let complexity = docpack
+            .metrics_for(&symbol)
+            .map(|m| m.cyclomatic_complexity);
+        let documented = docpack
+            .get_documentation(&symbol.doc_id)
+            .is_ok_and(|doc| !doc.summary.trim().is_empty());
+
+        let mut score = age_days as f64 * (1.0 + complexity.unwrap_or(1) as f64 / 10.0);
+        if !documented {
+            score *= 2.0;
+        }
+
+        entries.push(StaleEntry {
+            symbol: symbol.id,
+            file: symbol.file,
+            line: symbol.line,
+            last_modified,
+            age_days,
+            complexity,
+            documented,
+            score,
+        });
+    }
+
+    entries.sort_by(|a, b| b.score.total_cmp(&a.score));
+    entries.truncate(limit);
+
+    format.render(&StaleReport { symbols: entries })
+}
+
+/// Rewrite a docpack with every entry but the manifest encrypted, or all in the clear
+fn set_encryption(
+    path: &str,
+    encrypt: bool,
+    output: Option<&std::path::Path>,
+    format: OutputFormat,
+) -> Result<()> {
+    use anyhow::Context;
+
+    let key = config::docpack_key()?.context(format!(
+        "No encryption key; set {} or `key` under [encryption] in {}",
+        config::KEY_ENV,
+        config::default_path()?.display()
+    ))?;
+
+    let mut docpack = Docpack::open(path)?;
+    docpack.manifest.encrypted = encrypt;
+
+    let mut writer = packer::PackWriter::from_docpack(path)?;
+    writer.set_json("manifest.json", &docpack.manifest)?;
+    writer.set_key(encrypt.then_some(key));
+
+    let dest = output.unwrap_or(std::path::Path::new(path));
+    writer.write(dest)?;
+
+    format.render(&EncryptionReport {
+        docpack: dest.display().to_string(),
+        encrypted: encrypt,
+    })
+}
+
+/// Remove the selected sections and record them in the manifest's `stripped` list
+fn prune_docs(
+    path: &str,
+    mut sections: Vec<PruneSection>,
+    output: Option<&std::path::Path>,
+    format: OutputFormat,
+) -> Result<()> {
+    if sections.is_empty() {
+        sections = vec![
+            PruneSection::Docs,
+            PruneSection::Examples,
+            PruneSection::Snippets,
+        ];
+    }
+    sections.sort();
+    sections.dedup();
+
+    let mut docpack = Docpack::open(path)?;
+    let mut writer = packer::PackWriter::from_docpack(path)?;
+
+    let mut removed_entries = 0;
+    for section in &sections {
+        removed_entries += match section {
+            PruneSection::Docs => writer.remove_matching(|name| name.starts_with("docs/")),
+            PruneSection::Examples => writer.remove_matching(|name| name.starts_with("examples/")),
+            PruneSection::Metrics => writer.remove_matching(|name| name == "metrics.json"),
+            PruneSection::Profile => writer.remove_matching(|name| name == "profile.json"),
+            PruneSection::Assets => writer.remove_matching(|name| name == "assets.json"),
+            PruneSection::Snippets => {
+                let snippets = docpack
+                    .symbols
+                    .iter_mut()
+                    .filter_map(|s| s.source_snippet.take())
+                    .count();
+                writer.set_json("symbols.json", &docpack.symbols)?;
+                snippets
+            }
+        };
+    }
+
+    for section in &sections {
+        if !docpack
+            .manifest
+            .stripped
+            .iter()
+            .any(|s| s == section.name())
+        {
+            docpack.manifest.stripped.push(section.name().to_string());
+        }
+    }
+    writer.set_json("manifest.json", &docpack.manifest)?;
+
+    let dest = output.unwrap_or(std::path::Path::new(path));
+    let size_before = std::fs::metadata(path)?.len();
+    writer.write(dest)?;
+    let size_after = std::fs::metadata(dest)?.len();
+
+    format.render(&PruneReport {
+        docpack: dest.display().to_string(),
+        stripped: sections.iter().map(|s| s.name().to_string()).collect(),
+        removed_entries,
+        size_before,
+        size_after,
+    })
+}
+
+/// Compute metrics for every symbol with a source snippet and store them in metrics.json
+fn recompute_metrics(path: &str, format: OutputFormat) -> Result<()> {
+    let docpack = Docpack::open(path)?;
+
+    let computed: std::collections::BTreeMap<_, _> = docpack
+        .symbols
+        .iter()
+        .filter_map(|s| {
+            s.source_snippet
+                .as_deref()
+                .map(|snippet| (s.id.clone(), metrics::compute(snippet)))
+        })
+        .collect();
+
+    let mut writer = packer::PackWriter::from_docpack(path)?;
+    writer.set_json("metrics.json", &computed)?;
+    writer.write(std::path::Path::new(path))?;
+
+    format.render(&RecomputeMetricsReport {
+        docpack: path.to_string(),
+        measured_symbols: computed.len(),
+    })
+}
+
+fn reindex(path: &str, format: OutputFormat) -> Result<()> {
+    let docpack = Docpack::open(path)?;
+
+    // Writing a pack always refreshes its index.json
+    let writer = packer::PackWriter::from_docpack(path)?;
+    writer.write(std::path::Path::new(path))?;
+
+    format.render(&ReindexReport {
+        docpack: path.to_string(),
+        indexed_symbols: docpack.symbols.len(),
+    })
 }
 
 /// Generate shell completions
@@ -1227,8 +3126,106 @@ fn generate_completions(shell: Shell) {
 }
 
 /// Start an MCP server for AI agent access
-fn serve_mcp() -> Result<()> {
-    let packages_dir = get_packages_dir()?;
-    let server = mcp::McpServer::new(packages_dir);
-    server.run()
+fn serve_mcp(
+    http: Option<&str>,
+    packages_dirs: Vec<PathBuf>,
+    log_file: Option<&std::path::Path>,
+    profile: Option<config::McpProfile>,
+    config_path: Option<&std::path::Path>,
+) -> Result<()> {
+    let config = config::load(config_path)?;
+    let profile = profile.or(config.mcp.profile).unwrap_or_default();
+
+    let packages_dirs = if !packages_dirs.is_empty() {
+        packages_dirs
+    } else if !config.mcp.packages_dirs.is_empty() {
+        config.mcp.packages_dirs()?
+    } else {
+        default_packages_dirs()?
+    };
+    let mut server = mcp::McpServer::new(packages_dirs)
+        .with_tools(profile, config.mcp.tools)
+        .with_search(config.search);
+    if let Some(log_file) = log_file {
+        server = server.with_log_file(log_file)?;
+    }
+    match http {
+        Some(addr) => server.run_http(addr),
+        None => server.run(),
+    }
+}
+
+/// Summarize which tools agents call, how long they take, and what they ask for
+fn mcp_stats(log_file: &std::path::Path, top: usize, format: OutputFormat) -> Result<()> {
+    use anyhow::Context;
+    use std::collections::{BTreeMap, HashMap};
+    use std::fs;
+
+    let content = fs::read_to_string(log_file)
+        .context(format!("Failed to read log file {}", log_file.display()))?;
+
+    let mut entries = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: mcp::LogEntry = serde_json::from_str(line).context(format!(
+            "Line {} of {} is not an MCP log entry",
+            i + 1,
+            log_file.display()
+        ))?;
+        entries.push(entry);
+    }
+
+    let mut by_tool: BTreeMap<String, Vec<&mcp::LogEntry>> = BTreeMap::new();
+    let mut arguments: HashMap<(String, String), usize> = HashMap::new();
+    for entry in &entries {
+        let name = entry.tool.clone().unwrap_or_else(|| entry.method.clone());
+        by_tool.entry(name).or_default().push(entry);
+
+        if let Some(args) = entry.arguments.as_object() {
+            for (key, value) in args {
+                if let Some(value) = value.as_str() {
+                    *arguments
+                        .entry((key.clone(), value.to_string()))
+                        .or_default() += 1;
+                }
+            }
+        }
+    }
+
+    let mut tools: Vec<McpToolStats> = by_tool
+        .into_iter()
+        .map(|(name, calls)| {
+            let latencies: Vec<f64> = calls.iter().map(|e| e.latency_ms).collect();
+            McpToolStats {
+                name,
+                calls: calls.len(),
+                errors: calls.iter().filter(|e| e.is_error).count(),
+                avg_latency_ms: latencies.iter().sum::<f64>() / calls.len() as f64,
+                max_latency_ms: latencies.iter().cloned().fold(0.0, f64::max),
+                avg_result_bytes: calls.iter().map(|e| e.result_bytes).sum::<usize>() / calls.len(),
+            }
+        })
+        .collect();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then(a.name.cmp(&b.name)));
+
+    let mut top_arguments: Vec<McpArgumentCount> = arguments
+        .into_iter()
+        .map(|((key, value), count)| McpArgumentCount { key, value, count })
+        .collect();
+    top_arguments.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| (&a.key, &a.value).cmp(&(&b.key, &b.value)))
+    });
+    top_arguments.truncate(top);
+
+    format.render(&McpStatsReport {
+        requests: entries.len(),
+        first_timestamp: entries.iter().map(|e| e.timestamp).min(),
+        last_timestamp: entries.iter().map(|e| e.timestamp).max(),
+        tools,
+        top_arguments,
+    })
 }
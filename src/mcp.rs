@@ -1,9 +1,19 @@
-use crate::docpack::Docpack;
-use anyhow::Result;
+use crate::config::{McpProfile, SearchConfig, ToolOverride};
+use crate::docpack::{Call, Docpack, SearchMatch};
+use crate::models::{Documentation, Symbol};
+use crate::query::Query;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 // JSON-RPC 2.0 types
 #[derive(Debug, Deserialize)]
@@ -40,52 +50,555 @@ struct Tool {
     description: String,
     #[serde(rename = "inputSchema")]
     input_schema: Value,
+    /// Behavior hints, filled in for every tool by `handle_tools_list`
+    #[serde(skip_serializing_if = "Value::is_null")]
+    annotations: Value,
+}
+
+/// Built-in tools that only read installed docpacks
+const READ_ONLY_TOOLS: &[&str] = &[
+    "list_packages",
+    "health",
+    "list_symbols",
+    "get_symbol",
+    "get_examples",
+    "get_source",
+    "list_modules",
+    "get_module",
+    "get_module_overview",
+    "get_architecture",
+    "get_callers",
+    "get_callees",
+    "get_dependencies",
+    "diff_packages",
+    "search",
+];
+
+/// Types and functions get_module_overview lists per section
+const MODULE_OVERVIEW_LIMIT: usize = 15;
+
+/// Other matches get_symbol lists after the best one
+const OTHER_MATCHES_LIMIT: usize = 20;
+
+/// URI prefix of packages and symbols exposed as MCP resources
+const RESOURCE_SCHEME: &str = "docpack://";
+
+/// Symbols listed per section of the compare-versions prompt
+const API_DIFF_LIMIT: usize = 200;
+
+/// Resources per `resources/list` page
+const RESOURCES_PAGE: usize = 1000;
+
+/// Most values one `completion/complete` result may hold
+const COMPLETION_LIMIT: usize = 100;
+
+/// Page sizes when `limit` isn't given
+const DEFAULT_SYMBOLS_LIMIT: usize = 500;
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// Tool results longer than this are split into several content blocks
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Content blocks sent per response; clients fetch the rest with the `chunk` argument
+const CHUNKS_PER_RESPONSE: usize = 16;
+
+/// One line of the `--log-file` request log
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Unix time the request was received, in seconds
+    pub timestamp: u64,
+    pub method: String,
+    /// Tool name for `tools/call` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub arguments: Value,
+    pub latency_ms: f64,
+    /// Size of the serialized response
+    pub result_bytes: usize,
+    pub is_error: bool,
+    /// Problems noticed while handling the request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Severity of a diagnostic, the syslog levels used by MCP's `logging` capability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// A problem the server noticed, such as a docpack that failed to open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Requests slower than this are reported as warnings
+const SLOW_REQUEST_MS: f64 = 1000.0;
+
+/// Threads running tool calls concurrently on the stdio transport
+const WORKER_THREADS: usize = 4;
+
+/// Opened docpacks kept in memory; the least recently used is dropped past this
+const CACHED_DOCPACKS: usize = 8;
+
+thread_local! {
+    /// Diagnostics of the request being handled; each request runs on one thread
+    static DIAGNOSTICS: RefCell<Vec<Diagnostic>> = const { RefCell::new(Vec::new()) };
+    /// ID of the request being handled, for checking whether it was cancelled
+    static CURRENT_REQUEST: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// What to do after handling one incoming message
+enum Reply {
+    /// The response (none for notifications and cancelled requests),
+    /// preceded by `notifications/message` notifications
+    Response(Option<String>, Vec<String>),
+    Exit,
+}
+
+/// The parts of an HTTP request the MCP transport needs
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// Header names lowercased
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// An opened docpack and the modification time of the file it was read from
+struct CachedDocpack {
+    modified: SystemTime,
+    docpack: Arc<Docpack>,
+    /// When a request last used it, for evicting the least recently used
+    last_used: Instant,
+}
+
+/// A docpack file found in one of the package roots
+struct InstalledPackage {
+    /// "user:repo"
+    name: String,
+    path: PathBuf,
+    /// Index of the root it was found in
+    root: usize,
+    /// An earlier root has a package with the same name
+    shadowed: bool,
 }
 
 pub struct McpServer {
-    packages_dir: PathBuf,
+    /// Directories holding docpacks; earlier ones take precedence for the same package
+    packages_dirs: Vec<PathBuf>,
+    log_file: Option<Mutex<File>>,
+    started: Instant,
+    /// Set by `shutdown`; later requests other than `exit` are rejected
+    shutting_down: AtomicBool,
+    profile: McpProfile,
+    /// Renamed, redescribed, or disabled tools, keyed by built-in name
+    tool_overrides: BTreeMap<String, ToolOverride>,
+    /// Alias groups expanding `search` queries
+    search: SearchConfig,
+    /// Least severe diagnostic sent to the client, set by `logging/setLevel`
+    log_level: Mutex<LogLevel>,
+    /// Requests being handled or waiting for a worker, keyed by JSON-encoded ID,
+    /// and whether the client has cancelled them
+    in_flight: Mutex<HashMap<String, bool>>,
+    /// Docpacks opened so far, keyed by path
+    docpacks: Mutex<HashMap<String, CachedDocpack>>,
 }
 
 impl McpServer {
-    pub fn new(packages_dir: PathBuf) -> Self {
-        McpServer { packages_dir }
+    pub fn new(packages_dirs: Vec<PathBuf>) -> Self {
+        McpServer {
+            packages_dirs,
+            log_file: None,
+            started: Instant::now(),
+            shutting_down: AtomicBool::new(false),
+            profile: McpProfile::default(),
+            tool_overrides: BTreeMap::new(),
+            search: SearchConfig::default(),
+            log_level: Mutex::new(LogLevel::Warning),
+            in_flight: Mutex::new(HashMap::new()),
+            docpacks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Advertise the tools of `profile`, with names and descriptions from `overrides`
+    pub fn with_tools(
+        mut self,
+        profile: McpProfile,
+        overrides: BTreeMap<String, ToolOverride>,
+    ) -> Self {
+        self.profile = profile;
+        self.tool_overrides = overrides;
+        self
+    }
+
+    /// Expand `search` queries with the alias groups in `search`
+    pub fn with_search(mut self, search: SearchConfig) -> Self {
+        self.search = search;
+        self
     }
 
+    /// Append a JSON line per request to `path`, for `localdoc mcp-stats`
+    pub fn with_log_file(mut self, path: &Path) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context(format!("Failed to open log file {}", path.display()))?;
+        self.log_file = Some(Mutex::new(file));
+        Ok(self)
+    }
+
+    /// Serve over stdin/stdout. Tool calls, resource reads, and prompts run on
+    /// worker threads so a slow one doesn't hold up pings or cancellations; other
+    /// messages are handled in order as they arrive. Responses are written as each
+    /// request finishes, so concurrent tool calls may be answered out of order.
     pub fn run(&self) -> Result<()> {
         let stdin = std::io::stdin();
-        let mut stdout = std::io::stdout();
         let reader = BufReader::new(stdin.lock());
+        let (jobs, queue) = mpsc::channel::<String>();
+        let queue = Mutex::new(queue);
 
-        for line in reader.lines() {
-            let line = line?;
-            if line.trim().is_empty() {
-                continue;
+        std::thread::scope(|scope| -> Result<()> {
+            for _ in 0..WORKER_THREADS {
+                scope.spawn(|| loop {
+                    // Release the queue before handling the job
+                    let job = lock(&queue).recv();
+                    let Ok(line) = job else {
+                        break;
+                    };
+                    let handled = self
+                        .handle_message(&line)
+                        .and_then(|reply| write_reply(&reply));
+                    if let Err(e) = handled {
+                        eprintln!("Error handling MCP request: {:#}", e);
+                    }
+                });
             }
 
-            let request: JsonRpcRequest = match serde_json::from_str(&line) {
-                Ok(req) => req,
-                Err(e) => {
-                    let error_response = JsonRpcResponse {
-                        jsonrpc: "2.0".to_string(),
-                        id: Value::Null,
-                        result: None,
-                        error: Some(JsonRpcError {
-                            code: -32700,
-                            message: format!("Parse error: {}", e),
-                            data: None,
-                        }),
-                    };
-                    writeln!(stdout, "{}", serde_json::to_string(&error_response)?)?;
-                    stdout.flush()?;
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
                     continue;
                 }
-            };
 
-            let response = self.handle_request(request);
-            writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
-            stdout.flush()?;
+                if let Some(id) = background_request_id(&line) {
+                    // Registered now so it can be cancelled while waiting for a worker
+                    lock(&self.in_flight).entry(id).or_insert(false);
+                    jobs.send(line)?;
+                    continue;
+                }
+
+                match self.handle_message(&line)? {
+                    Reply::Exit => break,
+                    reply => write_reply(&reply)?,
+                }
+            }
+
+            // Workers finish queued requests, then stop
+            drop(jobs);
+            Ok(())
+        })?;
+
+        self.finish()
+    }
+
+    /// Serve the same handlers over MCP's Streamable HTTP transport: each JSON-RPC
+    /// message is POSTed to `/mcp`, and the reply comes back as JSON, or as a single
+    /// server-sent event when the client only accepts `text/event-stream`.
+    /// Each connection is served on its own thread, with read and write timeouts
+    /// so an idle client can't hold a thread forever, and at most
+    /// `MAX_HTTP_CONNECTIONS` at once.
+    pub fn run_http(&self, addr: &str) -> Result<()> {
+        let listener = TcpListener::bind(addr).context(format!("Failed to listen on {}", addr))?;
+        let local_addr = listener.local_addr()?;
+        eprintln!("MCP server listening on http://{}/mcp", local_addr);
+        let exiting = AtomicBool::new(false);
+        // Connections being served, and a signal for when one closes
+        let connections = (Mutex::new(0usize), Condvar::new());
+
+        std::thread::scope(|scope| {
+            for stream in listener.incoming() {
+                if exiting.load(Ordering::SeqCst) {
+                    break;
+                }
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        eprintln!("Error accepting HTTP connection: {}", e);
+                        continue;
+                    }
+                };
+                {
+                    let (open, closed) = &connections;
+                    let mut open = lock(open);
+                    while *open >= MAX_HTTP_CONNECTIONS {
+                        open = closed.wait(open).unwrap_or_else(|e| e.into_inner());
+                    }
+                    *open += 1;
+                }
+                let (exiting, connections) = (&exiting, &connections);
+                scope.spawn(move || {
+                    match self.serve_http_connection(stream) {
+                        Ok(true) => {
+                            exiting.store(true, Ordering::SeqCst);
+                            // Wake the accept loop so it sees the flag
+                            let _ = TcpStream::connect(local_addr);
+                        }
+                        Ok(false) => {}
+                        Err(e) => eprintln!("Error handling HTTP connection: {:#}", e),
+                    }
+                    let (open, closed) = connections;
+                    *lock(open) -= 1;
+                    closed.notify_one();
+                });
+            }
+        });
+
+        self.finish()
+    }
+
+    /// Answer the one request on an HTTP connection; true when it asked the server to exit
+    fn serve_http_connection(&self, mut stream: TcpStream) -> Result<bool> {
+        stream.set_read_timeout(Some(HTTP_TIMEOUT))?;
+        stream.set_write_timeout(Some(HTTP_TIMEOUT))?;
+
+        let request = match read_http_request(&mut stream) {
+            Ok(request) => request,
+            Err(e) => {
+                write_http_response(&mut stream, 400, "text/plain", &e.to_string())?;
+                return Ok(false);
+            }
+        };
+
+        // Browsers send Origin; only local pages may talk to the server
+        if let Some(origin) = request.header("origin") {
+            if !is_local_origin(origin) {
+                write_http_response(&mut stream, 403, "text/plain", "Origin not allowed")?;
+                return Ok(false);
+            }
+        }
+
+        if request.path != "/mcp" && request.path != "/" {
+            write_http_response(&mut stream, 404, "text/plain", "Not found")?;
+            return Ok(false);
+        }
+        if request.method != "POST" {
+            // No server-initiated stream: every reply answers a POST
+            write_http_response(&mut stream, 405, "text/plain", "Method not allowed")?;
+            return Ok(false);
+        }
+
+        let is_notification = serde_json::from_str::<Value>(&request.body)
+            .is_ok_and(|message| message.get("id").is_none());
+
+        match self.handle_message(&request.body)? {
+            Reply::Exit => {
+                write_http_response(&mut stream, 202, "text/plain", "")?;
+                return Ok(true);
+            }
+            Reply::Response(..) if is_notification => {
+                write_http_response(&mut stream, 202, "text/plain", "")?;
+            }
+            Reply::Response(None, _) => {
+                write_http_response(&mut stream, 202, "text/plain", "")?;
+            }
+            Reply::Response(Some(body), notifications) => {
+                // Plain JSON replies carry only the response; diagnostics still reach the log
+                let accept = request.header("accept").unwrap_or("application/json");
+                if accept.contains("text/event-stream") && !accept.contains("application/json") {
+                    let events: String = notifications
+                        .iter()
+                        .chain(std::iter::once(&body))
+                        .map(|message| format!("event: message\ndata: {}\n\n", message))
+                        .collect();
+                    write_http_response(&mut stream, 200, "text/event-stream", &events)?;
+                } else {
+                    write_http_response(&mut stream, 200, "application/json", &body)?;
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Handle one JSON-RPC message, logging it when a log file is set
+    fn handle_message(&self, message: &str) -> Result<Reply> {
+        let received = SystemTime::now();
+        let request: JsonRpcRequest = match serde_json::from_str(message) {
+            Ok(req) => req,
+            Err(e) => {
+                let error_response = JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: Value::Null,
+                    result: None,
+                    error: Some(JsonRpcError {
+                        code: -32700,
+                        message: format!("Parse error: {}", e),
+                        data: None,
+                    }),
+                };
+                let body = serde_json::to_string(&error_response)?;
+                self.diagnose(LogLevel::Error, format!("Unparseable message: {}", e));
+                let notifications = self.finish_request(LogEntry {
+                    timestamp: unix_seconds(received),
+                    method: "(parse error)".to_string(),
+                    tool: None,
+                    arguments: Value::Null,
+                    latency_ms: 0.0,
+                    result_bytes: body.len(),
+                    is_error: true,
+                    diagnostics: Vec::new(),
+                })?;
+                return Ok(Reply::Response(Some(body), notifications));
+            }
+        };
+
+        // `exit` is a notification: stop without replying
+        if request.method == "exit" {
+            return Ok(Reply::Exit);
+        }
+
+        let started = Instant::now();
+        let method = request.method.clone();
+        let params = request.params.clone();
+        let id = request.id.as_ref().map(Value::to_string);
+
+        if let Some(id) = &id {
+            lock(&self.in_flight).entry(id.clone()).or_insert(false);
+        }
+        CURRENT_REQUEST.set(id.clone());
+        let response = self.handle_request(request);
+        CURRENT_REQUEST.set(None);
+        let cancelled = id
+            .as_ref()
+            .and_then(|id| lock(&self.in_flight).remove(id))
+            .unwrap_or(false);
+
+        let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let body = serde_json::to_string(&response)?;
+
+        let is_tool_call = method == "tools/call";
+        let tool = params["name"]
+            .as_str()
+            .filter(|_| is_tool_call)
+            .map(str::to_string);
+        let name = tool.as_deref().unwrap_or(&method);
+        if cancelled {
+            self.diagnose(LogLevel::Info, format!("{} cancelled by the client", name));
+        } else if let Some(error) = response.error.as_ref().filter(|_| id.is_some()) {
+            self.diagnose(
+                LogLevel::Error,
+                format!("{} failed ({}): {}", name, error.code, error.message),
+            );
+        }
+        if latency_ms > SLOW_REQUEST_MS {
+            self.diagnose(
+                LogLevel::Warning,
+                format!("{} took {:.0} ms", name, latency_ms),
+            );
+        }
+
+        let notifications = self.finish_request(LogEntry {
+            timestamp: unix_seconds(received),
+            tool,
+            arguments: if is_tool_call {
+                params["arguments"].clone()
+            } else {
+                params
+            },
+            method,
+            latency_ms,
+            result_bytes: body.len(),
+            is_error: response.error.is_some()
+                || response
+                    .result
+                    .as_ref()
+                    .is_some_and(|r| r["isError"] == true),
+            diagnostics: Vec::new(),
+        })?;
+
+        // Notifications get no response, and cancelled requests shouldn't get one
+        let body = Some(body).filter(|_| id.is_some() && !cancelled);
+        Ok(Reply::Response(body, notifications))
+    }
+
+    /// Record a problem with the request being handled
+    fn diagnose(&self, level: LogLevel, message: String) {
+        DIAGNOSTICS.with_borrow_mut(|diagnostics| diagnostics.push(Diagnostic { level, message }));
+    }
+
+    /// Whether the client has cancelled the request being handled, for long
+    /// operations to check as they go
+    fn is_cancelled(&self) -> bool {
+        CURRENT_REQUEST.with_borrow(|id| {
+            id.as_ref()
+                .is_some_and(|id| lock(&self.in_flight).get(id) == Some(&true))
+        })
+    }
+
+    /// Mark a request cancelled; requests already answered are ignored
+    fn cancel(&self, params: &Value) {
+        // MCP's `notifications/cancelled` sends `requestId`, LSP-style `$/cancelRequest` sends `id`
+        let Some(id) = params.get("requestId").or_else(|| params.get("id")) else {
+            return;
+        };
+        if let Some(cancelled) = lock(&self.in_flight).get_mut(&id.to_string()) {
+            *cancelled = true;
+        }
+    }
+
+    /// Write the request's log entry with its diagnostics, and return the
+    /// `notifications/message` notifications for those at or above the client's level
+    fn finish_request(&self, mut entry: LogEntry) -> Result<Vec<String>> {
+        entry.diagnostics = DIAGNOSTICS.take();
+
+        if let Some(log_file) = &self.log_file {
+            writeln!(lock(log_file), "{}", serde_json::to_string(&entry)?)?;
         }
 
+        entry
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.level >= *lock(&self.log_level))
+            .map(|diagnostic| {
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/message",
+                    "params": {
+                        "level": diagnostic.level,
+                        "logger": "localdoc",
+                        "data": diagnostic.message
+                    }
+                });
+                Ok(serde_json::to_string(&notification)?)
+            })
+            .collect()
+    }
+
+    fn finish(&self) -> Result<()> {
+        if let Some(log_file) = &self.log_file {
+            lock(log_file).flush()?;
+        }
         Ok(())
     }
 
@@ -93,10 +606,35 @@ impl McpServer {
         let id = request.id.unwrap_or(Value::Null);
 
         let result = match request.method.as_str() {
+            _ if self.shutting_down.load(Ordering::SeqCst) => Err(JsonRpcError {
+                code: -32600,
+                message: "Server is shutting down".to_string(),
+                data: None,
+            }),
+            // Cancelled while waiting for a worker
+            _ if self.is_cancelled() => Err(JsonRpcError {
+                code: -32800,
+                message: "Request cancelled".to_string(),
+                data: None,
+            }),
+            "shutdown" => {
+                self.shutting_down.store(true, Ordering::SeqCst);
+                Ok(json!({}))
+            }
             "initialize" => self.handle_initialize(),
-            "initialized" => Ok(json!({})),
+            "initialized" | "notifications/initialized" => Ok(json!({})),
+            "notifications/cancelled" | "$/cancelRequest" => {
+                self.cancel(&request.params);
+                Ok(json!({}))
+            }
+            "logging/setLevel" => self.handle_set_level(&request.params),
             "tools/list" => self.handle_tools_list(),
             "tools/call" => self.handle_tools_call(&request.params),
+            "prompts/list" => self.handle_prompts_list(),
+            "prompts/get" => self.handle_prompts_get(&request.params),
+            "resources/list" => self.handle_resources_list(&request.params),
+            "resources/read" => self.handle_resources_read(&request.params),
+            "completion/complete" => self.handle_complete(&request.params),
             "ping" => Ok(json!({})),
             _ => Err(JsonRpcError {
                 code: -32601,
@@ -127,7 +665,16 @@ impl McpServer {
             "capabilities": {
                 "tools": {
                     "listChanged": false
-                }
+                },
+                "resources": {
+                    "subscribe": false,
+                    "listChanged": false
+                },
+                "prompts": {
+                    "listChanged": false
+                },
+                "logging": {},
+                "completions": {}
             },
             "serverInfo": {
                 "name": "localdoc",
@@ -136,6 +683,16 @@ impl McpServer {
         }))
     }
 
+    fn handle_set_level(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let level = serde_json::from_value(params["level"].clone()).map_err(|_| JsonRpcError {
+            code: -32602,
+            message: format!("Unknown log level: {}", params["level"]),
+            data: None,
+        })?;
+        *lock(&self.log_level) = level;
+        Ok(json!({}))
+    }
+
     fn handle_tools_list(&self) -> Result<Value, JsonRpcError> {
         let tools = vec![
             Tool {
@@ -146,6 +703,18 @@ impl McpServer {
                     "properties": {},
                     "required": []
                 }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "health".to_string(),
+                description: "Report server version, uptime, and the state of installed docpacks"
+                    .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+                annotations: Value::Null,
             },
             Tool {
                 name: "list_symbols".to_string(),
@@ -153,6 +722,18 @@ impl McpServer {
                 input_schema: json!({
                     "type": "object",
                     "properties": {
+                        "limit": {
+                            "type": "integer",
+                            "description": "Optional: maximum number of results to return (default 500)"
+                        },
+                        "cursor": {
+                            "type": "string",
+                            "description": "Optional: nextCursor from a previous call, to get the next page"
+                        },
+                        "chunk": {
+                            "type": "integer",
+                            "description": "Optional: index of the first chunk to return when a previous result was split"
+                        },
                         "package": {
                             "type": "string",
                             "description": "Package name in format username:reponame"
@@ -160,6 +741,7 @@ impl McpServer {
                     },
                     "required": ["package"]
                 }),
+                annotations: Value::Null,
             },
             Tool {
                 name: "get_symbol".to_string(),
@@ -178,6 +760,176 @@ impl McpServer {
                     },
                     "required": ["package", "symbol"]
                 }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_examples".to_string(),
+                description: "Get usage examples for a specific symbol".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "package": {
+                            "type": "string",
+                            "description": "Package name in format username:reponame"
+                        },
+                        "symbol": {
+                            "type": "string",
+                            "description": "Symbol name or ID to look up"
+                        }
+                    },
+                    "required": ["package", "symbol"]
+                }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_source".to_string(),
+                description: "Get the source code of a symbol and its location".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "package": {
+                            "type": "string",
+                            "description": "Package name in format username:reponame"
+                        },
+                        "symbol": {
+                            "type": "string",
+                            "description": "Symbol ID (or name) to get the source of"
+                        },
+                        "context": {
+                            "type": "integer",
+                            "description": "Optional: lines of surrounding code to include, when the docpack embeds source files"
+                        }
+                    },
+                    "required": ["package", "symbol"]
+                }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "list_modules".to_string(),
+                description: "List the source files of a docpack with their symbol counts"
+                    .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "package": {
+                            "type": "string",
+                            "description": "Package name in format username:reponame"
+                        }
+                    },
+                    "required": ["package"]
+                }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_module_overview".to_string(),
+                description:
+                    "Summarize what a source file or directory is for: its module documentation and main public types and functions"
+                        .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "package": {
+                            "type": "string",
+                            "description": "Package name in format username:reponame"
+                        },
+                        "module": {
+                            "type": "string",
+                            "description": "File path or directory, as shown by list_modules"
+                        }
+                    },
+                    "required": ["package", "module"]
+                }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_module".to_string(),
+                description:
+                    "Get an overview of a source file or directory: its symbols and their summaries"
+                        .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chunk": {
+                            "type": "integer",
+                            "description": "Optional: index of the first chunk to return when a previous result was split"
+                        },
+                        "package": {
+                            "type": "string",
+                            "description": "Package name in format username:reponame"
+                        },
+                        "module": {
+                            "type": "string",
+                            "description": "File path or directory, as shown by list_modules"
+                        }
+                    },
+                    "required": ["package", "module"]
+                }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_architecture".to_string(),
+                description: "Get the big picture of a docpack: project info, its modules, and its main types with summaries"
+                    .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chunk": {
+                            "type": "integer",
+                            "description": "Optional: index of the first chunk to return when a previous result was split"
+                        },
+                        "package": {
+                            "type": "string",
+                            "description": "Package name in format username:reponame"
+                        }
+                    },
+                    "required": ["package"]
+                }),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_callers".to_string(),
+                description: "List the functions that call a symbol, and optionally their callers in turn"
+                    .to_string(),
+                input_schema: graph_schema(false),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_callees".to_string(),
+                description: "List the symbols a function calls, and optionally what those call in turn"
+                    .to_string(),
+                input_schema: graph_schema(false),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "get_dependencies".to_string(),
+                description: "List what a symbol depends on: the symbols it calls and the types in its signature, transitively up to a depth"
+                    .to_string(),
+                input_schema: graph_schema(true),
+                annotations: Value::Null,
+            },
+            Tool {
+                name: "diff_packages".to_string(),
+                description: "Compare two docpacks, e.g. two versions of a library, as JSON: added and removed symbols, signature, generics, and complexity changes"
+                    .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chunk": {
+                            "type": "integer",
+                            "description": "Optional: index of the first chunk to return when a previous result was split"
+                        },
+                        "old": {
+                            "type": "string",
+                            "description": "Package to compare from, in format username:reponame"
+                        },
+                        "new": {
+                            "type": "string",
+                            "description": "Package to compare to, in format username:reponame"
+                        }
+                    },
+                    "required": ["old", "new"]
+                }),
+                annotations: Value::Null,
             },
             Tool {
                 name: "search".to_string(),
@@ -185,6 +937,18 @@ impl McpServer {
                 input_schema: json!({
                     "type": "object",
                     "properties": {
+                        "limit": {
+                            "type": "integer",
+                            "description": "Optional: maximum number of results to return (default 100)"
+                        },
+                        "cursor": {
+                            "type": "string",
+                            "description": "Optional: nextCursor from a previous call, to get the next page"
+                        },
+                        "chunk": {
+                            "type": "integer",
+                            "description": "Optional: index of the first chunk to return when a previous result was split"
+                        },
                         "query": {
                             "type": "string",
                             "description": "Search query"
@@ -196,270 +960,2117 @@ impl McpServer {
                     },
                     "required": ["query"]
                 }),
+                annotations: Value::Null,
             },
         ];
 
-        Ok(json!({ "tools": tools }))
-    }
-
-    fn handle_tools_call(&self, params: &Value) -> Result<Value, JsonRpcError> {
-        let name = params["name"].as_str().ok_or_else(|| JsonRpcError {
-            code: -32602,
+        let tools: Vec<_> = tools
+            .into_iter()
+            .filter(|tool| self.tool_enabled(&tool.name))
+            .map(|mut tool| {
+                // Hints for clients deciding whether to confirm a call; tools only read docpacks
+                tool.annotations = json!({
+                    "title": tool_title(&tool.name),
+                    "readOnlyHint": READ_ONLY_TOOLS.contains(&tool.name.as_str()),
+                    "destructiveHint": false,
+                    "idempotentHint": true,
+                    "openWorldHint": false
+                });
+                // Every tool takes a size budget for its result
+                tool.input_schema["properties"]["max_chars"] = json!({
+                    "type": "integer",
+                    "description": "Optional: longest result to return, in characters; less important detail is dropped first"
+                });
+                tool.input_schema["properties"]["max_tokens"] = json!({
+                    "type": "integer",
+                    "description": "Optional: like max_chars, counted in tokens (about 4 characters each)"
+                });
+                if let Some(tool_override) = self.tool_overrides.get(&tool.name) {
+                    if let Some(name) = &tool_override.name {
+                        tool.name = name.clone();
+                    }
+                    if let Some(description) = &tool_override.description {
+                        tool.description = description.clone();
+                    }
+                }
+                tool
+            })
+            .collect();
+
+        Ok(json!({ "tools": tools }))
+    }
+
+    /// Whether the profile includes a built-in tool and the config doesn't disable it
+    fn tool_enabled(&self, builtin: &str) -> bool {
+        self.profile.includes(builtin)
+            && !self
+                .tool_overrides
+                .get(builtin)
+                .is_some_and(|tool_override| tool_override.disabled)
+    }
+
+    /// The built-in tool behind an advertised name, if that tool is enabled
+    fn builtin_tool_name<'a>(&'a self, advertised: &'a str) -> Option<&'a str> {
+        let renamed = self
+            .tool_overrides
+            .iter()
+            .find(|(_, tool_override)| tool_override.name.as_deref() == Some(advertised))
+            .map(|(builtin, _)| builtin.as_str());
+
+        let builtin = match renamed {
+            Some(builtin) => builtin,
+            // A renamed tool is no longer reachable under its built-in name
+            None if self
+                .tool_overrides
+                .get(advertised)
+                .is_some_and(|tool_override| tool_override.name.is_some()) =>
+            {
+                return None
+            }
+            None => advertised,
+        };
+
+        self.tool_enabled(builtin).then_some(builtin)
+    }
+
+    fn handle_prompts_list(&self) -> Result<Value, JsonRpcError> {
+        let argument = |name: &str, description: &str| json!({ "name": name, "description": description, "required": true });
+        let package = "Package name in format username:reponame";
+
+        Ok(json!({
+            "prompts": [
+                {
+                    "name": "explain-symbol",
+                    "description": "Explain what a symbol does and how to use it, from its documentation",
+                    "arguments": [
+                        argument("package", package),
+                        argument("symbol", "Symbol name or ID")
+                    ]
+                },
+                {
+                    "name": "summarize-architecture",
+                    "description": "Summarize a project's architecture from its modules and types",
+                    "arguments": [argument("package", package)]
+                },
+                {
+                    "name": "compare-versions",
+                    "description": "Describe the API changes between two docpacks, e.g. two versions of a project",
+                    "arguments": [
+                        argument("old", "Package holding the older version"),
+                        argument("new", "Package holding the newer version")
+                    ]
+                }
+            ]
+        }))
+    }
+
+    fn handle_prompts_get(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let invalid_params = |message: String| JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        };
+        let name = params["name"]
+            .as_str()
+            .ok_or_else(|| invalid_params("Missing prompt name".to_string()))?;
+        let args = &params["arguments"];
+        let arg = |key: &str| {
+            args[key]
+                .as_str()
+                .ok_or_else(|| invalid_params(format!("Missing '{}' argument", key)))
+        };
+
+        let (description, text) = match name {
+            "explain-symbol" => {
+                let (package, symbol) = (arg("package")?, arg("symbol")?);
+                let docs = self
+                    .tool_get_symbol(&json!({ "package": package, "symbol": symbol }), &mut None)
+                    .map_err(invalid_params)?;
+                (
+                    format!("Explain {} from {}", symbol, package),
+                    format!(
+                        "Explain what `{}` from the {} package does, when to use it, and how to call it correctly. \
+                         Point out any pitfalls the documentation mentions.\n\n{}",
+                        symbol, package, docs
+                    ),
+                )
+            }
+            "summarize-architecture" => {
+                let package = arg("package")?;
+                (
+                    format!("Summarize the architecture of {}", package),
+                    format!(
+                        "Summarize the architecture of the {} package: its main components, what each is \
+                         responsible for, and how they fit together.\n\n{}",
+                        package,
+                        self.architecture_outline(package).map_err(invalid_params)?
+                    ),
+                )
+            }
+            "compare-versions" => {
+                let (old, new) = (arg("old")?, arg("new")?);
+                (
+                    format!("Compare {} with {}", old, new),
+                    format!(
+                        "Describe the API changes from {} to {}: what was added, removed, or changed, \
+                         and what callers need to update.\n\n{}",
+                        old,
+                        new,
+                        self.api_diff(old, new).map_err(invalid_params)?
+                    ),
+                )
+            }
+            _ => return Err(invalid_params(format!("Unknown prompt: {}", name))),
+        };
+
+        Ok(json!({
+            "description": description,
+            "messages": [{
+                "role": "user",
+                "content": { "type": "text", "text": text }
+            }]
+        }))
+    }
+
+    /// Modules plus every type with its summary, for the architecture prompt and tool
+    fn architecture_outline(&self, package: &str) -> Result<String, String> {
+        let mut output = self.tool_list_modules(&json!({ "package": package }))?;
+
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
+        let types: Vec<_> = docpack
+            .symbols
+            .iter()
+            .filter(|s| {
+                matches!(
+                    s.kind.as_str(),
+                    "struct" | "class" | "enum" | "trait" | "interface" | "protocol"
+                )
+            })
+            .collect();
+
+        if !types.is_empty() {
+            output.push_str("\n\nTypes:\n\n");
+        }
+        for symbol in &types {
+            let summary = docpack
+                .get_documentation(&symbol.doc_id)
+                .map(|doc| doc.summary)
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "[{}] {} ({}): {}\n",
+                symbol.kind, symbol.id, symbol.file, summary
+            ));
+        }
+
+        Ok(output)
+    }
+
+    /// Symbols added, removed, or with changed signatures between two packages
+    fn api_diff(&self, old: &str, new: &str) -> Result<String, String> {
+        let open = |package: &str| {
+            let path = self.resolve_package_path(package)?;
+            self.open_docpack(&path)
+        };
+        let (old_pack, new_pack) = (open(old)?, open(new)?);
+
+        let old_symbols: BTreeMap<_, _> = old_pack.symbols.iter().map(|s| (&s.id, s)).collect();
+        let new_symbols: BTreeMap<_, _> = new_pack.symbols.iter().map(|s| (&s.id, s)).collect();
+
+        let mut output = format!(
+            "{} v{} -> {} v{}\n",
+            old_pack.manifest.project.name,
+            old_pack.manifest.project.version,
+            new_pack.manifest.project.name,
+            new_pack.manifest.project.version
+        );
+
+        let header_len = output.len();
+        let mut section = |title: &str, lines: Vec<String>| {
+            if lines.is_empty() {
+                return;
+            }
+            output.push_str(&format!("\n{} ({}):\n", title, lines.len()));
+            for line in lines.iter().take(API_DIFF_LIMIT) {
+                output.push_str(&format!("- {}\n", line));
+            }
+            if lines.len() > API_DIFF_LIMIT {
+                output.push_str(&format!(
+                    "- ... and {} more\n",
+                    lines.len() - API_DIFF_LIMIT
+                ));
+            }
+        };
+
+        section(
+            "Added",
+            new_symbols
+                .iter()
+                .filter(|(id, _)| !old_symbols.contains_key(*id))
+                .map(|(_, s)| format!("[{}] {}: `{}`", s.kind, s.id, s.signature))
+                .collect(),
+        );
+        section(
+            "Removed",
+            old_symbols
+                .iter()
+                .filter(|(id, _)| !new_symbols.contains_key(*id))
+                .map(|(_, s)| format!("[{}] {}: `{}`", s.kind, s.id, s.signature))
+                .collect(),
+        );
+        section(
+            "Signature changed",
+            new_symbols
+                .iter()
+                .filter_map(|(id, s)| {
+                    let before = old_symbols.get(*id)?;
+                    (before.signature != s.signature)
+                        .then(|| format!("{}: `{}` -> `{}`", s.id, before.signature, s.signature))
+                })
+                .collect(),
+        );
+
+        if output.len() == header_len {
+            output.push_str("\nNo API changes.\n");
+        }
+
+        Ok(output)
+    }
+
+    /// Every package and symbol as a `docpack://` resource, a page at a time
+    fn handle_resources_list(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let invalid_params = |message: String| JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        };
+        let (offset, limit) = page_arguments(params, RESOURCES_PAGE).map_err(invalid_params)?;
+
+        let mut resources = Vec::new();
+        for package in self.installed_packages().map_err(invalid_params)? {
+            if package.shadowed {
+                continue;
+            }
+            let Ok(docpack) = self.open_docpack(&package.path.to_string_lossy()) else {
+                continue;
+            };
+
+            resources.push(json!({
+                "uri": format!("{}{}", RESOURCE_SCHEME, package.name),
+                "name": package.name,
+                "description": format!(
+                    "{} v{}: {} symbols",
+                    docpack.manifest.project.name,
+                    docpack.manifest.project.version,
+                    docpack.symbols.len()
+                ),
+                "mimeType": "text/markdown"
+            }));
+            for symbol in &docpack.symbols {
+                resources.push(json!({
+                    "uri": format!("{}{}/{}", RESOURCE_SCHEME, package.name, symbol.id),
+                    "name": symbol.id,
+                    "description": format!("[{}] {}:{}", symbol.kind, symbol.file, symbol.line),
+                    "mimeType": "text/markdown"
+                }));
+            }
+        }
+
+        let total = resources.len();
+        let end = offset.saturating_add(limit).min(total);
+        let page: Vec<_> = resources.drain(offset.min(end)..end).collect();
+        let mut result = json!({ "resources": page });
+        if end < total {
+            result["nextCursor"] = json!(end.to_string());
+        }
+        Ok(result)
+    }
+
+    /// Documentation for a `docpack://<package>` or `docpack://<package>/<symbol id>` URI
+    fn handle_resources_read(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let not_found = |message: String| JsonRpcError {
+            code: -32002,
+            message,
+            data: None,
+        };
+
+        let uri = params["uri"].as_str().ok_or_else(|| JsonRpcError {
+            code: -32602,
+            message: "Missing 'uri' parameter".to_string(),
+            data: None,
+        })?;
+        let (package, symbol_id) = match uri.strip_prefix(RESOURCE_SCHEME) {
+            Some(rest) => match rest.split_once('/') {
+                Some((package, symbol_id)) => (package, Some(symbol_id)),
+                None => (rest, None),
+            },
+            None => return Err(not_found(format!("Unknown resource URI: {}", uri))),
+        };
+
+        let text = match symbol_id {
+            None => {
+                let path = self.resolve_package_path(package).map_err(not_found)?;
+                let docpack = self.open_docpack(&path).map_err(not_found)?;
+                let project = &docpack.manifest.project;
+
+                let mut output = format!("# {}\n\n", package);
+                output.push_str(&format!(
+                    "**Project:** {} v{}\n",
+                    project.name, project.version
+                ));
+                if !project.repo.is_empty() {
+                    output.push_str(&format!("**Repository:** {}\n", project.repo));
+                }
+                output.push('\n');
+                output.push_str(
+                    &self
+                        .tool_list_modules(&json!({ "package": package }))
+                        .map_err(not_found)?,
+                );
+                output
+            }
+            Some(symbol_id) => {
+                let path = self.resolve_package_path(package).map_err(not_found)?;
+                let docpack = self.open_docpack(&path).map_err(not_found)?;
+                let symbol = docpack
+                    .symbols
+                    .iter()
+                    .find(|s| s.id == symbol_id)
+                    .ok_or_else(|| {
+                        not_found(format!("No symbol '{}' in {}", symbol_id, package))
+                    })?;
+                let doc = docpack
+                    .get_documentation(&symbol.doc_id)
+                    .map_err(|e| not_found(format!("Failed to get documentation: {}", e)))?;
+                symbol_markdown(symbol, &doc)
+            }
+        };
+
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "text/markdown",
+                "text": text
+            }]
+        }))
+    }
+
+    /// Suggest values for a prompt's `package` (or `old`/`new`) and `symbol` arguments.
+    /// Symbols come from the package given in `context.arguments`.
+    fn handle_complete(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let argument = &params["argument"];
+        let name = argument["name"].as_str().ok_or_else(|| JsonRpcError {
+            code: -32602,
+            message: "Missing argument name".to_string(),
+            data: None,
+        })?;
+        let value = argument["value"].as_str().unwrap_or_default();
+
+        let candidates: Vec<String> = match name {
+            "package" | "old" | "new" => self
+                .installed_packages()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|package| !package.shadowed)
+                .map(|package| package.name)
+                .collect(),
+            "symbol" => params["context"]["arguments"]["package"]
+                .as_str()
+                .and_then(|package| self.resolve_package_path(package).ok())
+                .and_then(|path| self.open_docpack(&path).ok())
+                .map(|docpack| docpack.symbols.iter().map(|s| s.id.clone()).collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        let values = completions(&candidates, value);
+        Ok(json!({
+            "completion": {
+                "values": values.iter().take(COMPLETION_LIMIT).collect::<Vec<_>>(),
+                "total": values.len(),
+                "hasMore": values.len() > COMPLETION_LIMIT
+            }
+        }))
+    }
+
+    fn handle_tools_call(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let name = params["name"].as_str().ok_or_else(|| JsonRpcError {
+            code: -32602,
             message: "Missing tool name".to_string(),
             data: None,
         })?;
 
         let arguments = &params["arguments"];
 
-        let result = match name {
-            "list_packages" => self.tool_list_packages(),
-            "list_symbols" => self.tool_list_symbols(arguments),
-            "get_symbol" => self.tool_get_symbol(arguments),
-            "search" => self.tool_search(arguments),
+        let builtin = self.builtin_tool_name(name).unwrap_or_default();
+        // The server never modifies docpacks; a tool that writes must not be served
+        // until it is reviewed and added to READ_ONLY_TOOLS
+        if !builtin.is_empty() && !READ_ONLY_TOOLS.contains(&builtin) {
+            return Ok(json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("Tool {} is not read-only and cannot be served", name)
+                }],
+                "isError": true
+            }));
+        }
+
+        // Set by paginated tools when more results remain
+        let mut next_cursor = None;
+        // Set by tools that also return their result as JSON
+        let mut structured = None;
+        let result = match builtin {
+            "list_packages" => self.tool_list_packages(&mut structured),
+            "health" => self.tool_health(),
+            "list_symbols" => self.tool_list_symbols(arguments, &mut next_cursor, &mut structured),
+            "get_symbol" => self.tool_get_symbol(arguments, &mut structured),
+            "get_examples" => self.tool_get_examples(arguments),
+            "get_source" => self.tool_get_source(arguments),
+            "list_modules" => self.tool_list_modules(arguments),
+            "get_module" => self.tool_get_module(arguments),
+            "get_module_overview" => self.tool_get_module_overview(arguments),
+            "get_architecture" => self.tool_get_architecture(arguments),
+            "get_callers" => self.tool_walk_edges(arguments, "Callers", |docpack, symbol| {
+                docpack
+                    .calls_to(symbol)
+                    .into_iter()
+                    .map(|call| (EdgeKind::Calls, call))
+                    .collect()
+            }),
+            "get_callees" => self.tool_walk_edges(arguments, "Callees", |docpack, symbol| {
+                docpack
+                    .calls_from(symbol)
+                    .into_iter()
+                    .map(|call| (EdgeKind::Calls, call))
+                    .collect()
+            }),
+            "get_dependencies" => {
+                self.tool_walk_edges(arguments, "Dependencies", |docpack, symbol| {
+                    let calls = docpack.calls_from(symbol).into_iter();
+                    let types = docpack.signature_types(symbol).into_iter();
+                    calls
+                        .map(|call| (EdgeKind::Calls, call))
+                        .chain(types.map(|symbol| {
+                            let call = Call {
+                                symbol,
+                                lines: Vec::new(),
+                                count: 0,
+                            };
+                            (EdgeKind::Types, call)
+                        }))
+                        .collect()
+                })
+            }
+            "diff_packages" => self.tool_diff_packages(arguments, &mut structured),
+            "search" => self.tool_search(arguments, &mut next_cursor, &mut structured),
             _ => Err(format!("Unknown tool: {}", name)),
         };
+        let budget = budget_argument(arguments);
+        let result = result.map(|text| match budget {
+            Some(max_chars) => fit_to_budget(&text, max_chars),
+            None => text,
+        });
+        // A budget caps the whole result, and later chunks repeat nothing
+        if budget.is_some() || arguments["chunk"].as_u64().unwrap_or(0) > 0 {
+            structured = None;
+        }
 
-        match result {
-            Ok(text) => Ok(json!({
+        let mut response = match result {
+            Ok(text) if text.len() > CHUNK_BYTES => {
+                let chunks = chunk_text(&text, CHUNK_BYTES);
+                let first = arguments["chunk"].as_u64().unwrap_or(0) as usize;
+                if first >= chunks.len() {
+                    return Ok(json!({
+                        "content": [{
+                            "type": "text",
+                            "text": format!("Chunk {} is out of range; the result has {} chunk(s)", first, chunks.len())
+                        }],
+                        "isError": true
+                    }));
+                }
+
+                let last = (first + CHUNKS_PER_RESPONSE).min(chunks.len());
+                let mut content: Vec<Value> = chunks[first..last]
+                    .iter()
+                    .map(|chunk| json!({ "type": "text", "text": chunk }))
+                    .collect();
+                let next_chunk = (last < chunks.len()).then_some(last);
+                if let Some(next) = next_chunk {
+                    content.push(json!({
+                        "type": "text",
+                        "text": format!(
+                            "[Output continues: call {} again with \"chunk\": {} for the next part ({} of {} chunks sent)]",
+                            name, next, last, chunks.len()
+                        )
+                    }));
+                }
+
+                json!({
+                    "content": content,
+                    "_meta": {
+                        "chunks": {
+                            "first": first,
+                            "returned": last - first,
+                            "total": chunks.len(),
+                            "totalBytes": text.len(),
+                            "nextChunk": next_chunk
+                        }
+                    }
+                })
+            }
+            Ok(text) => json!({
                 "content": [{
                     "type": "text",
                     "text": text
                 }]
-            })),
-            Err(e) => Ok(json!({
+            }),
+            Err(e) => json!({
                 "content": [{
                     "type": "text",
                     "text": e
                 }],
                 "isError": true
-            })),
-        }
-    }
+            }),
+        };
 
-    fn tool_list_packages(&self) -> Result<String, String> {
-        if !self.packages_dir.exists() {
-            return Ok("No docpacks installed yet.".to_string());
+        if let Some(cursor) = next_cursor {
+            response["_meta"]["nextCursor"] = json!(cursor);
         }
+        if let (Some(structured), None) = (structured, response.get("isError")) {
+            response["structuredContent"] = structured;
+        }
+        Ok(response)
+    }
 
-        let entries: Vec<_> = std::fs::read_dir(&self.packages_dir)
-            .map_err(|e| format!("Failed to read packages directory: {}", e))?
-            .filter_map(|e| e.ok())
-            .filter(|e| {
-                e.path()
-                    .extension()
-                    .map(|ext| ext == "docpack")
-                    .unwrap_or(false)
-            })
-            .collect();
-
-        if entries.is_empty() {
+    fn tool_list_packages(&self, structured: &mut Option<Value>) -> Result<String, String> {
+        let packages = self.installed_packages()?;
+        let mut entries = Vec::new();
+        if packages.is_empty() {
+            *structured = Some(json!({ "packages": entries }));
             return Ok("No docpacks installed yet.".to_string());
         }
 
         let mut output = String::new();
         output.push_str("Installed docpacks:\n\n");
 
-        for entry in &entries {
-            let path = entry.path();
-            let filename = path.file_stem().unwrap_or_default().to_string_lossy();
-            let name = filename.replacen('_', ":", 1);
-
-            match Docpack::open(&path.to_string_lossy()) {
+        for package in &packages {
+            let name = &package.name;
+            let mut entry = json!({
+                "name": name,
+                "directory": self.packages_dirs[package.root],
+                "shadowed": package.shadowed
+            });
+            match self.open_docpack(&package.path.to_string_lossy()) {
                 Ok(docpack) => {
                     output.push_str(&format!(
-                        "- {} (v{}, {} symbols)\n",
+                        "- {} (v{}, {} symbols)",
                         name,
                         docpack.manifest.project.version,
                         docpack.manifest.stats.symbols_extracted
                     ));
+                    entry["version"] = json!(docpack.manifest.project.version);
+                    entry["symbols"] = json!(docpack.manifest.stats.symbols_extracted);
                 }
                 Err(_) => {
-                    output.push_str(&format!("- {} (unable to read metadata)\n", name));
+                    output.push_str(&format!("- {} (unable to read metadata)", name));
                 }
             }
+            output.push_str(&self.origin_label(package));
+            output.push('\n');
+            entries.push(entry);
+        }
+
+        *structured = Some(json!({ "packages": entries }));
+        Ok(output)
+    }
+
+    /// Docpacks in every root, in precedence order
+    fn installed_packages(&self) -> Result<Vec<InstalledPackage>, String> {
+        use std::collections::HashSet;
+
+        let mut seen = HashSet::new();
+        let mut packages = Vec::new();
+        for (root, dir) in self.packages_dirs.iter().enumerate() {
+            if !dir.exists() {
+                continue;
+            }
+
+            let mut paths: Vec<_> = std::fs::read_dir(dir)
+                .map_err(|e| format!("Failed to read packages directory: {}", e))?
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|path| {
+                    path.extension()
+                        .map(|ext| ext == "docpack")
+                        .unwrap_or(false)
+                })
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                let filename = path.file_stem().unwrap_or_default().to_string_lossy();
+                let name = filename.replacen('_', ":", 1);
+                let shadowed = !seen.insert(name.clone());
+                packages.push(InstalledPackage {
+                    name,
+                    path,
+                    root,
+                    shadowed,
+                });
+            }
+        }
+
+        Ok(packages)
+    }
+
+    /// " [root]" when serving several roots, noting packages hidden by an earlier root
+    fn origin_label(&self, package: &InstalledPackage) -> String {
+        if self.packages_dirs.len() < 2 {
+            return String::new();
+        }
+
+        let root = self.packages_dirs[package.root].display();
+        if package.shadowed {
+            format!(" [{}, shadowed by an earlier root]", root)
+        } else {
+            format!(" [{}]", root)
+        }
+    }
+
+    fn tool_health(&self) -> Result<String, String> {
+        let mut output = String::new();
+        output.push_str("Status: ok\n");
+        output.push_str(&format!("Version: {}\n", env!("CARGO_PKG_VERSION")));
+        output.push_str(&format!("Uptime: {}s\n", self.started.elapsed().as_secs()));
+        for dir in &self.packages_dirs {
+            output.push_str(&format!("Packages directory: {}\n", dir.display()));
         }
+        output.push_str(&format!(
+            "Request log: {}\n\n",
+            if self.log_file.is_some() { "on" } else { "off" }
+        ));
 
+        output.push_str(&self.tool_list_packages(&mut None)?);
         Ok(output)
     }
 
-    fn tool_list_symbols(&self, args: &Value) -> Result<String, String> {
+    fn tool_list_symbols(
+        &self,
+        args: &Value,
+        next_cursor: &mut Option<String>,
+        structured: &mut Option<Value>,
+    ) -> Result<String, String> {
         let package = args["package"]
             .as_str()
             .ok_or("Missing 'package' argument")?;
+        let (offset, limit) = page_arguments(args, DEFAULT_SYMBOLS_LIMIT)?;
 
         let path = self.resolve_package_path(package)?;
-        let docpack = Docpack::open(&path).map_err(|e| format!("Failed to open docpack: {}", e))?;
+        let docpack = self.open_docpack(&path)?;
 
         let mut output = String::new();
         output.push_str(&format!("Symbols in {}:\n\n", package));
 
-        for symbol in &docpack.symbols {
+        let page: Vec<_> = docpack.symbols.iter().skip(offset).take(limit).collect();
+        for symbol in &page {
             output.push_str(&format!(
                 "[{}] {} ({}:{})\n",
                 symbol.kind, symbol.id, symbol.file, symbol.line
             ));
         }
+        *structured = Some(json!({
+            "symbols": page
+                .iter()
+                .map(|s| json!({ "id": s.id, "kind": s.kind, "file": s.file, "line": s.line }))
+                .collect::<Vec<_>>(),
+            "total": docpack.symbols.len()
+        }));
 
         output.push_str(&format!("\nTotal: {} symbols", docpack.symbols.len()));
+        output.push_str(&page_footer(
+            offset,
+            limit,
+            docpack.symbols.len(),
+            next_cursor,
+        ));
         Ok(output)
     }
 
-    fn tool_get_symbol(&self, args: &Value) -> Result<String, String> {
+    fn tool_list_modules(&self, args: &Value) -> Result<String, String> {
+        use std::collections::BTreeMap;
+
         let package = args["package"]
             .as_str()
             .ok_or("Missing 'package' argument")?;
-        let symbol_name = args["symbol"]
-            .as_str()
-            .ok_or("Missing 'symbol' argument")?;
 
         let path = self.resolve_package_path(package)?;
-        let mut docpack =
-            Docpack::open(&path).map_err(|e| format!("Failed to open docpack: {}", e))?;
+        let docpack = self.open_docpack(&path)?;
 
-        let matches: Vec<_> = docpack
-            .find_symbols_by_name(symbol_name)
-            .into_iter()
-            .cloned()
-            .collect();
-
-        if matches.is_empty() {
-            return Err(format!("No symbol found matching '{}'", symbol_name));
+        let mut kinds_by_file: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+        for symbol in &docpack.symbols {
+            *kinds_by_file
+                .entry(&symbol.file)
+                .or_default()
+                .entry(&symbol.kind)
+                .or_default() += 1;
         }
 
         let mut output = String::new();
+        output.push_str(&format!("Modules in {}:\n\n", package));
 
-        for symbol in matches {
-            let doc = docpack
-                .get_documentation(&symbol.doc_id)
-                .map_err(|e| format!("Failed to get documentation: {}", e))?;
+        for (file, kinds) in &kinds_by_file {
+            let counts = kinds
+                .iter()
+                .map(|(kind, count)| format!("{}: {}", kind, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            output.push_str(&format!(
+                "{} ({} symbols; {})\n",
+                file,
+                kinds.values().sum::<usize>(),
+                counts
+            ));
+        }
 
-            output.push_str(&format!("# {}\n\n", symbol.id));
-            output.push_str(&format!("**Kind:** {}\n", symbol.kind));
-            output.push_str(&format!("**Location:** {}:{}\n", symbol.file, symbol.line));
-            output.push_str(&format!("**Signature:** `{}`\n\n", symbol.signature));
+        output.push_str(&format!("\nTotal: {} modules", kinds_by_file.len()));
+        Ok(output)
+    }
 
-            output.push_str(&format!("## Summary\n{}\n\n", doc.summary));
-            output.push_str(&format!("## Description\n{}\n\n", doc.description));
+    fn tool_get_module(&self, args: &Value) -> Result<String, String> {
+        let package = args["package"]
+            .as_str()
+            .ok_or("Missing 'package' argument")?;
+        let module = args["module"].as_str().ok_or("Missing 'module' argument")?;
+        let module = module.trim_end_matches('/');
 
-            if !doc.parameters.is_empty() {
-                output.push_str("## Parameters\n");
-                for param in &doc.parameters {
-                    output.push_str(&format!(
-                        "- **{}** ({}): {}\n",
-                        param.name, param.param_type, param.description
-                    ));
-                }
-                output.push('\n');
-            }
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
 
-            if !doc.returns.is_empty() {
-                output.push_str(&format!("## Returns\n{}\n\n", doc.returns));
-            }
+        let members = module_members(&docpack, module)?;
+        let mut files: Vec<_> = members.iter().map(|s| s.file.as_str()).collect();
+        files.dedup();
 
-            if !doc.example.is_empty() {
-                output.push_str(&format!("## Example\n```\n{}\n```\n\n", doc.example));
-            }
+        let mut output = String::new();
+        output.push_str(&format!("# {}\n\n", module));
+        output.push_str(&format!(
+            "**Files:** {}\n**Symbols:** {}\n\n",
+            files.len(),
+            members.len()
+        ));
 
-            if !doc.notes.is_empty() {
-                output.push_str("## Notes\n");
-                for note in &doc.notes {
-                    output.push_str(&format!("- {}\n", note));
+        let mut current_file = "";
+        for symbol in &members {
+            if symbol.file != current_file {
+                if !current_file.is_empty() {
+                    output.push('\n');
                 }
-                output.push('\n');
+                current_file = &symbol.file;
+                output.push_str(&format!("## {}\n", current_file));
             }
 
-            output.push_str("---\n\n");
+            let summary = docpack
+                .get_documentation(&symbol.doc_id)
+                .map(|doc| doc.summary)
+                .unwrap_or_default();
+            if summary.is_empty() {
+                output.push_str(&format!(
+                    "- [{}] {} (line {})\n",
+                    symbol.kind, symbol.id, symbol.line
+                ));
+            } else {
+                output.push_str(&format!(
+                    "- [{}] {} (line {}): {}\n",
+                    symbol.kind, symbol.id, symbol.line, summary
+                ));
+            }
         }
 
         Ok(output)
     }
 
-    fn tool_search(&self, args: &Value) -> Result<String, String> {
-        let query = args["query"].as_str().ok_or("Missing 'query' argument")?;
-        let package_filter = args["package"].as_str();
-
-        let mut all_results: Vec<(String, String, String, String)> = Vec::new();
+    /// What a module is for: its own documentation, then its main public types
+    /// and functions, without the full listing `get_module` gives
+    fn tool_get_module_overview(&self, args: &Value) -> Result<String, String> {
+        use std::collections::BTreeMap;
 
-        if let Some(package) = package_filter {
-            // Search specific package
-            let path = self.resolve_package_path(package)?;
-            let mut docpack =
-                Docpack::open(&path).map_err(|e| format!("Failed to open docpack: {}", e))?;
+        let package = args["package"]
+            .as_str()
+            .ok_or("Missing 'package' argument")?;
+        let module = args["module"].as_str().ok_or("Missing 'module' argument")?;
+        let module = module.trim_end_matches('/');
 
-            let results = docpack
-                .search_symbols(query)
-                .map_err(|e| format!("Search failed: {}", e))?;
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
+        let members = module_members(&docpack, module)?;
 
-            for (symbol, doc) in results {
-                all_results.push((
-                    package.to_string(),
-                    symbol.id,
-                    symbol.kind,
-                    doc.summary,
-                ));
-            }
-        } else {
-            // Search all packages
-            if self.packages_dir.exists() {
-                let entries: Vec<_> = std::fs::read_dir(&self.packages_dir)
-                    .map_err(|e| format!("Failed to read packages directory: {}", e))?
-                    .filter_map(|e| e.ok())
-                    .filter(|e| {
-                        e.path()
-                            .extension()
-                            .map(|ext| ext == "docpack")
-                            .unwrap_or(false)
-                    })
-                    .collect();
+        let mut output = format!("# {}\n\n", module);
 
-                for entry in entries {
-                    let path = entry.path();
-                    let filename = path.file_stem().unwrap_or_default().to_string_lossy();
-                    let package_name = filename.replacen('_', ":", 1);
-
-                    if let Ok(mut docpack) = Docpack::open(&path.to_string_lossy()) {
-                        if let Ok(results) = docpack.search_symbols(query) {
-                            for (symbol, doc) in results {
-                                all_results.push((
-                                    package_name.clone(),
-                                    symbol.id,
-                                    symbol.kind,
-                                    doc.summary,
-                                ));
-                            }
-                        }
-                    }
+        let module_doc = members
+            .iter()
+            .find(|s| matches!(s.kind.as_str(), "module" | "mod"))
+            .and_then(|s| docpack.get_documentation(&s.doc_id).ok())
+            .filter(|doc| !doc.summary.is_empty());
+        match module_doc {
+            Some(doc) => {
+                output.push_str(&format!("{}\n\n", doc.summary));
+                if !doc.description.is_empty() {
+                    output.push_str(&format!("{}\n\n", doc.description));
                 }
             }
+            None => {
+                output.push_str("No module-level documentation; summarized from its symbols.\n\n")
+            }
         }
 
-        if all_results.is_empty() {
-            return Ok(format!("No results found for '{}'", query));
+        let mut kinds: BTreeMap<&str, usize> = BTreeMap::new();
+        for symbol in &members {
+            *kinds.entry(&symbol.kind).or_default() += 1;
         }
+        let counts: Vec<String> = kinds
+            .iter()
+            .map(|(kind, count)| format!("{}: {}", kind, count))
+            .collect();
+        output.push_str(&format!("**Contains:** {}\n", counts.join(", ")));
 
-        let mut output = String::new();
-        output.push_str(&format!("Search results for '{}':\n\n", query));
+        // Public items where the language marks them, else everything but tests
+        let candidates: Vec<_> = members
+            .iter()
+            .filter(|s| !crate::docpack::is_test_symbol(s))
+            .collect();
+        let any_public = candidates.iter().any(|s| s.is_public());
+        let key_items = |kinds: &[&str]| -> Vec<_> {
+            candidates
+                .iter()
+                .filter(|s| kinds.contains(&s.kind.as_str()) && (!any_public || s.is_public()))
+                .collect()
+        };
 
-        for (package, id, kind, summary) in &all_results {
-            output.push_str(&format!("[{}] {}:{}\n", kind, package, id));
-            output.push_str(&format!("  {}\n\n", summary));
+        for (title, items) in [
+            (
+                "Types",
+                key_items(&[
+                    "struct",
+                    "class",
+                    "enum",
+                    "trait",
+                    "interface",
+                    "protocol",
+                    "type",
+                ]),
+            ),
+            ("Functions", key_items(&["function"])),
+        ] {
+            if items.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n## {}\n", title));
+            for symbol in items.iter().take(MODULE_OVERVIEW_LIMIT) {
+                let summary = docpack
+                    .get_documentation(&symbol.doc_id)
+                    .map(|doc| doc.summary)
+                    .unwrap_or_default();
+                if summary.is_empty() {
+                    output.push_str(&format!("- {}\n", symbol.id));
+                } else {
+                    output.push_str(&format!("- {}: {}\n", symbol.id, summary));
+                }
+            }
+            if items.len() > MODULE_OVERVIEW_LIMIT {
+                output.push_str(&format!(
+                    "- ... and {} more (get_module lists every symbol)\n",
+                    items.len() - MODULE_OVERVIEW_LIMIT
+                ));
+            }
         }
 
-        output.push_str(&format!("Found {} result(s)", all_results.len()));
         Ok(output)
     }
 
-    fn resolve_package_path(&self, package: &str) -> Result<String, String> {
-        let filename = format!("{}.docpack", package.replace(':', "_"));
-        let path = self.packages_dir.join(&filename);
+    fn tool_get_architecture(&self, args: &Value) -> Result<String, String> {
+        let package = args["package"]
+            .as_str()
+            .ok_or("Missing 'package' argument")?;
 
-        if path.exists() {
-            Ok(path.to_string_lossy().to_string())
-        } else {
-            Err(format!(
-                "Docpack '{}' not found. Run 'localdoc list' to see installed docpacks.",
-                package
-            ))
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
+        let manifest = &docpack.manifest;
+
+        let mut languages: Vec<_> = manifest.language_summary.iter().collect();
+        languages.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let languages: Vec<String> = languages
+            .iter()
+            .map(|(language, count)| format!("{} ({})", language, count))
+            .collect();
+
+        let mut output = format!(
+            "# {} v{}\n\nRepository: {}\n",
+            manifest.project.name, manifest.project.version, manifest.project.repo
+        );
+        if !languages.is_empty() {
+            output.push_str(&format!("Languages: {}\n", languages.join(", ")));
+        }
+        output.push_str(&format!("Symbols: {}\n\n", docpack.symbols.len()));
+        output.push_str(&self.architecture_outline(package)?);
+        Ok(output)
+    }
+
+    /// Walk the edges `edges` gives from a symbol, depth first, listing each
+    /// symbol reached once and indenting it under the symbol it was reached from
+    fn tool_walk_edges(
+        &self,
+        args: &Value,
+        title: &str,
+        edges: impl for<'d> Fn(&'d Docpack, &'d Symbol) -> Vec<(EdgeKind, Call<'d>)>,
+    ) -> Result<String, String> {
+        let package = args["package"]
+            .as_str()
+            .ok_or("Missing 'package' argument")?;
+        let symbol_name = args["symbol"].as_str().ok_or("Missing 'symbol' argument")?;
+        let depth = match &args["depth"] {
+            Value::Null => 1,
+            depth => depth
+                .as_u64()
+                .filter(|d| (1..=MAX_GRAPH_DEPTH as u64).contains(d))
+                .ok_or(format!("'depth' must be between 1 and {}", MAX_GRAPH_DEPTH))?
+                as usize,
+        };
+        let kinds = match &args["edge_kinds"] {
+            Value::Null => vec![EdgeKind::Calls, EdgeKind::Types],
+            kinds => kinds
+                .as_array()
+                .ok_or("'edge_kinds' must be an array")?
+                .iter()
+                .map(|kind| match kind.as_str() {
+                    Some("calls") => Ok(EdgeKind::Calls),
+                    Some("types") => Ok(EdgeKind::Types),
+                    _ => Err(format!("Unknown edge kind {}; use calls or types", kind)),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
+
+        let matches = docpack.find_symbols_by_name(symbol_name);
+        let start = match matches.iter().find(|s| s.id == symbol_name) {
+            Some(symbol) => *symbol,
+            None => match matches.as_slice() {
+                [] => return Err(format!("No symbol found matching '{}'", symbol_name)),
+                [symbol] => *symbol,
+                _ => {
+                    let ids: Vec<_> = matches.iter().take(10).map(|s| s.id.as_str()).collect();
+                    return Err(format!(
+                        "'{}' matches {} symbols; use one of: {}",
+                        symbol_name,
+                        matches.len(),
+                        ids.join(", ")
+                    ));
+                }
+            },
+        };
+
+        let mut lines = Vec::new();
+        let mut seen = HashSet::from([start.id.as_str()]);
+        let mut stack: Vec<(Call, usize, EdgeKind)> = Vec::new();
+        let push_edges = |stack: &mut Vec<_>, symbol, level| {
+            let mut next: Vec<_> = edges(&docpack, symbol)
+                .into_iter()
+                .filter(|(kind, _)| kinds.contains(kind))
+                .collect();
+            next.sort_by(|a, b| {
+                (b.1.count.cmp(&a.1.count)).then_with(|| a.1.symbol.id.cmp(&b.1.symbol.id))
+            });
+            // Reversed so the stack pops them most called first, then in ID order
+            stack.extend(
+                next.into_iter()
+                    .rev()
+                    .map(|(kind, call)| (call, level, kind)),
+            );
+        };
+        push_edges(&mut stack, start, 1);
+
+        while let Some((call, level, kind)) = stack.pop() {
+            if self.is_cancelled() {
+                return Err(format!("{} walk cancelled", title));
+            }
+            let symbol = call.symbol;
+            if !seen.insert(&symbol.id) {
+                continue;
+            }
+
+            let call_lines: Vec<_> = call.lines.iter().map(|l| l.to_string()).collect();
+            let via = match kind {
+                EdgeKind::Calls if call.count == 1 => {
+                    format!(" (call on line {})", call_lines.join(", "))
+                }
+                EdgeKind::Calls if call_lines.len() == 1 => {
+                    format!(" ({} calls on line {})", call.count, call_lines[0])
+                }
+                EdgeKind::Calls => {
+                    format!(" ({} calls on lines {})", call.count, call_lines.join(", "))
+                }
+                EdgeKind::Types => " (type)".to_string(),
+            };
+            lines.push(format!(
+                "{}- [{}] {} ({}:{}){}",
+                "  ".repeat(level - 1),
+                symbol.kind,
+                symbol.id,
+                symbol.file,
+                symbol.line,
+                via
+            ));
+            if level < depth {
+                push_edges(&mut stack, symbol, level + 1);
+            }
+        }
+
+        let mut output = format!("{} of {} (depth {}):\n\n", title, start.id, depth);
+        if lines.is_empty() {
+            output.push_str("None found.\n");
+        } else {
+            output.push_str(&lines.join("\n"));
+            output.push_str(&format!("\n\nTotal: {} symbol(s)", lines.len()));
+        }
+        Ok(output)
+    }
+
+    /// The `compare` report between two packages, as JSON
+    fn tool_diff_packages(
+        &self,
+        args: &Value,
+        structured: &mut Option<Value>,
+    ) -> Result<String, String> {
+        let open = |arg: &str| {
+            let package = args[arg]
+                .as_str()
+                .ok_or(format!("Missing '{}' argument", arg))?;
+            let path = self.resolve_package_path(package)?;
+            self.open_docpack(&path)
+        };
+        let (old, new) = (open("old")?, open("new")?);
+
+        let report = serde_json::to_value(crate::build_compare_report(&old, &new))
+            .map_err(|e| format!("Failed to serialize diff: {}", e))?;
+        let text = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Failed to serialize diff: {}", e))?;
+        *structured = Some(report);
+        Ok(text)
+    }
+
+    fn tool_get_symbol(
+        &self,
+        args: &Value,
+        structured: &mut Option<Value>,
+    ) -> Result<String, String> {
+        let package = args["package"]
+            .as_str()
+            .ok_or("Missing 'package' argument")?;
+        let symbol_name = args["symbol"].as_str().ok_or("Missing 'symbol' argument")?;
+
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
+
+        let mut matches = docpack.find_symbols_by_name(symbol_name);
+
+        if matches.is_empty() {
+            return Err(format!("No symbol found matching '{}'", symbol_name));
+        }
+
+        // Best first: the name as written, then public API over tests, then shortest ID
+        matches.sort_by_key(|s| {
+            (
+                !(s.id == symbol_name || s.id.ends_with(&format!("::{}", symbol_name))),
+                crate::docpack::is_test_symbol(s),
+                !s.is_public(),
+                s.id.len(),
+                s.id.as_str(),
+            )
+        });
+        matches.dedup_by(|a, b| a.id == b.id);
+
+        let best = matches[0];
+        let doc = docpack
+            .get_documentation(&best.doc_id)
+            .map_err(|e| format!("Failed to get documentation: {}", e))?;
+        let mut output = symbol_markdown(best, &doc);
+
+        // The rest only get a line each, so a vague name doesn't flood the context
+        let others = &matches[1..];
+        if !others.is_empty() {
+            output.push_str(&format!(
+                "---\n\nOther matches ({}); call get_symbol with an exact ID for details:\n\n",
+                others.len()
+            ));
+            for symbol in others.iter().take(OTHER_MATCHES_LIMIT) {
+                let summary = docpack
+                    .get_documentation(&symbol.doc_id)
+                    .map(|doc| doc.summary)
+                    .unwrap_or_default();
+                output.push_str(&format!("- [{}] {}: {}\n", symbol.kind, symbol.id, summary));
+            }
+            if others.len() > OTHER_MATCHES_LIMIT {
+                output.push_str(&format!(
+                    "- ... and {} more\n",
+                    others.len() - OTHER_MATCHES_LIMIT
+                ));
+            }
+        }
+
+        *structured = Some(json!({
+            "symbol": best,
+            "documentation": doc,
+            "otherMatches": others.iter().map(|s| &s.id).collect::<Vec<_>>()
+        }));
+        Ok(output)
+    }
+
+    fn tool_get_source(&self, args: &Value) -> Result<String, String> {
+        let package = args["package"]
+            .as_str()
+            .ok_or("Missing 'package' argument")?;
+        let symbol_name = args["symbol"].as_str().ok_or("Missing 'symbol' argument")?;
+        let context = match &args["context"] {
+            Value::Null => 0,
+            context => context
+                .as_u64()
+                .ok_or("'context' must be a non-negative integer")? as usize,
+        };
+
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
+
+        let matches = docpack.find_symbols_by_name(symbol_name);
+        let symbol = match matches.as_slice() {
+            [] => return Err(format!("No symbol found matching '{}'", symbol_name)),
+            [symbol] => *symbol,
+            _ => match matches.iter().find(|s| s.id == symbol_name) {
+                Some(symbol) => *symbol,
+                None => {
+                    let ids: Vec<_> = matches.iter().take(10).map(|s| s.id.as_str()).collect();
+                    return Err(format!(
+                        "'{}' matches {} symbols; use one of: {}",
+                        symbol_name,
+                        matches.len(),
+                        ids.join(", ")
+                    ));
+                }
+            },
+        };
+
+        // Without a recorded span, the snippet's length tells where the definition ends
+        let end_line = symbol.end_line.unwrap_or_else(|| {
+            let snippet_lines = symbol
+                .source_snippet
+                .as_deref()
+                .map_or(0, |s| s.lines().count());
+            symbol.line + snippet_lines.saturating_sub(1)
+        });
+        let mut output = format!(
+            "# {}\n\n**Location:** {}:{}",
+            symbol.id, symbol.file, symbol.line
+        );
+        if end_line > symbol.line {
+            output.push_str(&format!("-{}", end_line));
+        }
+        output.push_str("\n\n");
+
+        // The embedded file, when there is one, gives exact lines plus context
+        let source_file = docpack
+            .get_source_file(&symbol.file)
+            .map_err(|e| format!("Failed to read source: {}", e))?;
+        let lines: Vec<&str> = source_file.iter().flat_map(|s| s.lines()).collect();
+        if symbol.line >= 1 && symbol.line <= lines.len() {
+            let first = symbol.line.saturating_sub(context).max(1);
+            let last = (end_line + context).min(lines.len());
+            output.push_str(&format!("Lines {}-{}:\n\n```\n", first, last));
+            for (number, line) in lines[first - 1..last].iter().enumerate() {
+                output.push_str(&format!("{:>5} | {}\n", first + number, line));
+            }
+            output.push_str("```\n");
+        } else if let Some(snippet) = &symbol.source_snippet {
+            output.push_str(&format!("```\n{}\n```\n", snippet.trim_end()));
+        } else {
+            output.push_str(&format!(
+                "No source embedded for this symbol.\n\n**Signature:** `{}`\n",
+                symbol.signature
+            ));
+        }
+
+        Ok(output)
+    }
+
+    fn tool_get_examples(&self, args: &Value) -> Result<String, String> {
+        let package = args["package"]
+            .as_str()
+            .ok_or("Missing 'package' argument")?;
+        let symbol_name = args["symbol"].as_str().ok_or("Missing 'symbol' argument")?;
+
+        let path = self.resolve_package_path(package)?;
+        let docpack = self.open_docpack(&path)?;
+
+        let matches = docpack.find_symbols_by_name(symbol_name);
+
+        if matches.is_empty() {
+            return Err(format!("No symbol found matching '{}'", symbol_name));
+        }
+
+        let mut output = String::new();
+
+        for symbol in matches {
+            let doc = docpack
+                .get_documentation(&symbol.doc_id)
+                .map_err(|e| format!("Failed to get documentation: {}", e))?;
+            let examples = docpack
+                .get_examples(&symbol.doc_id)
+                .map_err(|e| format!("Failed to get examples: {}", e))?;
+
+            output.push_str(&format!("# Examples for {}\n\n", symbol.id));
+
+            if doc.example.is_empty() && examples.is_empty() {
+                output.push_str("No examples available for this symbol.\n\n");
+                continue;
+            }
+
+            if !doc.example.is_empty() {
+                output.push_str(&format!("```\n{}\n```\n\n", doc.example));
+            }
+
+            for example in &examples {
+                if !example.title.is_empty() {
+                    output.push_str(&format!("## {}\n", example.title));
+                }
+                if !example.source.is_empty() {
+                    output.push_str(&format!("_Source: {}_\n", example.source));
+                }
+                output.push_str(&format!("```\n{}\n```\n\n", example.code));
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn tool_search(
+        &self,
+        args: &Value,
+        next_cursor: &mut Option<String>,
+        structured: &mut Option<Value>,
+    ) -> Result<String, String> {
+        let query = args["query"].as_str().ok_or("Missing 'query' argument")?;
+        let (offset, limit) = page_arguments(args, DEFAULT_SEARCH_LIMIT)?;
+        let package_filter = args["package"].as_str();
+
+        let parsed = Query::parse(query).map_err(|e| e.to_string())?;
+        let as_typed = Query::syntax_error(query)
+            .map(|e| format!("{}; searched for the text as typed.\n\n", e))
+            .unwrap_or_default();
+
+        // (package, origin, match)
+        let mut all_results: Vec<(String, String, SearchMatch)> = Vec::new();
+
+        if let Some(package) = package_filter {
+            // Search specific package
+            let path = self.resolve_package_path(package)?;
+            let docpack = self.open_docpack(&path)?;
+
+            let results = docpack
+                .search_symbols(query, &self.search)
+                .map_err(|e| format!("Search failed: {}", e))?;
+
+            for result in results {
+                all_results.push((package.to_string(), String::new(), result));
+            }
+        } else {
+            // Search all packages, skipping those shadowed by an earlier root
+            for package in self.installed_packages()? {
+                if self.is_cancelled() {
+                    return Err("Search cancelled".to_string());
+                }
+
+                // The pack's index can rule a match out without loading it: each
+                // required term, or one of its aliases, must be there
+                let path = package.path.to_string_lossy();
+                let may_match = parsed.required_terms().into_iter().all(|term| {
+                    std::iter::once(term.to_string())
+                        .chain(self.search.aliases_for(term))
+                        .any(|term| crate::docpack::may_contain(&path, &term))
+                });
+                if package.shadowed || !may_match {
+                    continue;
+                }
+
+                let Ok(docpack) = self.open_docpack(&path) else {
+                    continue;
+                };
+                match docpack.search_symbols(query, &self.search) {
+                    Ok(results) => {
+                        let origin = self.origin_label(&package);
+                        for result in results {
+                            all_results.push((package.name.clone(), origin.clone(), result));
+                        }
+                    }
+                    Err(e) => self.diagnose(
+                        LogLevel::Warning,
+                        format!("Search of {} failed: {}", package.name, e),
+                    ),
+                }
+            }
+        }
+
+        if all_results.is_empty() {
+            return Ok(format!("{}No results found for '{}'", as_typed, query));
+        }
+        // Most relevant first across packages; ties keep package order
+        all_results.sort_by_key(|(_, _, result)| std::cmp::Reverse(result.score));
+
+        let mut output = as_typed;
+        output.push_str(&format!("Search results for '{}':\n\n", query));
+
+        let mut entries = Vec::new();
+        for (package, origin, result) in all_results.iter().skip(offset).take(limit) {
+            let SearchMatch {
+                symbol,
+                doc,
+                via_alias,
+                context,
+                score,
+            } = result;
+            entries.push(json!({
+                "package": package,
+                "id": symbol.id,
+                "kind": symbol.kind,
+                "file": symbol.file,
+                "line": symbol.line,
+                "summary": doc.summary,
+                "viaAlias": via_alias,
+                "match": context,
+                "score": score
+            }));
+            output.push_str(&format!(
+                "[{}] {}:{}{}",
+                symbol.kind, package, symbol.id, origin
+            ));
+            if let Some(alias) = via_alias {
+                output.push_str(&format!(" (via alias '{}')", alias));
+            }
+            output.push_str(&format!("\n  {}\n", doc.summary));
+            if let Some(context) = context {
+                output.push_str(&format!("  Match: {}\n", context));
+            }
+            output.push('\n');
+        }
+
+        output.push_str(&format!("Found {} result(s)", all_results.len()));
+        output.push_str(&page_footer(offset, limit, all_results.len(), next_cursor));
+        *structured = Some(json!({ "results": entries, "total": all_results.len() }));
+        Ok(output)
+    }
+
+    /// Open a docpack, recording a diagnostic when it can't be read. Packs stay
+    /// cached until their file changes or they are the least recently used of
+    /// more than `CACHED_DOCPACKS`.
+    fn open_docpack(&self, path: &str) -> Result<Arc<Docpack>, String> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let (Some(modified), Some(cached)) = (modified, lock(&self.docpacks).get_mut(path)) {
+            if cached.modified == modified {
+                cached.last_used = Instant::now();
+                return Ok(Arc::clone(&cached.docpack));
+            }
+        }
+
+        // Opened outside the lock so other requests aren't held up
+        let docpack = Docpack::open(path).map_err(|e| {
+            self.diagnose(
+                LogLevel::Error,
+                format!("Failed to open docpack {}: {:#}", path, e),
+            );
+            format!("Failed to open docpack: {}", e)
+        })?;
+        let docpack = Arc::new(docpack);
+        if let Some(modified) = modified {
+            let cached = CachedDocpack {
+                modified,
+                docpack: Arc::clone(&docpack),
+                last_used: Instant::now(),
+            };
+            let mut docpacks = lock(&self.docpacks);
+            docpacks.insert(path.to_string(), cached);
+            while docpacks.len() > CACHED_DOCPACKS {
+                let Some(oldest) = docpacks
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.last_used)
+                    .map(|(path, _)| path.clone())
+                else {
+                    break;
+                };
+                docpacks.remove(&oldest);
+            }
+        }
+        Ok(docpack)
+    }
+
+    fn resolve_package_path(&self, package: &str) -> Result<String, String> {
+        let not_found = || {
+            format!(
+                "Docpack '{}' not found. Run 'localdoc list' to see installed docpacks.",
+                package
+            )
+        };
+
+        // Package names are "user:repo"; anything that could leave a packages root is refused
+        let valid = !package.is_empty()
+            && !package.contains("..")
+            && package
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '_' | '-' | '.'));
+        if !valid {
+            return Err(format!("Invalid package name '{}'", package));
+        }
+
+        // The first root holding the package wins
+        let filename = format!("{}.docpack", package.replace(':', "_"));
+        let (root, path) = self
+            .packages_dirs
+            .iter()
+            .map(|dir| (dir, dir.join(&filename)))
+            .find(|(_, path)| path.exists())
+            .ok_or_else(not_found)?;
+
+        // Symlinks inside a root must not point outside it either
+        let root = root.canonicalize().map_err(|_| not_found())?;
+        let path = path.canonicalize().map_err(|_| not_found())?;
+        if !path.starts_with(&root) {
+            return Err(format!(
+                "Docpack '{}' resolves outside the packages directory",
+                package
+            ));
+        }
+
+        Ok(path.to_string_lossy().to_string())
+    }
+}
+
+/// Markdown sections dropped, in this order, to fit a result into a size budget;
+/// the summary, signature, and location are kept longest
+const DROPPABLE_SECTIONS: &[&str] = &[
+    "## Example",
+    "## Notes",
+    "## Description",
+    "## Parameters",
+    "## Returns",
+];
+
+/// Size budget in characters from `max_chars` or `max_tokens`, whichever is smaller
+fn budget_argument(args: &Value) -> Option<usize> {
+    let chars = args["max_chars"].as_u64();
+    let tokens = args["max_tokens"].as_u64().map(|t| t.saturating_mul(4));
+    chars.into_iter().chain(tokens).min().map(|n| n as usize)
+}
+
+/// Shorten a tool result to at most `max_chars` characters: first drop detail
+/// sections of symbol docs, then cut whole lines from the end, saying what was omitted
+fn fit_to_budget(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut text = text.to_string();
+    let mut omitted = Vec::new();
+    for heading in DROPPABLE_SECTIONS {
+        if text.chars().count() <= max_chars {
+            break;
+        }
+        let kept = drop_sections(&text, heading);
+        if kept.len() < text.len() {
+            omitted.push(heading.trim_start_matches("## "));
+            text = kept;
+        }
+    }
+
+    let mut trailer = String::new();
+    if !omitted.is_empty() {
+        trailer = format!("\n[{} omitted to fit max_chars]", omitted.join(", "));
+    }
+    if text.chars().count() + trailer.chars().count() <= max_chars {
+        return text + &trailer;
+    }
+
+    // Keep as many whole lines as fit alongside the trailer
+    let lines: Vec<&str> = text.lines().collect();
+    let mut kept = String::new();
+    let mut kept_lines = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let trailer = format!(
+            "\n... {} more line(s) omitted to fit max_chars{}",
+            lines.len() - i,
+            trailer
+        );
+        if kept.chars().count() + line.chars().count() + 1 + trailer.chars().count() > max_chars {
+            break;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+        kept_lines += 1;
+    }
+
+    format!(
+        "{}... {} more line(s) omitted to fit max_chars{}",
+        kept,
+        lines.len() - kept_lines,
+        trailer
+    )
+}
+
+/// Remove every markdown section whose heading starts with `heading`, up to the next heading
+fn drop_sections(text: &str, heading: &str) -> String {
+    let mut kept = String::new();
+    let mut dropping = false;
+    for line in text.split_inclusive('\n') {
+        if line.starts_with('#') || line.starts_with("---") {
+            dropping = line.starts_with(heading);
+        }
+        if !dropping {
+            kept.push_str(line);
+        }
+    }
+    kept
+}
+
+/// Deepest walk the call graph tools will do
+const MAX_GRAPH_DEPTH: usize = 5;
+
+/// How one symbol depends on another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    /// Called or instantiated from its source snippet
+    Calls,
+    /// Named in its signature
+    Types,
+}
+
+/// Input schema of the call graph tools; only `get_dependencies` filters by edge kind
+fn graph_schema(edge_kinds: bool) -> Value {
+    let mut schema = json!({
+        "type": "object",
+        "properties": {
+            "package": {
+                "type": "string",
+                "description": "Package name in format username:reponame"
+            },
+            "symbol": {
+                "type": "string",
+                "description": "Symbol name or ID to start from"
+            },
+            "depth": {
+                "type": "integer",
+                "description": format!("Optional: how many levels to follow (1-{}, default 1)", MAX_GRAPH_DEPTH)
+            }
+        },
+        "required": ["package", "symbol"]
+    });
+    if edge_kinds {
+        schema["properties"]["edge_kinds"] = json!({
+            "type": "array",
+            "items": { "type": "string", "enum": ["calls", "types"] },
+            "description": "Optional: edges to follow (default both)"
+        });
+    }
+    schema
+}
+
+/// Largest request body accepted over HTTP
+const MAX_HTTP_BODY: usize = 16 * 1024 * 1024;
+
+/// Largest request line plus headers accepted over HTTP
+const MAX_HTTP_HEADERS: u64 = 64 * 1024;
+
+/// How long an HTTP connection may sit idle while reading or writing
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP connections served at once; more wait to be accepted until one closes
+const MAX_HTTP_CONNECTIONS: usize = 32;
+
+/// Read one HTTP/1.1 request: request line, headers, and a `Content-Length` body
+fn read_http_request(stream: &mut TcpStream) -> Result<HttpRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut head = (&mut reader).take(MAX_HTTP_HEADERS);
+
+    let request_line = read_header_line(&mut head)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Malformed request line");
+    };
+    let method = method.to_string();
+    let path = path.split('?').next().unwrap_or(path).to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_header_line(&mut head)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length: usize = headers
+        .iter()
+        .find(|(key, _)| key == "content-length")
+        .map(|(_, value)| value.parse())
+        .transpose()
+        .context("Invalid Content-Length")?
+        .unwrap_or(0);
+    if length > MAX_HTTP_BODY {
+        anyhow::bail!("Request body too large");
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    Ok(HttpRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8(body).context("Request body is not valid UTF-8")?,
+    })
+}
+
+/// One line of the request head, failing once `MAX_HTTP_HEADERS` is used up
+fn read_header_line<R: BufRead>(head: &mut std::io::Take<R>) -> Result<String> {
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+    if head.limit() == 0 && !line.ends_with('\n') {
+        anyhow::bail!("Request headers too large");
+    }
+    Ok(line)
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Symbols of a module, a single file or every file under a directory,
+/// in file and line order
+fn module_members<'d>(docpack: &'d Docpack, module: &str) -> Result<Vec<&'d Symbol>, String> {
+    let mut members: Vec<_> = docpack
+        .symbols
+        .iter()
+        .filter(|s| {
+            s.file == module
+                || s.file
+                    .strip_prefix(module)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .collect();
+
+    if members.is_empty() {
+        return Err(format!(
+            "No module found matching '{}'. Use list_modules to see available modules.",
+            module
+        ));
+    }
+
+    members.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+    Ok(members)
+}
+
+// A panic while holding one of these locks leaves the data consistent (a log
+// file, a level, in-flight flags, or cached docpacks), so poisoning is ignored
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Write a stdio reply, keeping its notifications and response together
+fn write_reply(reply: &Reply) -> Result<()> {
+    let Reply::Response(body, notifications) = reply else {
+        return Ok(());
+    };
+    let mut stdout = std::io::stdout().lock();
+    for message in notifications.iter().chain(body) {
+        writeln!(stdout, "{}", message)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// The JSON-encoded ID of a request to run on a worker thread: tool calls,
+/// resource reads, and prompts, which may load whole docpacks
+fn background_request_id(message: &str) -> Option<String> {
+    let message: Value = serde_json::from_str(message).ok()?;
+    let id = message.get("id")?;
+    matches!(
+        message["method"].as_str(),
+        Some("tools/call" | "resources/read" | "prompts/get")
+    )
+    .then(|| id.to_string())
+}
+
+/// Candidates starting with `value`, ignoring case, then those whose last `::`
+/// segment does, so "par" offers "Parser::parse_node"
+fn completions<'a>(candidates: &'a [String], value: &str) -> Vec<&'a String> {
+    let value = value.to_lowercase();
+    let (mut matches, rest): (Vec<_>, Vec<_>) = candidates
+        .iter()
+        .partition(|c| c.to_lowercase().starts_with(&value));
+    matches.extend(rest.into_iter().filter(|c| {
+        c.rsplit("::")
+            .next()
+            .is_some_and(|segment| segment.to_lowercase().starts_with(&value))
+    }));
+    matches
+}
+
+/// Human-readable tool name for annotations, e.g. "Get symbol" for `get_symbol`
+fn tool_title(name: &str) -> String {
+    let words = name.replace('_', " ");
+    let mut chars = words.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Whether an `Origin` header names this machine, guarding against DNS rebinding
+fn is_local_origin(origin: &str) -> bool {
+    let authority = origin.split("://").nth(1).unwrap_or(origin);
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => authority.split([':', '/']).next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+fn write_http_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "",
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    if status == 405 {
+        response.push_str("Allow: POST\r\n");
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+
+    // The client may have gone away; that shouldn't stop the server
+    let _ = stream.write_all(response.as_bytes());
+    Ok(())
+}
+
+/// Offset from the `cursor` argument and page size from `limit`
+fn page_arguments(args: &Value, default_limit: usize) -> Result<(usize, usize), String> {
+    let offset = match &args["cursor"] {
+        Value::Null => 0,
+        Value::String(cursor) => cursor
+            .parse()
+            .map_err(|_| format!("Invalid cursor '{}'", cursor))?,
+        other => return Err(format!("Invalid cursor {}", other)),
+    };
+    let limit = match &args["limit"] {
+        Value::Null => default_limit,
+        limit => limit
+            .as_u64()
+            .filter(|&limit| limit > 0)
+            .ok_or("'limit' must be a positive integer")? as usize,
+    };
+    Ok((offset, limit))
+}
+
+/// Describe which page was shown, setting `next_cursor` when more results remain
+fn page_footer(
+    offset: usize,
+    limit: usize,
+    total: usize,
+    next_cursor: &mut Option<String>,
+) -> String {
+    let end = offset.saturating_add(limit).min(total);
+    if offset == 0 && end == total {
+        return String::new();
+    }
+    if end < total {
+        *next_cursor = Some(end.to_string());
+        format!(
+            " (showing {}-{}; pass cursor \"{}\" for more)",
+            offset + 1,
+            end,
+            end
+        )
+    } else if offset < total {
+        format!(" (showing {}-{})", offset + 1, end)
+    } else {
+        " (no more results)".to_string()
+    }
+}
+
+/// Split text into pieces of at most `max_bytes`, breaking after a newline where possible
+fn chunk_text(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while rest.len() > max_bytes {
+        let mut end = max_bytes;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        if let Some(newline) = rest[..end].rfind('\n') {
+            end = newline + 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// Markdown documentation for one symbol, as returned by `get_symbol`
+fn symbol_markdown(symbol: &Symbol, doc: &Documentation) -> String {
+    let mut output = String::new();
+    output.push_str(&format!("# {}\n\n", symbol.id));
+    output.push_str(&format!("**Kind:** {}\n", symbol.kind));
+    output.push_str(&format!("**Location:** {}:{}\n", symbol.file, symbol.line));
+    output.push_str(&format!("**Signature:** `{}`\n", symbol.signature));
+    if !symbol.attributes.is_empty() {
+        output.push_str(&format!(
+            "**Attributes:** `{}`\n",
+            symbol.attributes.join(" ")
+        ));
+    }
+    if let Some(cfg) = &symbol.cfg {
+        output.push_str(&format!("**Cfg:** `{}`\n", cfg));
+    }
+    if let Some(generics) = &symbol.generics {
+        output.push_str(&format!("**Generics:** `{}`\n", generics));
+    }
+    output.push('\n');
+
+    output.push_str(&format!("## Summary\n{}\n\n", doc.summary));
+    output.push_str(&format!("## Description\n{}\n\n", doc.description));
+
+    if !symbol.variants.is_empty() {
+        output.push_str("## Variants\n");
+        for variant in &symbol.variants {
+            if variant.doc.is_empty() {
+                output.push_str(&format!("- `{}`\n", variant));
+            } else {
+                output.push_str(&format!("- `{}`: {}\n", variant, variant.doc));
+            }
+        }
+        output.push('\n');
+    }
+
+    if !doc.parameters.is_empty() {
+        output.push_str("## Parameters\n");
+        for param in &doc.parameters {
+            if param.description.is_empty() {
+                output.push_str(&format!("- **{}** ({})\n", param.name, param.param_type));
+            } else {
+                output.push_str(&format!(
+                    "- **{}** ({}): {}\n",
+                    param.name, param.param_type, param.description
+                ));
+            }
+        }
+        output.push('\n');
+    }
+
+    if !doc.returns.is_empty() {
+        output.push_str(&format!("## Returns\n{}\n\n", doc.returns));
+    }
+
+    if !doc.example.is_empty() {
+        output.push_str(&format!("## Example\n```\n{}\n```\n\n", doc.example));
+    } else if let Some(example) = synthesize_example(symbol, doc) {
+        output.push_str(&format!(
+            "## Example (generated from signature)\n```\n{}\n```\n\n",
+            example
+        ));
+    }
+
+    if !doc.notes.is_empty() {
+        output.push_str("## Notes\n");
+        for note in &doc.notes {
+            output.push_str(&format!("- {}\n", note));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Build a minimal call example for a function from its signature and parameter
+/// types, for symbols whose documentation has none
+fn synthesize_example(symbol: &Symbol, doc: &Documentation) -> Option<String> {
+    if !matches!(symbol.kind.as_str(), "function" | "method") {
+        return None;
+    }
+
+    let segments: Vec<_> = symbol
+        .id
+        .split([':', '.'])
+        .filter(|s| !s.is_empty())
+        .collect();
+    let name = *segments.last()?;
+    let owner = segments.len().checked_sub(2).map(|i| segments[i]);
+
+    let mut params: Vec<(String, String)> = if doc.parameters.is_empty() {
+        signature_params(&symbol.signature)
+    } else {
+        doc.parameters
+            .iter()
+            .map(|p| (p.name.clone(), p.param_type.clone()))
+            .collect()
+    };
+
+    let language = symbol.file.rsplit_once('.').map_or("", |(_, ext)| ext);
+    let is_receiver = |name: &str| {
+        matches!(
+            name.trim_start_matches(['&', ' '])
+                .trim_start_matches("mut "),
+            "self" | "this"
+        )
+    };
+    let has_receiver = params.first().is_some_and(|(name, _)| is_receiver(name));
+    if has_receiver {
+        params.remove(0);
+    }
+    params.retain(|(name, _)| name != "cls");
+
+    let args = params
+        .iter()
+        .map(|(name, ty)| placeholder(name, ty, language))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let returns = symbol
+        .signature
+        .rsplit_once("->")
+        .map(|(_, returns)| returns.trim().trim_end_matches(['{', ':']).trim());
+    let fallible = returns.is_some_and(|r| r.contains("Result"));
+
+    let callee = match (owner, has_receiver) {
+        (Some(owner), true) => format!("{}.{}", receiver_name(owner), name),
+        (Some(owner), false) if language == "rs" => format!("{}::{}", owner, name),
+        (Some(owner), false) => format!("{}.{}", owner, name),
+        (None, _) => name.to_string(),
+    };
+    let call = format!("{}({})", callee, args);
+
+    let example = match language {
+        "rs" => match returns {
+            Some(_) if fallible => format!("let result = {}?;", call),
+            Some(_) => format!("let result = {};", call),
+            None => format!("{};", call),
+        },
+        "py" => match returns {
+            Some(r) if r != "None" => format!("result = {}", call),
+            _ => call,
+        },
+        "js" | "ts" | "jsx" | "tsx" => format!("const result = {};", call),
+        _ => call,
+    };
+
+    Some(example)
+}
+
+/// Parameters as (name, type) parsed from a `name: Type` style signature
+fn signature_params(signature: &str) -> Vec<(String, String)> {
+    let Some(start) = signature.find('(') else {
+        return Vec::new();
+    };
+
+    let mut depth = 0;
+    let mut params = Vec::new();
+    let mut current = String::new();
+    for c in signature[start + 1..].chars() {
+        match c {
+            '(' | '<' | '[' | '{' => depth += 1,
+            ')' | '>' | ']' | '}' if depth > 0 => depth -= 1,
+            ')' => break,
+            ',' if depth == 0 => {
+                params.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    params.push(current);
+
+    params
+        .into_iter()
+        .map(|param| param.trim().to_string())
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.split_once(':') {
+            Some((name, ty)) => (name.trim().to_string(), ty.trim().to_string()),
+            None => (param, String::new()),
+        })
+        .collect()
+}
+
+/// A sample argument for a parameter based on its type
+fn placeholder(name: &str, ty: &str, language: &str) -> String {
+    let ty = ty.trim();
+    let base = ty.trim_start_matches('&').trim_start_matches("mut ").trim();
+
+    let value = match (language, base) {
+        (_, "str" | "string" | "String") => "\"example\"",
+        ("rs", "bool") | ("js" | "ts", "boolean") => "false",
+        ("py", "bool") => "False",
+        (_, "f32" | "f64" | "float" | "number") => "0.0",
+        (
+            _,
+            "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" | "int",
+        ) => "0",
+        ("rs", b) if b.starts_with("Vec<") || b.starts_with('[') => "vec![]",
+        ("rs", b) if b.starts_with("Option<") => "None",
+        ("py", b) if b.starts_with("list") || b.starts_with("List") => "[]",
+        ("py", b) if b.starts_with("dict") || b.starts_with("Dict") => "{}",
+        _ => return name.trim_start_matches(['*', '_']).to_string(),
+    };
+
+    if language == "rs" && ty.starts_with('&') && base == "String" {
+        format!("&{}.to_string()", value)
+    } else if language == "rs" && base == "String" {
+        format!("{}.to_string()", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// A variable name for an instance of `type_name`, e.g. "http_client" for "HttpClient"
+fn receiver_name(type_name: &str) -> String {
+    let mut name = String::new();
+    for (i, c) in type_name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+        } else {
+            name.push(c);
         }
     }
+    name
 }
@@ -0,0 +1,491 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Metrics computed from a symbol's source snippet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metrics {
+    pub halstead_volume: f64,
+    pub cyclomatic_complexity: u32,
+    /// Maintainability index normalized to 0-100 (higher is easier to maintain)
+    pub maintainability_index: f64,
+    pub nesting_depth: u32,
+    pub lines_of_code: u32,
+}
+
+/// Keywords that open an extra path through the code
+const DECISION_KEYWORDS: &[&str] = &[
+    "if", "elif", "for", "while", "loop", "case", "catch", "except", "when",
+];
+
+/// Keywords counted as Halstead operators rather than operands
+const OPERATOR_KEYWORDS: &[&str] = &[
+    "if", "else", "elif", "for", "while", "loop", "match", "case", "switch", "return", "break",
+    "continue", "let", "var", "const", "fn", "def", "function", "in", "try", "catch", "except",
+    "finally", "throw", "raise", "await", "async", "yield", "new", "mut", "as", "and", "or", "not",
+    "is", "lambda", "with",
+];
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Literal(&'a str),
+    Punct(&'a str),
+}
+
+/// Compute metrics for a snippet of source code
+pub fn compute(snippet: &str) -> Metrics {
+    let tokens = tokenize(snippet);
+
+    let mut operators = HashSet::new();
+    let mut operands = HashSet::new();
+    let mut total_operators = 0usize;
+    let mut total_operands = 0usize;
+    let mut complexity = 1u32;
+
+    for token in &tokens {
+        match token {
+            Token::Word(word) if OPERATOR_KEYWORDS.contains(word) => {
+                operators.insert(*word);
+                total_operators += 1;
+                if DECISION_KEYWORDS.contains(word) {
+                    complexity += 1;
+                }
+            }
+            Token::Word(word) | Token::Literal(word) => {
+                operands.insert(*word);
+                total_operands += 1;
+            }
+            Token::Punct(punct) => {
+                // Closing brackets pair with their opener and aren't counted twice
+                if matches!(*punct, ")" | "]" | "}") {
+                    continue;
+                }
+                operators.insert(*punct);
+                total_operators += 1;
+                if matches!(*punct, "&&" | "||" | "?") {
+                    complexity += 1;
+                }
+            }
+        }
+    }
+
+    let vocabulary = (operators.len() + operands.len()) as f64;
+    let length = (total_operators + total_operands) as f64;
+    let halstead_volume = if vocabulary > 1.0 {
+        length * vocabulary.log2()
+    } else {
+        0.0
+    };
+
+    let lines_of_code = code_lines(snippet).count() as u32;
+
+    // Classic maintainability index, rescaled to 0-100 as popularized by Visual Studio
+    let raw_mi = 171.0
+        - 5.2 * halstead_volume.max(1.0).ln()
+        - 0.23 * complexity as f64
+        - 16.2 * (lines_of_code.max(1) as f64).ln();
+    let maintainability_index = (raw_mi * 100.0 / 171.0).clamp(0.0, 100.0);
+
+    Metrics {
+        halstead_volume,
+        cyclomatic_complexity: complexity,
+        maintainability_index,
+        nesting_depth: nesting_depth(snippet, &tokens),
+        lines_of_code,
+    }
+}
+
+/// A TODO-style marker found in a comment
+#[derive(Debug, Clone, Serialize)]
+pub struct Marker {
+    pub kind: String,
+    /// Zero-based line offset within the snippet
+    pub offset: usize,
+    pub text: String,
+}
+
+/// Markers that flag unfinished or questionable code
+const MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Find TODO/FIXME/HACK/XXX markers in a snippet's comments
+pub fn find_markers(snippet: &str) -> Vec<Marker> {
+    let mut markers = Vec::new();
+
+    for (offset, line) in snippet.lines().enumerate() {
+        let Some(start) = comment_start(line) else {
+            continue;
+        };
+        let comment = &line[start..];
+
+        let found = MARKERS.iter().find(|marker| {
+            comment.match_indices(*marker).any(|(i, _)| {
+                let before = comment[..i].chars().next_back();
+                let after = comment[i + marker.len()..].chars().next();
+                !before.is_some_and(|c| c.is_alphanumeric())
+                    && !after.is_some_and(|c| c.is_alphanumeric())
+            })
+        });
+
+        if let Some(kind) = found {
+            let text = comment
+                .trim_start_matches(['/', '*', '#', '!'])
+                .trim()
+                .to_string();
+            markers.push(Marker {
+                kind: kind.to_string(),
+                offset,
+                text,
+            });
+        }
+    }
+
+    markers
+}
+
+/// Paths that a snippet calls or instantiates, e.g. "Parser::new", "self.next",
+/// or "Node" for `Node { .. }` and `Node(..)`. Comments and strings are ignored.
+pub fn call_paths(snippet: &str) -> Vec<String> {
+    call_sites(snippet)
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// The paths `call_paths` finds, each with the zero-based line offset of its call
+pub fn call_sites(snippet: &str) -> Vec<(usize, String)> {
+    let spans = tokenize_spans(snippet);
+    let tokens: Vec<&Token> = spans.iter().map(|(_, token)| token).collect();
+    let mut paths = Vec::new();
+    // Newlines counted up to `counted`, so each line offset costs only the gap since the last
+    let (mut offset, mut counted) = (0, 0);
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let Token::Word(first) = *tokens[i] else {
+            i += 1;
+            continue;
+        };
+
+        let mut segments = vec![first];
+        let mut j = i + 1;
+        while let (Some(Token::Punct("::" | ".")), Some(&&Token::Word(next))) =
+            (tokens.get(j), tokens.get(j + 1))
+        {
+            segments.push(next);
+            j += 2;
+        }
+
+        let last = segments[segments.len() - 1];
+        let is_call = match tokens.get(j) {
+            Some(Token::Punct("(")) => true,
+            // Only type-like names open struct literals; `if x {` is not a call
+            Some(Token::Punct("{")) => last.starts_with(|c: char| c.is_ascii_uppercase()),
+            _ => false,
+        };
+        // Skip control keywords like `if (` and the names in `fn parse(` / `def parse(`
+        let is_keyword = segments.len() == 1 && OPERATOR_KEYWORDS.contains(&last);
+        let is_definition = i > 0
+            && matches!(
+                tokens[i - 1],
+                Token::Word("fn" | "def" | "function" | "class" | "struct" | "enum")
+            );
+        if is_call && !is_keyword && !is_definition {
+            let start = spans[i].0;
+            offset += snippet[counted..start].matches('\n').count();
+            counted = start;
+            paths.push((offset, segments.join("::")));
+        }
+
+        i = j;
+    }
+
+    paths
+}
+
+/// Macros that panic when reached
+const PANIC_MACROS: &[&str] = &["panic", "unreachable", "todo", "unimplemented"];
+
+/// Count explicit failure points in a snippet: panicking macros, `.unwrap()`,
+/// `.expect(..)`, `raise`, and `throw`. Comments and strings are ignored.
+pub fn panic_sources(snippet: &str) -> BTreeMap<&'static str, usize> {
+    let tokens = tokenize(snippet);
+    let mut counts = BTreeMap::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        let Token::Word(word) = token else {
+            continue;
+        };
+        let next = tokens.get(i + 1);
+        let after_dot = i > 0 && tokens[i - 1] == Token::Punct(".");
+
+        let kind = match *word {
+            w if next == Some(&Token::Punct("!")) => {
+                PANIC_MACROS.iter().find(|m| **m == w).copied()
+            }
+            "unwrap" if after_dot && next == Some(&Token::Punct("(")) => Some("unwrap"),
+            "expect" if after_dot && next == Some(&Token::Punct("(")) => Some("expect"),
+            "raise" => Some("raise"),
+            "throw" => Some("throw"),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            *counts.entry(kind).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+/// Number of `unsafe` keywords in a snippet, ignoring comments and strings
+pub fn unsafe_count(snippet: &str) -> usize {
+    tokenize(snippet)
+        .iter()
+        .filter(|token| **token == Token::Word("unsafe"))
+        .count()
+}
+
+/// Quoted string literals in a snippet's code, with their zero-based line offset.
+/// Comments are skipped; the quotes themselves are not included.
+pub fn string_literals(snippet: &str) -> Vec<(usize, &str)> {
+    let mut literals = Vec::new();
+
+    for (offset, line) in snippet.lines().enumerate() {
+        let code = &line[..comment_start(line).unwrap_or(line.len())];
+        let bytes = code.as_bytes();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let quote = bytes[i];
+            if !matches!(quote, b'"' | b'\'' | b'`') {
+                i += 1;
+                continue;
+            }
+
+            let start = i + 1;
+            let mut end = start;
+            while end < bytes.len() && bytes[end] != quote {
+                if bytes[end] == b'\\' {
+                    end += 1;
+                }
+                end += 1;
+            }
+            if end >= bytes.len() {
+                break;
+            }
+
+            literals.push((offset, &code[start..end]));
+            i = end + 1;
+        }
+    }
+
+    literals
+}
+
+/// Fraction of non-blank lines in a snippet that carry a comment
+pub fn comment_density(snippet: &str) -> f64 {
+    let lines: Vec<_> = snippet.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return 0.0;
+    }
+    let commented = lines.iter().filter(|l| comment_start(l).is_some()).count();
+    commented as f64 / lines.len() as f64
+}
+
+/// Byte offset where a line's comment begins, if it has one
+fn comment_start(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    if trimmed.starts_with('*') || is_hash_comment(trimmed) {
+        return Some(indent);
+    }
+    match (line.find("//"), line.find("/*")) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Lines that contain code, i.e. are neither blank nor comment-only
+fn code_lines(snippet: &str) -> impl Iterator<Item = &str> {
+    snippet.lines().map(str::trim).filter(|line| {
+        !line.is_empty()
+            && !line.starts_with("//")
+            && !line.starts_with("/*")
+            && !line.starts_with('*')
+            && !is_hash_comment(line)
+    })
+}
+
+/// Whether a line starting with '#' is a comment rather than a Rust attribute
+fn is_hash_comment(line: &str) -> bool {
+    line.starts_with('#') && !line.starts_with("#[") && !line.starts_with("#!")
+}
+
+/// Deepest block nesting inside the symbol's own body.
+/// Uses braces when present, otherwise indentation (for Python-like code).
+fn nesting_depth(snippet: &str, tokens: &[Token]) -> u32 {
+    if tokens.contains(&Token::Punct("{")) {
+        let mut depth = 0u32;
+        let mut max_depth = 0u32;
+        for token in tokens {
+            match token {
+                Token::Punct("{") => {
+                    depth += 1;
+                    max_depth = max_depth.max(depth);
+                }
+                Token::Punct("}") => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        return max_depth.saturating_sub(1);
+    }
+
+    let indents: Vec<usize> = snippet
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum()
+        })
+        .collect();
+
+    let base = indents.first().copied().unwrap_or(0);
+    let deepest = indents
+        .iter()
+        .map(|indent| indent.saturating_sub(base) / 4)
+        .max()
+        .unwrap_or(0);
+
+    (deepest as u32).saturating_sub(1)
+}
+
+/// Split source code into words, literals and punctuation, skipping comments
+fn tokenize(code: &str) -> Vec<Token<'_>> {
+    tokenize_spans(code)
+        .into_iter()
+        .map(|(_, token)| token)
+        .collect()
+}
+
+/// Tokens of `tokenize`, each with the byte offset where it starts
+fn tokenize_spans(code: &str) -> Vec<(usize, Token<'_>)> {
+    let bytes = code.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if code[i..].starts_with("//") || is_hash_comment(&code[i..]) {
+            i += code[i..].find('\n').unwrap_or(code.len() - i);
+        } else if code[i..].starts_with("/*") {
+            i += code[i..]
+                .find("*/")
+                .map(|end| end + 2)
+                .unwrap_or(code.len() - i);
+        } else if c == b'"' || is_char_literal(&bytes[i..]) {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i] != c {
+                if bytes[i] == b'\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(bytes.len());
+            tokens.push((start, Token::Literal(&code[start..i])));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.' || bytes[i] == b'_')
+            {
+                i += 1;
+            }
+            tokens.push((start, Token::Literal(&code[start..i])));
+        } else if c.is_ascii_alphabetic() || c == b'_' || !c.is_ascii() {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || !bytes[i].is_ascii())
+            {
+                i += 1;
+            }
+            tokens.push((start, Token::Word(&code[start..i])));
+        } else if b"+-*/%=<>!&|^~?:".contains(&c) {
+            let start = i;
+            while i < bytes.len() && b"+-*/%=<>!&|^~?:".contains(&bytes[i]) {
+                i += 1;
+            }
+            tokens.push((start, Token::Punct(&code[start..i])));
+        } else {
+            tokens.push((i, Token::Punct(&code[i..i + 1])));
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// Whether the input starts with a quoted character literal like 'a' or '\n'
+/// rather than a lifetime like 'a
+fn is_char_literal(bytes: &[u8]) -> bool {
+    if bytes.first() != Some(&b'\'') {
+        return false;
+    }
+    match bytes.get(1) {
+        Some(b'\\') => true,
+        Some(_) => bytes.get(2) == Some(&b'\''),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halstead_counts() {
+        // Operators: fn ( , { return + ; (7 distinct, 7 in all; closers pair with openers)
+        // Operands: add a b (3 distinct, 5 in all)
+        let metrics = compute("fn add(a, b) {\n    return a + b;\n}");
+        let (vocabulary, length) = (7.0 + 3.0, 7.0 + 5.0);
+        assert!((metrics.halstead_volume - length * f64::log2(vocabulary)).abs() < 1e-9);
+        assert_eq!(metrics.cyclomatic_complexity, 1);
+        assert_eq!(metrics.nesting_depth, 0);
+        assert_eq!(metrics.lines_of_code, 3);
+    }
+
+    #[test]
+    fn comments_and_strings_are_not_counted() {
+        let plain = compute("fn f() {\n    g(\"x\");\n}");
+        let commented = compute("fn f() {\n    // if a && b { loop }\n    g(\"if else\");\n}");
+        assert_eq!(commented.halstead_volume, plain.halstead_volume);
+        assert_eq!(commented.cyclomatic_complexity, 1);
+        assert_eq!(commented.lines_of_code, plain.lines_of_code);
+    }
+
+    #[test]
+    fn complexity_and_nesting() {
+        let snippet = "fn f(a: bool, b: bool) {\n    if a && b {\n        for x in y {\n            g(x?);\n        }\n    } else if a || b {\n        h();\n    }\n}";
+        let metrics = compute(snippet);
+        // One path, plus if, &&, for, ?, if, ||
+        assert_eq!(metrics.cyclomatic_complexity, 7);
+        assert_eq!(metrics.nesting_depth, 2);
+        assert_eq!(metrics.lines_of_code, 9);
+    }
+
+    #[test]
+    fn call_sites_with_line_offsets() {
+        let snippet = "fn run() {\n    let c = Config::load();\n    // serve(c);\n    self.serve(c, Node { id: 1 });\n    if (ready) {}\n}";
+        assert_eq!(
+            call_sites(snippet),
+            vec![
+                (1, "Config::load".to_string()),
+                (3, "self::serve".to_string()),
+                (3, "Node".to_string()),
+            ]
+        );
+    }
+}
@@ -9,6 +9,12 @@ pub struct Manifest {
     pub language_summary: HashMap<String, u32>,
     pub stats: Stats,
     pub public: bool,
+    /// Sections removed by `localdoc prune-docs`, e.g. "docs" or "snippets"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stripped: Vec<String>,
+    /// Every entry except manifest.json is AES-encrypted (see `localdoc encrypt`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +23,19 @@ pub struct ProjectInfo {
     pub version: String,
     pub repo: String,
     pub commit: String,
+    /// SPDX identifier or name of the detected license
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dependency {
+    pub name: String,
+    /// Version requirement as declared by the project
+    #[serde(default)]
+    pub version: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,11 +50,178 @@ pub struct Symbol {
     pub kind: String,
     pub file: String,
     pub line: usize,
+    /// Last line of the symbol's definition, when the generator recorded its span
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
     pub signature: String,
     pub doc_id: String,
+    /// IDs of test functions that exercise this symbol
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generics: Option<Generics>,
+    /// Variants of an enum symbol
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<Variant>,
+    /// Attributes or decorators, e.g. "#[deprecated]" or "@staticmethod"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    /// Conditional-compilation predicate gating this symbol,
+    /// e.g. `feature = "serde"` or `target_os = "linux"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfg: Option<String>,
+    /// Source code of the symbol's definition, when the generator embedded it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_snippet: Option<String>,
+    /// Free-form tags, e.g. "security:eval" added by `localdoc scan`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Most recent commit touching the symbol, recorded by `localdoc enrich`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<LastModified>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastModified {
+    pub commit: String,
+    pub author: String,
+    /// Author date as YYYY-MM-DD
+    pub date: String,
+    /// Author date as a Unix timestamp
+    pub timestamp: i64,
+}
+
+impl Symbol {
+    /// Lines of code spanned by this symbol, if its end line is known
+    pub fn loc(&self) -> Option<usize> {
+        self.end_line
+            .filter(|end| *end >= self.line)
+            .map(|end| end - self.line + 1)
+    }
+
+    /// Whether any attribute contains `attr` (case-insensitive)
+    pub fn has_attribute(&self, attr: &str) -> bool {
+        let attr = attr.to_lowercase();
+        self.attributes
+            .iter()
+            .any(|a| a.to_lowercase().contains(&attr))
+    }
+
+    /// Whether this symbol is gated behind the given cargo feature
+    pub fn requires_feature(&self, feature: &str) -> bool {
+        self.cfg.as_ref().is_some_and(|cfg| {
+            cfg.replace(' ', "")
+                .contains(&format!("feature=\"{}\"", feature))
+        })
+    }
+
+    /// Issue and PR references recorded by `localdoc enrich`, e.g. "#123"
+    pub fn related_refs(&self) -> Vec<&str> {
+        self.tags
+            .iter()
+            .filter_map(|tag| tag.strip_prefix("ref:"))
+            .collect()
+    }
+
+    /// Whether the signature declares an async function (`async fn`, `async def`, ...)
+    pub fn is_async(&self) -> bool {
+        self.signature
+            .split_whitespace()
+            .any(|word| word == "async")
+    }
+
+    /// Whether the signature returns a `Result` or declares thrown exceptions
+    pub fn is_fallible(&self) -> bool {
+        let returns = self
+            .signature
+            .rsplit_once("->")
+            .map_or("", |(_, returns)| returns);
+        returns.contains("Result") || self.signature.contains(" throws ")
+    }
+
+    /// Whether the signature declares this symbol as fully `pub`
+    pub fn is_public(&self) -> bool {
+        self.signature.trim_start().starts_with("pub ")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variant {
+    pub name: String,
+    #[serde(default)]
+    pub fields: Vec<VariantField>,
+    #[serde(default)]
+    pub doc: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantField {
+    /// Field name; empty for tuple variants
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+impl std::fmt::Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+
+        if self.fields.iter().all(|field| field.name.is_empty()) {
+            let types: Vec<_> = self.fields.iter().map(|f| f.field_type.as_str()).collect();
+            write!(f, "({})", types.join(", "))
+        } else {
+            let fields: Vec<_> = self
+                .fields
+                .iter()
+                .map(|f| format!("{}: {}", f.name, f.field_type))
+                .collect();
+            write!(f, " {{ {} }}", fields.join(", "))
+        }
+    }
+}
+
+/// Generic parameters, lifetimes, and where-clauses of a symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Generics {
+    #[serde(default)]
+    pub lifetimes: Vec<String>,
+    #[serde(default)]
+    pub params: Vec<GenericParam>,
+    #[serde(default)]
+    pub where_clauses: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenericParam {
+    pub name: String,
+    #[serde(default)]
+    pub bounds: Vec<String>,
+}
+
+impl std::fmt::Display for Generics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut params: Vec<String> = self.lifetimes.clone();
+        for param in &self.params {
+            if param.bounds.is_empty() {
+                params.push(param.name.clone());
+            } else {
+                params.push(format!("{}: {}", param.name, param.bounds.join(" + ")));
+            }
+        }
+
+        write!(f, "<{}>", params.join(", "))?;
+        if !self.where_clauses.is_empty() {
+            write!(f, " where {}", self.where_clauses.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Documentation {
     pub symbol: String,
     pub summary: String,
@@ -44,6 +230,9 @@ pub struct Documentation {
     pub returns: String,
     pub example: String,
     pub notes: Vec<String>,
+    /// Other names people search for, e.g. "Dictionary" for a hash map type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,5 +240,30 @@ pub struct Parameter {
     pub name: String,
     #[serde(rename = "type")]
     pub param_type: String,
+    /// Per-parameter documentation; older docpacks may omit it
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A standalone usage example stored under `examples/<doc_id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Example {
+    #[serde(default)]
+    pub title: String,
+    pub code: String,
+    /// Where the example came from, e.g. "tests" or "generated"
+    #[serde(default)]
+    pub source: String,
+}
+
+/// A non-code file (config, schema, proto, ...) listed in `assets.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Asset {
+    pub path: String,
+    /// Kind of file, e.g. "config", "schema", or "proto"
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default)]
     pub description: String,
 }
@@ -0,0 +1,196 @@
+use crate::config;
+use crate::index::PackIndex;
+use crate::models::{Documentation, Symbol};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, DateTime, ZipArchive, ZipWriter};
+
+/// In-memory copy of a docpack archive that can be edited and written back out
+pub struct PackWriter {
+    entries: BTreeMap<String, Vec<u8>>,
+    /// When set, every entry but manifest.json is written AES-256 encrypted
+    key: Option<String>,
+}
+
+impl PackWriter {
+    /// Load every entry of an existing docpack, decrypting encrypted ones
+    /// with the configured key. Encrypted docpacks are written back encrypted.
+    pub fn from_docpack(path: &str) -> Result<Self> {
+        let file = File::open(path).context("Failed to open docpack file")?;
+        let mut archive = ZipArchive::new(file).context("Failed to read docpack as ZIP archive")?;
+        let key = config::docpack_key()?;
+
+        let mut entries = BTreeMap::new();
+        let mut encrypted = false;
+        for i in 0..archive.len() {
+            let mut entry = match &key {
+                Some(key) => archive.by_index_decrypt(i, key.as_bytes()),
+                None => archive.by_index(i),
+            }
+            .context(format!("Failed to read entry {} of {}", i, path))?;
+            if entry.is_dir() {
+                continue;
+            }
+            encrypted |= entry.encrypted();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            entries.insert(entry.name().to_string(), content);
+        }
+
+        Ok(PackWriter {
+            entries,
+            key: key.filter(|_| encrypted),
+        })
+    }
+
+    /// Encrypt entries with `key` when writing, or write them in the clear with `None`
+    pub fn set_key(&mut self, key: Option<String>) {
+        self.key = key;
+    }
+
+    /// Add or replace an entry with the JSON encoding of `value`. Object keys are
+    /// sorted (via `serde_json::Value`), so hash map fields serialize the same every time.
+    pub fn set_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        let content = serde_json::to_value(value)
+            .and_then(|value| serde_json::to_vec_pretty(&value))
+            .context(format!("Failed to serialize {}", name))?;
+        self.entries.insert(name.to_string(), content);
+        Ok(())
+    }
+
+    /// Remove every entry whose name matches, returning how many were removed
+    pub fn remove_matching(&mut self, matches: impl Fn(&str) -> bool) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|name, _| !matches(name));
+        before - self.entries.len()
+    }
+
+    /// Build `index.json` from the symbols and documentation entries
+    fn search_index(&self) -> Result<Vec<u8>> {
+        let symbols: Vec<Symbol> = match self.entries.get("symbols.json") {
+            Some(content) => {
+                serde_json::from_slice(content).context("Failed to parse symbols.json")?
+            }
+            None => Vec::new(),
+        };
+        let docs = self
+            .entries
+            .iter()
+            .filter(|(name, _)| name.starts_with("docs/"))
+            .map(|(name, content)| {
+                serde_json::from_slice::<Documentation>(content)
+                    .context(format!("Failed to parse {}", name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let index = PackIndex::build(&symbols, &docs);
+        serde_json::to_vec_pretty(&index).context("Failed to serialize index.json")
+    }
+
+    /// Write the archive to `dest`, replacing it atomically if it already exists.
+    /// Entries are written in name order with fixed timestamps and permissions, so the
+    /// same contents always produce the same bytes (except when encrypting, which salts
+    /// each entry).
+    pub fn write(&self, dest: &Path) -> Result<()> {
+        let tmp_path = dest.with_extension("docpack.tmp");
+
+        {
+            let file = File::create(&tmp_path)
+                .context(format!("Failed to create {}", tmp_path.display()))?;
+            let mut zip = ZipWriter::new(file);
+            let options = SimpleFileOptions::default()
+                .last_modified_time(DateTime::default())
+                .unix_permissions(0o644);
+
+            // Every write refreshes the search index so it matches the contents
+            let index = self.search_index()?;
+            let mut entries: BTreeMap<&str, &[u8]> = self
+                .entries
+                .iter()
+                .map(|(name, content)| (name.as_str(), content.as_slice()))
+                .collect();
+            entries.insert("index.json", &index);
+
+            for (name, content) in entries {
+                match &self.key {
+                    // The manifest stays readable so packs can be listed without the key
+                    Some(key) if name != "manifest.json" => {
+                        zip.start_file(name, options.with_aes_encryption(AesMode::Aes256, key))?
+                    }
+                    _ => zip.start_file(name, options)?,
+                }
+                zip.write_all(content)?;
+            }
+
+            zip.finish()?;
+        }
+
+        std::fs::rename(&tmp_path, dest).context(format!("Failed to write {}", dest.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    /// A writer with a small docpack's entries, added in the given order
+    fn writer(reversed: bool) -> PackWriter {
+        let mut writer = PackWriter {
+            entries: BTreeMap::new(),
+            key: None,
+        };
+        let symbols = json!([{
+            "id": "app::run",
+            "kind": "function",
+            "file": "src/app.rs",
+            "line": 1,
+            "signature": "pub fn run()",
+            "doc_id": "d1",
+        }]);
+        let doc = Documentation {
+            symbol: "app::run".to_string(),
+            summary: "Start the app".to_string(),
+            ..Default::default()
+        };
+        // Hash maps iterate in a different order in each instance
+        let stats: HashMap<String, usize> = (0..32).map(|i| (format!("k{}", i), i)).collect();
+
+        let mut names = ["manifest.json", "symbols.json", "docs/d1.json"];
+        if reversed {
+            names.reverse();
+        }
+        for name in names {
+            match name {
+                "manifest.json" => writer.set_json(name, &stats),
+                "symbols.json" => writer.set_json(name, &symbols),
+                _ => writer.set_json(name, &doc),
+            }
+            .unwrap();
+        }
+        writer
+    }
+
+    #[test]
+    fn same_input_packs_to_same_bytes() {
+        let dir = std::env::temp_dir().join(format!("localdoc-packer-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (first, second) = (dir.join("first.docpack"), dir.join("second.docpack"));
+
+        writer(false).write(&first).unwrap();
+        writer(true).write(&second).unwrap();
+
+        let (first, second) = (
+            std::fs::read(&first).unwrap(),
+            std::fs::read(&second).unwrap(),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(first == second, "packs of the same input differ");
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{IsTerminal, Write};
+
+/// One entry the picker can select
+pub struct Candidate {
+    /// Text the query is matched against
+    pub label: String,
+    /// Shown dimmed after the label
+    pub detail: String,
+}
+
+/// Let the user narrow `candidates` by typing and choose one with Enter.
+/// Draws on stderr so the caller's stdout stays free for the result.
+/// Returns the index of the chosen candidate, or `None` if the user cancelled.
+pub fn pick(candidates: &[Candidate], initial_query: &str) -> Result<Option<usize>> {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        anyhow::bail!("pick needs an interactive terminal");
+    }
+
+    let mut out = std::io::stderr();
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen)?;
+    let result = run(&mut out, candidates, initial_query);
+    execute!(out, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn run(
+    out: &mut impl Write,
+    candidates: &[Candidate],
+    initial_query: &str,
+) -> Result<Option<usize>> {
+    let mut query = initial_query.to_string();
+    let mut selected = 0;
+    let mut scroll = 0;
+
+    loop {
+        let matches = ranked(candidates, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        let (width, height) = terminal::size()?;
+        let rows = (height as usize).saturating_sub(2).max(1);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows {
+            scroll = selected + 1 - rows;
+        }
+
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(0, 0),
+            Print("> "),
+            Print(&query)
+        )?;
+        queue!(
+            out,
+            MoveTo(0, 1),
+            SetAttribute(Attribute::Dim),
+            Print(format!("  {}/{}", matches.len(), candidates.len())),
+            SetAttribute(Attribute::Reset)
+        )?;
+        for (row, &index) in matches.iter().skip(scroll).take(rows).enumerate() {
+            let candidate = &candidates[index];
+            let is_selected = scroll + row == selected;
+            let line: String = format!(
+                "{} {}  {}",
+                if is_selected { ">" } else { " " },
+                candidate.label,
+                candidate.detail
+            )
+            .chars()
+            .take(width as usize)
+            .collect();
+            let label_len = candidate.label.chars().count() + 2;
+            let (label, detail) = line.split_at(
+                line.char_indices()
+                    .nth(label_len)
+                    .map_or(line.len(), |(i, _)| i),
+            );
+
+            queue!(out, MoveTo(0, row as u16 + 2))?;
+            if is_selected {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                out,
+                Print(label),
+                SetAttribute(Attribute::Dim),
+                Print(detail),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+        queue!(out, MoveTo(2 + query.chars().count() as u16, 0))?;
+        out.flush()?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => return Ok(matches.get(selected).copied()),
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c' | 'd' | 'g') if ctrl => return Ok(None),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p' | 'k') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected += 1,
+            KeyCode::Char('n' | 'j') if ctrl => selected += 1,
+            KeyCode::PageUp => selected = selected.saturating_sub(rows),
+            KeyCode::PageDown => selected += rows,
+            KeyCode::Char('u') if ctrl => {
+                query.clear();
+                selected = 0;
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Indices of the candidates matching `query`, best first; ties keep their order
+fn ranked(candidates: &[Candidate], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| fuzzy_score(query, &c.label).map(|score| (score, i)))
+        .collect();
+    scored.sort_by_key(|&(score, i)| (std::cmp::Reverse(score), i));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Score `text` for a query whose characters appear in it in order, ignoring
+/// case and spaces. Matches at word starts and runs of adjacent characters
+/// score higher; skipped characters and long text score lower.
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase().next().unwrap_or(q);
+        let found = (pos..text.len()).find(|&i| text[i].to_lowercase().next() == Some(q))?;
+
+        let prev = found.checked_sub(1).map(|p| text[p]);
+        let word_start = prev.is_none_or(|p| {
+            !p.is_alphanumeric() || (p.is_lowercase() && text[found].is_uppercase())
+        });
+        score += 16;
+        if word_start {
+            score += 8;
+        }
+        match last_match {
+            Some(last) if last + 1 == found => score += 4,
+            Some(last) => score -= (found - last - 1).min(8) as i64,
+            None => score -= found.min(8) as i64,
+        }
+
+        last_match = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score - text.len() as i64 / 8)
+}
@@ -0,0 +1,404 @@
+use crate::models::{Documentation, Symbol};
+use anyhow::Result;
+
+/// A parsed search query, e.g. `name:parse AND kind:function NOT module:tests`.
+///
+/// Words are combined with `AND` (the default between words), `OR`, and `NOT`,
+/// and can be grouped with parentheses. `"quoted phrases"` match as one term.
+/// A word prefixed with a field name only matches that field. Input that isn't
+/// valid query syntax, such as `parse_node(`, is searched for as typed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// Matches anywhere the keyword search looks
+    Term(String),
+    Field(Field, String),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+/// Fields a query word can be restricted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// The symbol ID
+    Name,
+    /// The symbol kind, matched exactly (e.g. `kind:function`)
+    Kind,
+    /// The source file, or the module path in the symbol ID
+    Module,
+    Signature,
+    /// Summary and description
+    Doc,
+    Tag,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Field> {
+        match name.to_lowercase().as_str() {
+            "name" | "id" => Some(Field::Name),
+            "kind" => Some(Field::Kind),
+            "module" | "file" => Some(Field::Module),
+            "sig" | "signature" => Some(Field::Signature),
+            "doc" => Some(Field::Doc),
+            "tag" => Some(Field::Tag),
+            _ => None,
+        }
+    }
+
+    fn matches(self, symbol: &Symbol, doc: &Documentation, value: &str) -> bool {
+        let contains = |text: &str| text.to_lowercase().contains(value);
+        match self {
+            Field::Name => contains(&symbol.id),
+            Field::Kind => symbol.kind.eq_ignore_ascii_case(value),
+            Field::Module => {
+                let module = symbol.id.rsplit_once("::").map_or("", |(module, _)| module);
+                contains(&symbol.file) || contains(module)
+            }
+            Field::Signature => contains(&symbol.signature),
+            Field::Doc => contains(&doc.summary) || contains(&doc.description),
+            Field::Tag => symbol.tags.iter().any(|tag| contains(tag)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query> {
+        if input.trim().is_empty() {
+            anyhow::bail!("Empty search query");
+        }
+        Ok(Query::parse_syntax(input).unwrap_or_else(|_| Query::Term(input.trim().to_lowercase())))
+    }
+
+    /// Why `parse` searches for the input as typed rather than as a query, if it does
+    pub fn syntax_error(input: &str) -> Option<String> {
+        if input.trim().is_empty() {
+            return None;
+        }
+        Query::parse_syntax(input).err().map(|e| e.to_string())
+    }
+
+    fn parse_syntax(input: &str) -> Result<Query> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let query = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            anyhow::bail!("Unexpected {} in search query", describe(token));
+        }
+        Ok(query)
+    }
+
+    /// Whether a symbol matches. `matches_term` decides plain terms, so callers
+    /// control how loosely they match (stemming, aliases).
+    pub fn matches(
+        &self,
+        symbol: &Symbol,
+        doc: &Documentation,
+        matches_term: &dyn Fn(&str) -> bool,
+    ) -> bool {
+        match self {
+            Query::Term(term) => matches_term(term),
+            Query::Field(field, value) => field.matches(symbol, doc, value),
+            Query::And(a, b) => {
+                a.matches(symbol, doc, matches_term) && b.matches(symbol, doc, matches_term)
+            }
+            Query::Or(a, b) => {
+                a.matches(symbol, doc, matches_term) || b.matches(symbol, doc, matches_term)
+            }
+            Query::Not(a) => !a.matches(symbol, doc, matches_term),
+        }
+    }
+
+    /// The query's only word when it is a single plain term
+    pub fn as_term(&self) -> Option<&str> {
+        match self {
+            Query::Term(term) => Some(term),
+            _ => None,
+        }
+    }
+
+    /// Terms every match must contain somewhere in its searchable text: plain
+    /// terms and name, signature, and doc fields outside `OR` and `NOT`
+    pub fn required_terms(&self) -> Vec<&str> {
+        match self {
+            Query::Term(term) => vec![term],
+            Query::Field(Field::Name | Field::Signature | Field::Doc, value) => vec![value],
+            Query::And(a, b) => {
+                let mut terms = a.required_terms();
+                terms.extend(b.required_terms());
+                terms
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Terms that count towards a match's score: plain terms and name, signature,
+    /// and doc fields anywhere outside `NOT`
+    pub fn scored_terms(&self) -> Vec<&str> {
+        match self {
+            Query::Term(term) => vec![term],
+            Query::Field(Field::Name | Field::Signature | Field::Doc, value) => vec![value],
+            Query::And(a, b) | Query::Or(a, b) => {
+                let mut terms = a.scored_terms();
+                terms.extend(b.scored_terms());
+                terms
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => format!("'{}'", word),
+        Token::And => "AND".to_string(),
+        Token::Or => "OR".to_string(),
+        Token::Not => "NOT".to_string(),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                // A word runs to whitespace or a parenthesis; quotes may span both
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if c == '"' {
+                        quoted = !quoted;
+                    } else if !quoted && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    } else {
+                        word.push(c);
+                    }
+                    chars.next();
+                }
+                if quoted {
+                    anyhow::bail!("Unclosed quote in search query");
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent over `or := and (OR and)*`, `and := not (AND? not)*`,
+/// `not := NOT not | atom`, `atom := ( or ) | word`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> Result<Query> {
+        let mut query = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query> {
+        let mut query = self.not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.pos += 1,
+                // Words side by side are ANDed
+                Some(Token::Word(_) | Token::Not | Token::Open) => {}
+                _ => break,
+            }
+            query = Query::And(Box::new(query), Box::new(self.not()?));
+        }
+        Ok(query)
+    }
+
+    fn not(&mut self) -> Result<Query> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Query::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Query> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Open) => {
+                if self.peek().is_none() {
+                    anyhow::bail!("Unclosed `(` in search query");
+                }
+                let query = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    anyhow::bail!("Unclosed `(` in search query");
+                }
+                self.pos += 1;
+                Ok(query)
+            }
+            Some(Token::Word(word)) => {
+                let word = word.to_lowercase();
+                Ok(match word.split_once(':') {
+                    Some((name, value)) if !value.is_empty() => match Field::parse(name) {
+                        Some(field) => Query::Field(field, value.to_string()),
+                        // Paths like "std::fmt" are plain terms
+                        None => Query::Term(word),
+                    },
+                    _ => Query::Term(word),
+                })
+            }
+            Some(token) => anyhow::bail!("Unexpected {} in search query", describe(&token)),
+            None => anyhow::bail!("Search query ends with an operator"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(word: &str) -> Box<Query> {
+        Box::new(Query::Term(word.to_string()))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let cases = [
+            (
+                "a b OR c",
+                Query::Or(Box::new(Query::And(term("a"), term("b"))), term("c")),
+            ),
+            (
+                "a OR b AND c",
+                Query::Or(term("a"), Box::new(Query::And(term("b"), term("c")))),
+            ),
+            (
+                "(a OR b) c",
+                Query::And(Box::new(Query::Or(term("a"), term("b"))), term("c")),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(Query::parse(input).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn not_applies_to_the_next_word_or_group() {
+        let cases = [
+            ("NOT a", Query::Not(term("a"))),
+            (
+                "a NOT b",
+                Query::And(term("a"), Box::new(Query::Not(term("b")))),
+            ),
+            (
+                "NOT (a OR b)",
+                Query::Not(Box::new(Query::Or(term("a"), term("b")))),
+            ),
+            ("NOT NOT a", Query::Not(Box::new(Query::Not(term("a"))))),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(Query::parse(input).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn fields_restrict_words() {
+        let cases = [
+            ("name:Parse", Query::Field(Field::Name, "parse".to_string())),
+            (
+                "kind:function",
+                Query::Field(Field::Kind, "function".to_string()),
+            ),
+            (
+                "file:src/a.rs",
+                Query::Field(Field::Module, "src/a.rs".to_string()),
+            ),
+            (
+                "sig:\"&str\"",
+                Query::Field(Field::Signature, "&str".to_string()),
+            ),
+            ("std::fmt", Query::Term("std::fmt".to_string())),
+            ("name:", Query::Term("name:".to_string())),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(Query::parse(input).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn quotes_keep_phrases_and_operators_together() {
+        assert_eq!(
+            Query::parse("\"a OR b\"").unwrap(),
+            Query::Term("a or b".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_syntax_is_searched_as_typed() {
+        let cases = [
+            ("parse_node(", "Unclosed `(` in search query"),
+            ("(a OR b", "Unclosed `(` in search query"),
+            ("a OR", "Search query ends with an operator"),
+            ("a)", "Unexpected ')' in search query"),
+            ("\"open quote", "Unclosed quote in search query"),
+        ];
+        for (input, error) in cases {
+            assert_eq!(
+                Query::parse(input).unwrap(),
+                Query::Term(input.to_lowercase()),
+                "{}",
+                input
+            );
+            assert_eq!(
+                Query::syntax_error(input).as_deref(),
+                Some(error),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(Query::syntax_error("a AND b"), None);
+    }
+
+    #[test]
+    fn empty_queries_are_rejected() {
+        assert!(Query::parse("").is_err());
+        assert!(Query::parse("   ").is_err());
+    }
+}
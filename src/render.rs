@@ -0,0 +1,83 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Output format for command results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored, human-readable text
+    #[default]
+    Text,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML document
+    Yaml,
+    /// TOML document
+    Toml,
+}
+
+impl OutputFormat {
+    pub fn is_text(self) -> bool {
+        self == OutputFormat::Text
+    }
+
+    /// Render a report to stdout using the renderer for this format
+    pub fn render<R: Report>(self, report: &R) -> Result<()> {
+        match self {
+            OutputFormat::Text => TextRenderer.render(report),
+            OutputFormat::Json => JsonRenderer.render(report),
+            OutputFormat::Yaml => YamlRenderer.render(report),
+            OutputFormat::Toml => TomlRenderer.render(report),
+        }
+    }
+}
+
+/// A structured command result.
+///
+/// Structured formats are derived from the `Serialize` impl, while the text
+/// format is provided by `render_text`.
+pub trait Report: Serialize {
+    /// Print the human-readable form of this report
+    fn render_text(&self);
+}
+
+/// Emits a report in a specific output format
+pub trait Renderer {
+    fn render<R: Report>(&self, report: &R) -> Result<()>;
+}
+
+pub struct TextRenderer;
+
+impl Renderer for TextRenderer {
+    fn render<R: Report>(&self, report: &R) -> Result<()> {
+        report.render_text();
+        Ok(())
+    }
+}
+
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn render<R: Report>(&self, report: &R) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(report)?);
+        Ok(())
+    }
+}
+
+pub struct YamlRenderer;
+
+impl Renderer for YamlRenderer {
+    fn render<R: Report>(&self, report: &R) -> Result<()> {
+        print!("{}", serde_yaml::to_string(report)?);
+        Ok(())
+    }
+}
+
+pub struct TomlRenderer;
+
+impl Renderer for TomlRenderer {
+    fn render<R: Report>(&self, report: &R) -> Result<()> {
+        print!("{}", toml::to_string_pretty(report)?);
+        Ok(())
+    }
+}
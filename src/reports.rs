@@ -0,0 +1,2365 @@
+use crate::display;
+use crate::docpack::MatchContext;
+use crate::metrics::Metrics;
+use crate::models::{
+    Asset, Dependency, Documentation, Example, Generics, LastModified, Manifest, Parameter, Symbol,
+};
+use crate::render::Report;
+use crate::scan::{Finding, Redaction};
+use crate::trace::ProfileStats;
+use colored::*;
+use serde::Serialize;
+
+/// A symbol together with its documentation
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolDoc {
+    pub symbol: Symbol,
+    pub documentation: Documentation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<Metrics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<ProfileStats>,
+    /// Direct callers and callees, with `--with-callers`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calls: Option<CallContext>,
+}
+
+/// The symbols a symbol calls and is called by, read from source snippets
+#[derive(Debug, Clone, Serialize)]
+pub struct CallContext {
+    pub callers: Vec<CallNeighbor>,
+    pub callees: Vec<CallNeighbor>,
+}
+
+/// A caller or callee with its documented summary
+#[derive(Debug, Clone, Serialize)]
+pub struct CallNeighbor {
+    pub symbol: String,
+    pub kind: String,
+    pub file: String,
+    pub line: usize,
+    pub summary: String,
+    /// Lines of the calls, in the calling symbol's file
+    pub call_lines: Vec<usize>,
+    /// How many calls there are; several can share a line
+    pub call_count: usize,
+}
+
+impl CallContext {
+    fn render_text(&self) {
+        for (heading, verb, neighbors) in [
+            ("Callers:", "calls it", &self.callers),
+            ("Callees:", "called", &self.callees),
+        ] {
+            println!("{}", heading.bold().green());
+            if neighbors.is_empty() {
+                println!("  {}", "None found".dimmed());
+            }
+            for neighbor in neighbors {
+                let lines: Vec<_> = neighbor.call_lines.iter().map(|l| l.to_string()).collect();
+                let plural = if lines.len() == 1 { "line" } else { "lines" };
+                let times = match neighbor.call_count {
+                    1 => String::new(),
+                    count => format!(" {} times", count),
+                };
+                println!(
+                    "  {} {} {} {}",
+                    format!("[{}]", neighbor.kind).dimmed(),
+                    display::name(&neighbor.symbol).cyan(),
+                    format!("({}:{})", neighbor.file, neighbor.line).dimmed(),
+                    format!("{}{} on {} {}", verb, times, plural, lines.join(", ")).dimmed()
+                );
+                if !neighbor.summary.is_empty() {
+                    println!("    {}", neighbor.summary);
+                }
+            }
+            println!();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InspectReport {
+    #[serde(flatten)]
+    pub manifest: Manifest,
+    pub test_coverage: TestCoverage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<SizeStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintainability: Option<MaintainabilityStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryRange>,
+    /// Issue and PR references recorded across all symbols
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<String>,
+}
+
+/// Span of last-modified dates recorded by `localdoc enrich`
+#[derive(Debug, Serialize)]
+pub struct HistoryRange {
+    pub dated_symbols: usize,
+    pub oldest: String,
+    pub newest: String,
+}
+
+/// Maintainability over symbols with metrics, with the hardest to maintain listed first
+#[derive(Debug, Serialize)]
+pub struct MaintainabilityStats {
+    pub measured_symbols: usize,
+    pub average_index: f64,
+    pub lowest: Vec<SymbolMetrics>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolMetrics {
+    pub id: String,
+    pub metrics: Metrics,
+}
+
+/// Lines-of-code statistics over symbols with a known span
+#[derive(Debug, Serialize)]
+pub struct SizeStats {
+    pub measured_symbols: usize,
+    pub total_loc: usize,
+    pub max_loc: usize,
+}
+
+/// How many non-test symbols have at least one linked test
+#[derive(Debug, Serialize)]
+pub struct TestCoverage {
+    pub tested: usize,
+    pub total: usize,
+    /// The tests linked to each tested symbol, in docpack order
+    pub linked: Vec<LinkedTests>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkedTests {
+    pub symbol: String,
+    pub tests: Vec<String>,
+}
+
+impl Report for InspectReport {
+    fn render_text(&self) {
+        let manifest = &self.manifest;
+
+        println!("{}", "Docpack Metadata".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        println!("{}: {}", "Format Version".bold(), manifest.docpack_format);
+        println!();
+
+        println!("{}", "Project Information:".bold().green());
+        println!("  {}: {}", "Name".bold(), manifest.project.name);
+        println!("  {}: {}", "Version".bold(), manifest.project.version);
+        if !manifest.project.repo.is_empty() {
+            println!("  {}: {}", "Repository".bold(), manifest.project.repo);
+        }
+        if !manifest.project.commit.is_empty() {
+            println!("  {}: {}", "Commit".bold(), manifest.project.commit);
+        }
+        if let Some(license) = &manifest.project.license {
+            println!("  {}: {}", "License".bold(), license);
+        }
+        if !manifest.project.dependencies.is_empty() {
+            println!(
+                "  {}: {}",
+                "Dependencies".bold(),
+                manifest.project.dependencies.len()
+            );
+            for dep in &manifest.project.dependencies {
+                if dep.version.is_empty() {
+                    println!("    {}", dep.name);
+                } else {
+                    println!("    {} {}", dep.name, dep.version.dimmed());
+                }
+            }
+        }
+        println!();
+
+        println!("{}: {}", "Generated At".bold(), manifest.generated_at);
+        if manifest.encrypted {
+            println!("{}: {}", "Encrypted".bold(), "yes".yellow());
+        }
+        if !manifest.stripped.is_empty() {
+            println!(
+                "{}: {} {}",
+                "Stripped".bold(),
+                manifest.stripped.join(", ").yellow(),
+                "(index-only pack made by prune-docs)".dimmed()
+            );
+        }
+        println!();
+
+        println!("{}", "Language Summary:".bold().yellow());
+        for (lang, count) in &manifest.language_summary {
+            println!("  {}: {}", lang, count);
+        }
+        println!();
+
+        println!("{}", "Statistics:".bold().magenta());
+        println!(
+            "  {}: {}",
+            "Symbols Extracted".bold(),
+            manifest.stats.symbols_extracted
+        );
+        println!(
+            "  {}: {}",
+            "Docs Generated".bold(),
+            manifest.stats.docs_generated
+        );
+        println!(
+            "  {}: {}/{}",
+            "Symbols With Tests".bold(),
+            self.test_coverage.tested,
+            self.test_coverage.total
+        );
+        if let Some(size) = &self.size {
+            println!(
+                "  {}: {} {}",
+                "Lines of Code".bold(),
+                size.total_loc,
+                format!(
+                    "(avg {} over {} symbols, max {})",
+                    size.total_loc / size.measured_symbols,
+                    size.measured_symbols,
+                    size.max_loc
+                )
+                .dimmed()
+            );
+        }
+
+        if !self.test_coverage.linked.is_empty() {
+            println!();
+            println!("{}", "Tests:".bold().magenta());
+            for linked in &self.test_coverage.linked {
+                let tests: Vec<_> = linked.tests.iter().map(|t| display::name(t)).collect();
+                println!(
+                    "  {} {}",
+                    display::name(&linked.symbol).green(),
+                    format!("tested by {}", tests.join(", ")).dimmed()
+                );
+            }
+        }
+
+        if let Some(maintainability) = &self.maintainability {
+            println!();
+            println!("{}", "Maintainability:".bold().magenta());
+            println!(
+                "  {}: {:.1} {}",
+                "Average Index".bold(),
+                maintainability.average_index,
+                format!("(over {} symbols)", maintainability.measured_symbols).dimmed()
+            );
+            println!("  {}", "Hardest to maintain:".bold());
+            for entry in &maintainability.lowest {
+                println!(
+                    "    {} {}",
+                    display::name(&entry.id).green(),
+                    format_metrics(&entry.metrics).dimmed()
+                );
+            }
+        }
+
+        if let Some(history) = &self.history {
+            println!();
+            println!(
+                "{}: {} to {} {}",
+                "Last Modified".bold(),
+                history.oldest,
+                history.newest,
+                format!("(over {} symbols)", history.dated_symbols).dimmed()
+            );
+        }
+
+        if !self.related.is_empty() {
+            println!();
+            println!("{}: {}", "Related".bold(), self.related.join(", "));
+        }
+    }
+}
+
+/// One-line summary of a symbol's metrics
+fn format_metrics(metrics: &Metrics) -> String {
+    format!(
+        "MI {:.1}, Halstead volume {:.0}, complexity {}, nesting {}",
+        metrics.maintainability_index,
+        metrics.halstead_volume,
+        metrics.cyclomatic_complexity,
+        metrics.nesting_depth
+    )
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolListReport {
+    pub symbols: Vec<Symbol>,
+}
+
+impl Report for SymbolListReport {
+    fn render_text(&self) {
+        println!("{}", "All Symbols".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for symbol in &self.symbols {
+            let location = match symbol.loc() {
+                Some(loc) => format!("({}:{}, {} lines)", symbol.file, symbol.line, loc),
+                None => format!("({}:{})", symbol.file, symbol.line),
+            };
+            println!(
+                "{} {} {}",
+                format!("[{}]", symbol.kind).yellow(),
+                display::name(&symbol.id).green(),
+                location.dimmed()
+            );
+        }
+
+        println!();
+        println!("Total: {} symbols", self.symbols.len());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolDocsReport {
+    pub matches: Vec<SymbolDoc>,
+}
+
+impl Report for SymbolDocsReport {
+    fn render_text(&self) {
+        for SymbolDoc {
+            symbol,
+            documentation: doc,
+            metrics,
+            profile,
+            calls,
+        } in &self.matches
+        {
+            println!("{}", "Symbol Information".bold().cyan());
+            display::print_rule('=');
+            println!();
+
+            let name = display::name(&symbol.id);
+            if name == symbol.id {
+                println!("{}: {}", "ID".bold(), name.green());
+            } else {
+                println!(
+                    "{}: {} {}",
+                    "ID".bold(),
+                    name.green(),
+                    format!("(raw: {})", symbol.id).dimmed()
+                );
+            }
+            println!("{}: {}", "Kind".bold(), symbol.kind.yellow());
+            println!("{}: {}:{}", "File".bold(), symbol.file, symbol.line);
+            println!("{}: {}", "Signature".bold(), symbol.signature);
+            if !symbol.attributes.is_empty() {
+                println!("{}: {}", "Attributes".bold(), symbol.attributes.join(" "));
+            }
+            if let Some(cfg) = &symbol.cfg {
+                println!("{}: {}", "Cfg".bold(), cfg);
+            }
+            let tags: Vec<_> = symbol
+                .tags
+                .iter()
+                .filter(|tag| !tag.starts_with("ref:"))
+                .map(String::as_str)
+                .collect();
+            if !tags.is_empty() {
+                println!("{}: {}", "Tags".bold(), tags.join(", ").yellow());
+            }
+            if let Some(modified) = &symbol.last_modified {
+                println!(
+                    "{}: {} by {} {}",
+                    "Last Modified".bold(),
+                    modified.date,
+                    modified.author,
+                    format!("({})", &modified.commit[..modified.commit.len().min(7)]).dimmed()
+                );
+            }
+            let related = symbol.related_refs();
+            if !related.is_empty() {
+                println!("{}: {}", "Related".bold(), related.join(", "));
+            }
+            if let Some(generics) = &symbol.generics {
+                println!("{}: {}", "Generics".bold(), generics);
+            }
+            if let Some(metrics) = metrics {
+                println!("{}: {}", "Metrics".bold(), format_metrics(metrics));
+            }
+            if let Some(profile) = profile {
+                println!(
+                    "{}: {} self / {} total samples ({:.1}%)",
+                    "Profile".bold(),
+                    profile.self_samples,
+                    profile.total_samples,
+                    profile.total_percent
+                );
+            }
+            println!();
+
+            println!("{}", "Documentation".bold().cyan());
+            display::print_rule('-');
+            println!();
+            println!("{}: {}", "Summary".bold(), doc.summary);
+            println!();
+            println!("{}", "Description:".bold());
+            println!("{}", doc.description);
+            println!();
+
+            if !symbol.variants.is_empty() {
+                println!("{}", "Variants:".bold().green());
+                for variant in &symbol.variants {
+                    if variant.doc.is_empty() {
+                        println!("  {}", variant.to_string().bold());
+                    } else {
+                        println!("  {} - {}", variant.to_string().bold(), variant.doc);
+                    }
+                }
+                println!();
+            }
+
+            if !doc.parameters.is_empty() {
+                println!("{}", "Parameters:".bold().green());
+                for param in &doc.parameters {
+                    if param.description.is_empty() {
+                        println!(
+                            "  {} {}",
+                            param.name.bold(),
+                            format!("({})", param.param_type).dimmed()
+                        );
+                    } else {
+                        println!(
+                            "  {} {} - {}",
+                            param.name.bold(),
+                            format!("({})", param.param_type).dimmed(),
+                            param.description
+                        );
+                    }
+                }
+                println!();
+            }
+
+            println!("{}: {}", "Returns".bold(), doc.returns);
+            println!();
+
+            if !doc.example.is_empty() {
+                println!("{}", "Example:".bold().yellow());
+                println!("{}", doc.example);
+                println!();
+            }
+
+            if !doc.notes.is_empty() {
+                println!("{}", "Notes:".bold().magenta());
+                for note in &doc.notes {
+                    println!("  - {}", note);
+                }
+                println!();
+            }
+
+            if let Some(calls) = calls {
+                calls.render_text();
+            }
+        }
+    }
+}
+
+/// A search result with the text around the match
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub entry: SymbolDoc,
+    /// Documentation or source around the match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<MatchContext>,
+    /// The alias that matched, when the keyword itself didn't
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via_alias: Option<String>,
+    /// Relevance score, shown with `--debug-score`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u32>,
+}
+
+/// Recent searches of one docpack, most recent first
+#[derive(Debug, Serialize)]
+pub struct SearchHistoryReport {
+    pub docpack: String,
+    pub queries: Vec<String>,
+}
+
+impl Report for SearchHistoryReport {
+    fn render_text(&self) {
+        if self.queries.is_empty() {
+            println!("{}", "No searches recorded for this docpack.".yellow());
+            return;
+        }
+
+        println!("{}", "Search History".bold().cyan());
+        display::print_rule('=');
+        println!();
+        for (i, query) in self.queries.iter().enumerate() {
+            println!("  {} {}", format!("!{}", i + 1).dimmed(), query);
+        }
+        println!();
+        println!(
+            "{}",
+            "Re-run one with `search '!N'`, or the last with `search '!!'`.".dimmed()
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchReport {
+    pub keyword: String,
+    pub results: Vec<SearchHit>,
+}
+
+impl Report for SearchReport {
+    fn render_text(&self) {
+        println!(
+            "{}",
+            format!("Search Results for '{}'", self.keyword)
+                .bold()
+                .cyan()
+        );
+        display::print_rule('=');
+        println!();
+
+        for SearchHit {
+            entry:
+                SymbolDoc {
+                    symbol,
+                    documentation: doc,
+                    ..
+                },
+            context,
+            via_alias,
+            score,
+        } in &self.results
+        {
+            println!(
+                "{} {}",
+                format!("[{}]", symbol.kind).yellow(),
+                display::name(&symbol.id).green()
+            );
+            println!(
+                "  {}: {}",
+                "Location".dimmed(),
+                format!("{}:{}", symbol.file, symbol.line).dimmed()
+            );
+            println!("  {}: {}", "Summary".bold(), doc.summary);
+            if let Some(score) = score {
+                println!("  {}: {}", "Score".bold(), score);
+            }
+            if let Some(alias) = via_alias {
+                println!("  {}: {}", "Matched via alias".bold(), alias);
+            }
+            if let Some(context) = context {
+                println!(
+                    "  {}: {}{}{}",
+                    "Match".bold(),
+                    context.before,
+                    context.matched.yellow().bold(),
+                    context.after
+                );
+            }
+            println!();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileEntry {
+    pub file: String,
+    pub symbols: usize,
+    /// TODO/FIXME/HACK markers found in the file's snippets
+    pub todos: usize,
+    /// Fraction of snippet lines carrying comments, when snippets are available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_density: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilesReport {
+    pub files: Vec<FileEntry>,
+}
+
+impl Report for FilesReport {
+    fn render_text(&self) {
+        println!("{}", "Source Files".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for entry in &self.files {
+            let mut details = vec![format!("{} symbols", entry.symbols)];
+            if entry.todos > 0 {
+                details.push(format!("{} TODOs", entry.todos));
+            }
+            if let Some(density) = entry.comment_density {
+                details.push(format!("{:.0}% comments", density * 100.0));
+            }
+            println!(
+                "{} {}",
+                entry.file.green(),
+                format!("({})", details.join(", ")).dimmed()
+            );
+        }
+
+        println!();
+        println!("Total: {} files", self.files.len());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileSymbolsReport {
+    pub file: String,
+    pub symbols: Vec<Symbol>,
+}
+
+impl Report for FileSymbolsReport {
+    fn render_text(&self) {
+        println!("{}", format!("Symbols in '{}'", self.file).bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for symbol in &self.symbols {
+            println!(
+                "{} {} {}",
+                format!("[{}]", symbol.kind).yellow(),
+                display::name(&symbol.id).green(),
+                format!("(line {})", symbol.line).dimmed()
+            );
+            println!("  {}", symbol.signature.dimmed());
+            println!();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KindReport {
+    pub kind: String,
+    pub symbols: Vec<Symbol>,
+}
+
+impl Report for KindReport {
+    fn render_text(&self) {
+        println!(
+            "{}",
+            format!("Symbols of kind '{}'", self.kind).bold().cyan()
+        );
+        display::print_rule('=');
+        println!();
+
+        for symbol in &self.symbols {
+            println!(
+                "{} {} {}",
+                format!("[{}]", symbol.kind).yellow(),
+                display::name(&symbol.id).green(),
+                format!("({}:{})", symbol.file, symbol.line).dimmed()
+            );
+        }
+
+        println!();
+        println!("Total: {} symbols", self.symbols.len());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExampleEntry {
+    pub symbol: String,
+    pub example: String,
+    pub examples: Vec<Example>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExamplesReport {
+    pub examples: Vec<ExampleEntry>,
+}
+
+impl Report for ExamplesReport {
+    fn render_text(&self) {
+        for entry in &self.examples {
+            println!(
+                "{}",
+                format!("Examples for '{}'", display::name(&entry.symbol))
+                    .bold()
+                    .cyan()
+            );
+            display::print_rule('=');
+            println!();
+
+            if entry.example.is_empty() && entry.examples.is_empty() {
+                println!("{}", "No examples available for this symbol.".yellow());
+                println!();
+                continue;
+            }
+
+            if !entry.example.is_empty() {
+                println!("{}", entry.example);
+                println!();
+            }
+
+            for example in &entry.examples {
+                let title = if example.title.is_empty() {
+                    "Example"
+                } else {
+                    example.title.as_str()
+                };
+                if example.source.is_empty() {
+                    println!("{}", title.bold().yellow());
+                } else {
+                    println!(
+                        "{} {}",
+                        title.bold().yellow(),
+                        format!("({})", example.source).dimmed()
+                    );
+                }
+                println!("{}", example.code);
+                println!();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestsForEntry {
+    pub symbol: String,
+    pub tests: Vec<Symbol>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestsForReport {
+    pub entries: Vec<TestsForEntry>,
+}
+
+impl Report for TestsForReport {
+    fn render_text(&self) {
+        for entry in &self.entries {
+            println!(
+                "{}",
+                format!("Tests for '{}'", display::name(&entry.symbol))
+                    .bold()
+                    .cyan()
+            );
+            display::print_rule('=');
+            println!();
+
+            if entry.tests.is_empty() {
+                println!("{}", "No tests found for this symbol.".yellow());
+            } else {
+                for test in &entry.tests {
+                    println!(
+                        "{} {}",
+                        display::name(&test.id).green(),
+                        format!("({}:{})", test.file, test.line).dimmed()
+                    );
+                }
+            }
+            println!();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DepsEntry {
+    pub symbol: String,
+    pub parameters: Vec<Parameter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returns: Option<String>,
+    pub related: Vec<Symbol>,
+    /// Direct callers and callees, with `--with-callers`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calls: Option<CallContext>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DepsReport {
+    pub dependencies: Vec<DepsEntry>,
+}
+
+impl Report for DepsReport {
+    fn render_text(&self) {
+        for entry in &self.dependencies {
+            println!(
+                "{}",
+                format!("Dependencies for '{}'", display::name(&entry.symbol))
+                    .bold()
+                    .cyan()
+            );
+            display::print_rule('=');
+            println!();
+
+            // Type references from signature and parameters
+            println!("{}", "Type References:".bold().green());
+
+            if !entry.parameters.is_empty() {
+                println!("  {}", "Parameters:".bold());
+                for param in &entry.parameters {
+                    println!(
+                        "    {} {} {}",
+                        param.name.dimmed(),
+                        display::arrow(),
+                        param.param_type.yellow()
+                    );
+                }
+            }
+
+            if let Some(returns) = &entry.returns {
+                println!("  {}", "Returns:".bold());
+                println!("    {} {}", display::arrow(), returns.yellow());
+            }
+
+            if !entry.related.is_empty() {
+                println!();
+                println!("{}", "Related Symbols (same file):".bold().green());
+                for s in entry.related.iter().take(10) {
+                    println!(
+                        "    {} {}",
+                        format!("[{}]", s.kind).dimmed(),
+                        display::name(&s.id).cyan()
+                    );
+                }
+                if entry.related.len() > 10 {
+                    println!("    ... and {} more", entry.related.len() - 10);
+                }
+            }
+
+            println!();
+            if let Some(calls) = &entry.calls {
+                calls.render_text();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstalledPackage {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbols: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackageListReport {
+    pub packages: Vec<InstalledPackage>,
+}
+
+impl Report for PackageListReport {
+    fn render_text(&self) {
+        if self.packages.is_empty() {
+            println!("{}", "No docpacks installed yet.".yellow());
+            println!();
+            println!(
+                "Install one with: {}",
+                "localdoc install <username:reponame>".cyan()
+            );
+            return;
+        }
+
+        println!("{}", "Installed Docpacks".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for package in &self.packages {
+            match (&package.version, package.symbols) {
+                (Some(version), Some(symbols)) => {
+                    println!(
+                        "{} {} {}",
+                        package.name.green().bold(),
+                        format!("v{}", version).dimmed(),
+                        format!("({} symbols)", symbols).dimmed()
+                    );
+                }
+                _ => {
+                    println!(
+                        "{} {}",
+                        package.name.green().bold(),
+                        "(unable to read metadata)".dimmed()
+                    );
+                }
+            }
+        }
+
+        println!();
+        println!("Total: {} docpack(s)", self.packages.len());
+        println!();
+        println!("{}", "Usage:".bold());
+        println!("  {} {}", "localdoc inspect".dimmed(), "<name>".cyan());
+        println!(
+            "  {} {} {}",
+            "localdoc query".dimmed(),
+            "<name>".cyan(),
+            "symbols".dimmed()
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommonsResult {
+    pub name: String,
+    pub score: f64,
+    pub description: String,
+    pub symbol_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommonsSearchReport {
+    pub query: String,
+    pub results: Vec<CommonsResult>,
+}
+
+impl Report for CommonsSearchReport {
+    fn render_text(&self) {
+        if self.results.is_empty() {
+            println!(
+                "{}",
+                format!("No docpacks found matching '{}'", self.query).yellow()
+            );
+            return;
+        }
+
+        println!("{}", "Search Results".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for result in &self.results {
+            // Convert score to percentage for display
+            let score_pct = (result.score * 100.0) as u32;
+
+            println!(
+                "{} {} {}",
+                result.name.green().bold(),
+                format!("({}%)", score_pct).yellow(),
+                format!("{} symbols", result.symbol_count).dimmed()
+            );
+
+            if !result.description.is_empty() {
+                // Truncate description if too long
+                let desc = if result.description.len() > 60 {
+                    format!("{}...", &result.description[..57])
+                } else {
+                    result.description.clone()
+                };
+                println!("  {}", desc.dimmed());
+            }
+            println!();
+        }
+
+        println!("Found {} result(s)", self.results.len());
+        println!();
+        println!("{}", "To install:".bold());
+        println!(
+            "  {} {}",
+            "localdoc install".dimmed(),
+            "<username:reponame>".cyan()
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstallReport {
+    pub package: String,
+    pub location: String,
+}
+
+impl Report for InstallReport {
+    fn render_text(&self) {
+        println!();
+        println!("{}", "Installation complete!".green().bold());
+        println!();
+        println!("{}: {}", "Package".bold(), self.package.green());
+        println!("{}: {}", "Location".bold(), self.location.dimmed());
+        println!();
+        println!("{}", "Usage:".bold());
+        println!(
+            "  {} {} {}",
+            "localdoc inspect".dimmed(),
+            self.package.cyan(),
+            "# View metadata".dimmed()
+        );
+        println!(
+            "  {} {} {}",
+            "localdoc query".dimmed(),
+            self.package.cyan(),
+            "symbols # List all symbols".dimmed()
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RemoveReport {
+    pub package: String,
+}
+
+impl Report for RemoveReport {
+    fn render_text(&self) {
+        println!("{}", "Docpack removed!".green().bold());
+        println!();
+        println!("{}: {}", "Package".bold(), self.package.yellow());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdatedPackage {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateReport {
+    pub updated: Vec<UpdatedPackage>,
+    pub up_to_date: Vec<String>,
+    pub failed: Vec<String>,
+    pub not_found: Vec<String>,
+}
+
+impl Report for UpdateReport {
+    fn render_text(&self) {
+        println!();
+        if !self.updated.is_empty() {
+            println!(
+                "{}",
+                format!("Updated {} docpack(s)", self.updated.len())
+                    .green()
+                    .bold()
+            );
+        }
+        if !self.up_to_date.is_empty() {
+            println!(
+                "{}",
+                format!("{} docpack(s) already up to date", self.up_to_date.len()).dimmed()
+            );
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackageSummary {
+    pub name: String,
+    pub version: String,
+    pub symbols: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolRef {
+    pub id: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageDelta {
+    pub language: String,
+    pub a: u32,
+    pub b: u32,
+}
+
+/// A common symbol whose generic parameters differ between two docpacks
+#[derive(Debug, Serialize)]
+pub struct GenericsChange {
+    pub id: String,
+    pub a: Generics,
+    pub b: Generics,
+}
+
+/// A common symbol whose signature differs between two docpacks
+#[derive(Debug, Serialize)]
+pub struct SignatureChange {
+    pub id: String,
+    pub a: String,
+    pub b: String,
+}
+
+/// A common symbol whose cyclomatic complexity differs between two docpacks
+#[derive(Debug, Serialize)]
+pub struct ComplexityChange {
+    pub id: String,
+    pub a: u32,
+    pub b: u32,
+}
+
+/// A common symbol that is behind a different cfg gate in the second docpack
+#[derive(Debug, Serialize)]
+pub struct GatedSymbol {
+    pub id: String,
+    pub cfg: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareReport {
+    pub a: PackageSummary,
+    pub b: PackageSummary,
+    pub common: usize,
+    pub only_in_a: Vec<SymbolRef>,
+    pub only_in_b: Vec<SymbolRef>,
+    pub signature_changed: Vec<SignatureChange>,
+    pub generics_changed: Vec<GenericsChange>,
+    pub complexity_changed: Vec<ComplexityChange>,
+    pub newly_gated: Vec<GatedSymbol>,
+    pub languages: Vec<LanguageDelta>,
+}
+
+impl Report for CompareReport {
+    fn render_text(&self) {
+        println!("{}", "Docpack Comparison".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        println!("{}", "Package Information:".bold().green());
+        println!(
+            "  {} {} (v{})",
+            "A:".bold(),
+            self.a.name.cyan(),
+            self.a.version
+        );
+        println!(
+            "  {} {} (v{})",
+            "B:".bold(),
+            self.b.name.cyan(),
+            self.b.version
+        );
+        println!();
+
+        println!("{}", "Symbol Counts:".bold().green());
+        println!("  A: {} symbols", self.a.symbols);
+        println!("  B: {} symbols", self.b.symbols);
+        println!();
+
+        println!("{}", "Symbol Differences:".bold().green());
+        println!("  Common symbols: {}", self.common.to_string().cyan());
+        println!("  Only in A: {}", self.only_in_a.len().to_string().yellow());
+        println!("  Only in B: {}", self.only_in_b.len().to_string().yellow());
+        println!();
+
+        print_symbol_refs("Symbols only in A:", &self.only_in_a);
+        print_symbol_refs("Symbols only in B:", &self.only_in_b);
+
+        if !self.newly_gated.is_empty() {
+            println!("{}", "Now behind a cfg gate in B:".bold().yellow());
+            for gated in &self.newly_gated {
+                println!(
+                    "  {} {}",
+                    display::name(&gated.id).green(),
+                    format!("#[cfg({})]", gated.cfg).dimmed()
+                );
+            }
+            println!();
+        }
+
+        if !self.signature_changed.is_empty() {
+            println!("{}", "Signatures changed:".bold().yellow());
+            for change in &self.signature_changed {
+                println!("  {}", display::name(&change.id).green());
+                println!("    {} {}", "A:".dimmed(), change.a);
+                println!("    {} {}", "B:".dimmed(), change.b);
+            }
+            println!();
+        }
+
+        if !self.generics_changed.is_empty() {
+            println!("{}", "Generics changed:".bold().yellow());
+            for change in &self.generics_changed {
+                println!("  {}", display::name(&change.id).green());
+                println!("    {} {}", "A:".dimmed(), change.a);
+                println!("    {} {}", "B:".dimmed(), change.b);
+            }
+            println!();
+        }
+
+        if !self.complexity_changed.is_empty() {
+            println!("{}", "Complexity changed:".bold().yellow());
+            for change in &self.complexity_changed {
+                let delta = change.b as i64 - change.a as i64;
+                let delta = if delta > 0 {
+                    format!("+{}", delta).red()
+                } else {
+                    delta.to_string().green()
+                };
+                println!(
+                    "  {}: {} {} {} ({})",
+                    display::name(&change.id).green(),
+                    change.a,
+                    display::arrow(),
+                    change.b,
+                    delta
+                );
+            }
+            println!();
+        }
+
+        println!("{}", "Language Comparison:".bold().green());
+        for delta in &self.languages {
+            if delta.a != delta.b {
+                println!(
+                    "  {}: {} {} {}",
+                    delta.language,
+                    delta.a.to_string().dimmed(),
+                    display::arrow(),
+                    delta.b.to_string().cyan()
+                );
+            } else {
+                println!("  {}: {}", delta.language, delta.a);
+            }
+        }
+    }
+}
+
+/// Print a titled list of symbols, limited to the first 20
+fn print_symbol_refs(title: &str, symbols: &[SymbolRef]) {
+    if symbols.is_empty() {
+        return;
+    }
+
+    println!("{}", title.bold().yellow());
+    for (i, sym) in symbols.iter().enumerate() {
+        if i >= 20 {
+            println!("  ... and {} more", symbols.len() - 20);
+            break;
+        }
+        println!(
+            "  {} {}",
+            format!("[{}]", sym.kind).dimmed(),
+            display::name(&sym.id).green()
+        );
+    }
+    println!();
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecomputeMetricsReport {
+    pub docpack: String,
+    pub measured_symbols: usize,
+}
+
+impl Report for RecomputeMetricsReport {
+    fn render_text(&self) {
+        println!("{}", "Metrics recomputed!".green().bold());
+        println!();
+        println!("{}: {}", "Docpack".bold(), self.docpack);
+        println!("{}: {}", "Symbols Measured".bold(), self.measured_symbols);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReindexReport {
+    pub docpack: String,
+    pub indexed_symbols: usize,
+}
+
+impl Report for ReindexReport {
+    fn render_text(&self) {
+        println!("{}", "Docpack reindexed!".green().bold());
+        println!();
+        println!("{}: {}", "Docpack".bold(), self.docpack);
+        println!("{}: {}", "Symbols Indexed".bold(), self.indexed_symbols);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncryptionReport {
+    pub docpack: String,
+    pub encrypted: bool,
+}
+
+impl Report for EncryptionReport {
+    fn render_text(&self) {
+        if self.encrypted {
+            println!("{}", "Docpack encrypted!".green().bold());
+        } else {
+            println!("{}", "Docpack decrypted!".green().bold());
+        }
+        println!();
+        println!("{}: {}", "Docpack".bold(), self.docpack);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PruneReport {
+    pub docpack: String,
+    pub stripped: Vec<String>,
+    /// Archive entries deleted plus snippets cleared from symbols.json
+    pub removed_entries: usize,
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+impl Report for PruneReport {
+    fn render_text(&self) {
+        println!("{}", "Docpack pruned!".green().bold());
+        println!();
+        println!("{}: {}", "Docpack".bold(), self.docpack);
+        println!("{}: {}", "Stripped".bold(), self.stripped.join(", "));
+        println!("{}: {}", "Entries Removed".bold(), self.removed_entries);
+        println!(
+            "{}: {} -> {} bytes",
+            "Size".bold(),
+            self.size_before,
+            self.size_after
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodoEntry {
+    pub file: String,
+    pub line: usize,
+    pub symbol: String,
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodosReport {
+    pub todos: Vec<TodoEntry>,
+}
+
+impl Report for TodosReport {
+    fn render_text(&self) {
+        if self.todos.is_empty() {
+            println!("{}", "No TODO markers found.".yellow());
+            return;
+        }
+
+        println!("{}", "TODO Markers".bold().cyan());
+        display::print_rule('=');
+
+        let mut current_file = None;
+        for todo in &self.todos {
+            if current_file != Some(&todo.file) {
+                println!();
+                println!("{}", todo.file.bold());
+                current_file = Some(&todo.file);
+            }
+            println!(
+                "  {} {} {} {}",
+                format!("{}:", todo.line).dimmed(),
+                format!("[{}]", todo.kind).yellow(),
+                todo.text,
+                format!("({})", display::name(&todo.symbol)).dimmed()
+            );
+        }
+
+        println!();
+        println!("Total: {} marker(s)", self.todos.len());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanReport {
+    pub findings: Vec<Finding>,
+    pub tagged_symbols: usize,
+    /// Whether the tags were written back into the docpack
+    pub written: bool,
+}
+
+impl Report for ScanReport {
+    fn render_text(&self) {
+        if self.findings.is_empty() {
+            println!("{}", "No security-sensitive patterns found.".green());
+        } else {
+            println!("{}", "Security Scan".bold().cyan());
+            display::print_rule('=');
+            println!();
+
+            for finding in &self.findings {
+                println!(
+                    "{} {} {}",
+                    format!("[{}]", finding.tag).yellow(),
+                    display::name(&finding.symbol).green(),
+                    format!("({}:{})", finding.file, finding.line).dimmed()
+                );
+                println!("  {}", finding.matched.dimmed());
+            }
+
+            println!();
+            println!(
+                "Found {} finding(s) in {} symbol(s)",
+                self.findings.len(),
+                self.tagged_symbols
+            );
+        }
+
+        if self.written {
+            println!("{}", "Tags written to docpack.".dimmed());
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RedactReport {
+    pub redactions: Vec<Redaction>,
+    /// Whether the redacted snippets were written back into the docpack
+    pub written: bool,
+}
+
+impl Report for RedactReport {
+    fn render_text(&self) {
+        if self.redactions.is_empty() {
+            println!("{}", "No secrets found in snippets.".green());
+            return;
+        }
+
+        println!("{}", "Redactions".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for redaction in &self.redactions {
+            println!(
+                "{} {} {}",
+                format!("[{}]", redaction.kind).yellow(),
+                display::name(&redaction.symbol).green(),
+                format!("({}:{})", redaction.file, redaction.line).dimmed()
+            );
+        }
+
+        println!();
+        println!("Total: {} secret(s) redacted", self.redactions.len());
+        if self.written {
+            println!("{}", "Redacted snippets written to docpack.".dimmed());
+        } else {
+            println!("{}", "Dry run: docpack not modified.".dimmed());
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnsafeEntry {
+    pub symbol: String,
+    pub file: String,
+    pub line: usize,
+    /// Whether the function is itself part of the public API
+    pub public: bool,
+    /// Number of `unsafe` blocks, functions, or impls in the snippet
+    pub unsafe_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnsafeFileStats {
+    pub file: String,
+    pub functions: usize,
+    pub unsafe_functions: usize,
+    /// Share of the file's functions that use `unsafe`, 0-100
+    pub surface_percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnsafeReport {
+    pub functions: Vec<UnsafeEntry>,
+    pub files: Vec<UnsafeFileStats>,
+}
+
+impl Report for UnsafeReport {
+    fn render_text(&self) {
+        if self.functions.is_empty() {
+            println!("{}", "No unsafe code found in Rust snippets.".green());
+            return;
+        }
+
+        println!("{}", "Unsafe Functions".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for entry in &self.functions {
+            let visibility = if entry.public {
+                "[pub]".red()
+            } else {
+                "[private]".dimmed()
+            };
+            println!(
+                "{} {} {} {}",
+                visibility,
+                display::name(&entry.symbol).green(),
+                format!("({}:{})", entry.file, entry.line).dimmed(),
+                format!("{} unsafe", entry.unsafe_count).yellow()
+            );
+        }
+
+        println!();
+        println!("{}", "Unsafe Surface by File".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for stats in &self.files {
+            println!(
+                "{} {}",
+                format!("{:>5.1}%", stats.surface_percent).yellow(),
+                stats.file.green(),
+            );
+            println!(
+                "       {}",
+                format!("{}/{} functions", stats.unsafe_functions, stats.functions).dimmed()
+            );
+        }
+
+        println!();
+        println!("Total: {} unsafe function(s)", self.functions.len());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackageLicense {
+    pub name: String,
+    pub version: String,
+    pub repo: String,
+    pub license: Option<String>,
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LicensesReport {
+    pub packages: Vec<PackageLicense>,
+}
+
+impl Report for LicensesReport {
+    fn render_text(&self) {
+        if self.packages.is_empty() {
+            println!("{}", "No docpacks installed yet.".yellow());
+            return;
+        }
+
+        println!("{}", "Package Licenses".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for package in &self.packages {
+            let license = match &package.license {
+                Some(license) => license.green(),
+                None => "unknown".red(),
+            };
+            println!(
+                "{} {} {}",
+                package.name.bold(),
+                format!("v{}", package.version).dimmed(),
+                license
+            );
+            if !package.repo.is_empty() {
+                println!("  {}", package.repo.dimmed());
+            }
+            if !package.dependencies.is_empty() {
+                println!(
+                    "  {} dependencies: {}",
+                    package.dependencies.len(),
+                    package
+                        .dependencies
+                        .iter()
+                        .map(|d| d.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                        .dimmed()
+                );
+            }
+        }
+
+        let unknown = self.packages.iter().filter(|p| p.license.is_none()).count();
+        println!();
+        println!("Total: {} package(s)", self.packages.len());
+        if unknown > 0 {
+            println!(
+                "{}",
+                format!("{} package(s) without a detected license", unknown).yellow()
+            );
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssetsReport {
+    pub assets: Vec<Asset>,
+}
+
+impl Report for AssetsReport {
+    fn render_text(&self) {
+        if self.assets.is_empty() {
+            println!("{}", "No non-code files recorded in this docpack.".yellow());
+            return;
+        }
+
+        println!("{}", "Assets".bold().cyan());
+        display::print_rule('=');
+
+        let mut current_category = None;
+        for asset in &self.assets {
+            if current_category != Some(&asset.category) {
+                println!();
+                println!("{}", format!("[{}]", asset.category).yellow());
+                current_category = Some(&asset.category);
+            }
+            match asset.size {
+                Some(size) => println!(
+                    "  {} {}",
+                    asset.path.green(),
+                    format!("({} bytes)", size).dimmed()
+                ),
+                None => println!("  {}", asset.path.green()),
+            }
+            if !asset.description.is_empty() {
+                println!("    {}", asset.description.dimmed());
+            }
+        }
+
+        println!();
+        println!("Total: {} file(s)", self.assets.len());
+    }
+}
+
+/// Runtime statistics of the spans that mapped to one symbol
+#[derive(Debug, Serialize)]
+pub struct TraceMapEntry {
+    pub symbol: String,
+    pub file: String,
+    pub line: usize,
+    pub calls: usize,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceMapReport {
+    pub spans: usize,
+    pub matched_spans: usize,
+    pub symbols: Vec<TraceMapEntry>,
+    /// Distinct names of spans that matched no symbol
+    pub unmatched: Vec<String>,
+}
+
+impl Report for TraceMapReport {
+    fn render_text(&self) {
+        println!("{}", "Trace Map".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        if self.symbols.is_empty() {
+            println!("{}", "No spans matched symbols in this docpack.".yellow());
+        }
+
+        for entry in &self.symbols {
+            println!(
+                "{} {}",
+                display::name(&entry.symbol).green(),
+                format!("({}:{})", entry.file, entry.line).dimmed()
+            );
+            println!(
+                "  {} calls, {:.2} ms total, {:.2} ms mean, {:.2} ms max",
+                entry.calls, entry.total_ms, entry.mean_ms, entry.max_ms
+            );
+        }
+
+        if !self.unmatched.is_empty() {
+            println!();
+            println!("{}", "Unmatched spans:".bold().yellow());
+            for name in &self.unmatched {
+                println!("  {}", name.dimmed());
+            }
+        }
+
+        println!();
+        println!(
+            "Matched {}/{} spans to {} symbol(s)",
+            self.matched_spans,
+            self.spans,
+            self.symbols.len()
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileEntry {
+    pub symbol: String,
+    pub file: String,
+    pub line: usize,
+    #[serde(flatten)]
+    pub stats: ProfileStats,
+    pub self_percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileReport {
+    pub samples: u64,
+    /// Samples whose innermost frame matched no symbol
+    pub unattributed_samples: u64,
+    pub symbols: Vec<ProfileEntry>,
+    /// Whether the overlay was stored in the docpack
+    pub saved: bool,
+}
+
+impl Report for ProfileReport {
+    fn render_text(&self) {
+        println!("{}", "Runtime Profile".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        if self.symbols.is_empty() {
+            println!(
+                "{}",
+                "No profile frames matched symbols in this docpack.".yellow()
+            );
+        } else {
+            println!(
+                "{}",
+                format!("{:>7} {:>7}  symbol", "self", "total").dimmed()
+            );
+        }
+
+        for entry in &self.symbols {
+            println!(
+                "{:>6.1}% {:>6.1}%  {} {}",
+                entry.self_percent,
+                entry.stats.total_percent,
+                display::name(&entry.symbol).green(),
+                format!("({}:{})", entry.file, entry.line).dimmed()
+            );
+        }
+
+        println!();
+        println!(
+            "Total: {} samples, {} outside known symbols",
+            self.samples, self.unattributed_samples
+        );
+        if self.saved {
+            println!("{}", "Profile written to docpack.".dimmed());
+        }
+    }
+}
+
+/// A stack frame together with the symbol it maps to
+#[derive(Debug, Serialize)]
+pub struct ResolvedFrame {
+    pub index: usize,
+    pub function: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<ResolvedSymbol>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResolvedSymbol {
+    pub id: String,
+    pub kind: String,
+    pub file: String,
+    pub line: usize,
+    /// Namespace or file the symbol lives in
+    pub module: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResolveTraceReport {
+    pub frames: Vec<ResolvedFrame>,
+}
+
+impl Report for ResolveTraceReport {
+    fn render_text(&self) {
+        if self.frames.is_empty() {
+            println!("{}", "No stack frames found in input.".yellow());
+            return;
+        }
+
+        println!("{}", "Resolved Stack Trace".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for frame in &self.frames {
+            let function = if frame.function.is_empty() {
+                "<anonymous>"
+            } else {
+                frame.function.as_str()
+            };
+            let location = frame
+                .location
+                .as_deref()
+                .map(|l| format!(" ({})", l))
+                .unwrap_or_default();
+            println!(
+                "{} {}{}",
+                format!("#{:<3}", frame.index).dimmed(),
+                function,
+                location.dimmed()
+            );
+
+            match &frame.symbol {
+                Some(symbol) => {
+                    println!(
+                        "     {} {} {}",
+                        format!("[{}]", symbol.kind).yellow(),
+                        display::name(&symbol.id).green(),
+                        format!("in {}", symbol.module).dimmed()
+                    );
+                    if !symbol.summary.is_empty() {
+                        println!("     {}", symbol.summary);
+                    }
+                }
+                None => println!("     {}", "not in this docpack".dimmed()),
+            }
+        }
+
+        let resolved = self.frames.iter().filter(|f| f.symbol.is_some()).count();
+        println!();
+        println!("Resolved {}/{} frames", resolved, self.frames.len());
+    }
+}
+
+/// A string literal whose text matches a log line
+#[derive(Debug, Serialize)]
+pub struct WhenceMatch {
+    pub symbol: String,
+    pub kind: String,
+    pub file: String,
+    /// Line of the matching literal
+    pub line: usize,
+    pub literal: String,
+    /// Number of fixed (non-placeholder) characters that matched
+    pub score: usize,
+    pub summary: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WhenceReport {
+    pub message: String,
+    pub matches: Vec<WhenceMatch>,
+}
+
+impl Report for WhenceReport {
+    fn render_text(&self) {
+        if self.matches.is_empty() {
+            println!(
+                "{}",
+                format!("No snippet emits a message like '{}'", self.message).yellow()
+            );
+            return;
+        }
+
+        println!(
+            "{}",
+            format!("Possible sources of '{}'", self.message)
+                .bold()
+                .cyan()
+        );
+        display::print_rule('=');
+        println!();
+
+        for entry in &self.matches {
+            println!(
+                "{} {} {}",
+                format!("[{}]", entry.kind).yellow(),
+                display::name(&entry.symbol).green(),
+                format!("({}:{})", entry.file, entry.line).dimmed()
+            );
+            println!("  {}", format!("\"{}\"", entry.literal).dimmed());
+            if !entry.summary.is_empty() {
+                println!("  {}", entry.summary);
+            }
+            println!();
+        }
+
+        println!("Found {} candidate(s)", self.matches.len());
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnrichReport {
+    pub docpack: String,
+    /// Symbols with at least one issue or PR reference
+    pub referenced_symbols: usize,
+    pub references: usize,
+    /// Symbols with a recorded last-modified commit
+    pub dated_symbols: usize,
+    /// Symbols whose history couldn't be read (e.g. files missing from the repo)
+    pub skipped_symbols: usize,
+}
+
+impl Report for EnrichReport {
+    fn render_text(&self) {
+        println!(
+            "{}",
+            format!("{} Enriched docpack", display::check_mark())
+                .green()
+                .bold()
+        );
+        println!("{}: {}", "Docpack".bold(), self.docpack);
+        println!(
+            "{}: {} ({} distinct)",
+            "Symbols With References".bold(),
+            self.referenced_symbols,
+            self.references
+        );
+        println!("{}: {}", "Symbols With History".bold(), self.dated_symbols);
+        if self.skipped_symbols > 0 {
+            println!(
+                "{}",
+                format!(
+                    "Skipped {} symbol(s) whose history couldn't be read",
+                    self.skipped_symbols
+                )
+                .yellow()
+            );
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaleEntry {
+    pub symbol: String,
+    pub file: String,
+    pub line: usize,
+    pub last_modified: LastModified,
+    pub age_days: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub complexity: Option<u32>,
+    pub documented: bool,
+    /// Age weighted by complexity, doubled when undocumented
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaleReport {
+    pub symbols: Vec<StaleEntry>,
+}
+
+impl Report for StaleReport {
+    fn render_text(&self) {
+        if self.symbols.is_empty() {
+            println!("{}", "No history recorded in this docpack.".yellow());
+            println!();
+            println!(
+                "Record it with: {}",
+                "localdoc enrich <docpack> --repo <path>".cyan()
+            );
+            return;
+        }
+
+        println!("{}", "Stale Code".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for entry in &self.symbols {
+            println!(
+                "{} {}",
+                display::name(&entry.symbol).green(),
+                format!("({}:{})", entry.file, entry.line).dimmed()
+            );
+
+            let mut details = vec![format!(
+                "{} days since {} by {}",
+                entry.age_days, entry.last_modified.date, entry.last_modified.author
+            )];
+            if let Some(complexity) = entry.complexity {
+                details.push(format!("complexity {}", complexity));
+            }
+            if !entry.documented {
+                details.push("undocumented".to_string());
+            }
+            println!("  {}", details.join(", ").dimmed());
+        }
+    }
+}
+
+/// A constructor and the types it instantiates
+#[derive(Debug, Serialize)]
+pub struct ConstructionEntry {
+    /// Type the constructor builds
+    pub owner: String,
+    pub constructor: String,
+    pub file: String,
+    pub line: usize,
+    pub constructs: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConstructionReport {
+    pub constructors: Vec<ConstructionEntry>,
+}
+
+impl Report for ConstructionReport {
+    fn render_text(&self) {
+        if self.constructors.is_empty() {
+            println!("{}", "No constructor functions found.".yellow());
+            return;
+        }
+
+        println!("{}", "Construction Graph".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for entry in &self.constructors {
+            println!(
+                "{} {} {}",
+                entry.owner.green().bold(),
+                format!("via {}", display::name(&entry.constructor)).dimmed(),
+                format!("({}:{})", entry.file, entry.line).dimmed()
+            );
+            if entry.constructs.is_empty() {
+                println!("  {}", "constructs no other known types".dimmed());
+            }
+            for constructed in &entry.constructs {
+                println!("  {} {}", "->".yellow(), constructed);
+            }
+        }
+
+        let edges: usize = self.constructors.iter().map(|c| c.constructs.len()).sum();
+        println!();
+        println!(
+            "Total: {} constructor(s), {} construction edge(s)",
+            self.constructors.len(),
+            edges
+        );
+    }
+}
+
+/// A function location in async topology reports
+#[derive(Debug, Serialize)]
+pub struct AsyncSite {
+    pub symbol: String,
+    pub file: String,
+    pub line: usize,
+    /// What makes this site notable: a runtime attribute, a blocking call, or the async callee
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AsyncMapReport {
+    pub async_functions: usize,
+    pub entry_points: Vec<AsyncSite>,
+    /// Sync functions that drive async code (block_on, asyncio.run, ...)
+    pub boundaries: Vec<AsyncSite>,
+    /// Sync functions that call an async function without driving it
+    pub sync_callers: Vec<AsyncSite>,
+}
+
+impl Report for AsyncMapReport {
+    fn render_text(&self) {
+        if self.async_functions == 0 && self.boundaries.is_empty() {
+            println!("{}", "No async functions found.".yellow());
+            return;
+        }
+
+        println!("{}", "Async Topology".bold().cyan());
+        display::print_rule('=');
+        println!();
+        println!("{}: {}", "Async Functions".bold(), self.async_functions);
+
+        let sections = [
+            ("Entry points:", &self.entry_points),
+            ("Sync -> async boundaries:", &self.boundaries),
+            ("Async functions called from sync code:", &self.sync_callers),
+        ];
+        for (title, sites) in sections {
+            println!();
+            println!("{}", title.bold().yellow());
+            if sites.is_empty() {
+                println!("  {}", "none".dimmed());
+            }
+            for site in sites {
+                println!(
+                    "  {} {} {}",
+                    display::name(&site.symbol).green(),
+                    format!("({}:{})", site.file, site.line).dimmed(),
+                    site.detail
+                );
+            }
+        }
+    }
+}
+
+/// How a fallible function's errors reach the public API
+#[derive(Debug, Serialize)]
+pub struct ErrorPath {
+    pub symbol: String,
+    pub file: String,
+    pub line: usize,
+    pub public: bool,
+    /// Public fallible functions its errors propagate to through fallible callers
+    pub surfaces_in: Vec<String>,
+}
+
+/// A call from a non-fallible function into a fallible one
+#[derive(Debug, Serialize)]
+pub struct SwallowedError {
+    pub caller: String,
+    pub callee: String,
+    pub file: String,
+    pub line: usize,
+    /// How the caller appears to deal with the error, e.g. "unwrap" or "discarded"
+    pub handling: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorsReport {
+    pub fallible: Vec<ErrorPath>,
+    pub swallowed: Vec<SwallowedError>,
+}
+
+impl Report for ErrorsReport {
+    fn render_text(&self) {
+        if self.fallible.is_empty() {
+            println!("{}", "No functions returning errors found.".yellow());
+            return;
+        }
+
+        println!("{}", "Error Propagation".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for path in &self.fallible {
+            let visibility = if path.public {
+                "[pub]".green()
+            } else {
+                "[private]".dimmed()
+            };
+            println!(
+                "{} {} {}",
+                visibility,
+                display::name(&path.symbol).green(),
+                format!("({}:{})", path.file, path.line).dimmed()
+            );
+            if !path.surfaces_in.is_empty() {
+                println!(
+                    "  {} {}",
+                    "surfaces in".dimmed(),
+                    path.surfaces_in.join(", ")
+                );
+            } else if !path.public {
+                println!("  {}", "does not reach the public API".dimmed());
+            }
+        }
+
+        println!();
+        println!("{}", "Swallowed errors:".bold().yellow());
+        if self.swallowed.is_empty() {
+            println!("  {}", "none".dimmed());
+        }
+        for swallowed in &self.swallowed {
+            println!(
+                "  {} {} calls {} {}",
+                display::name(&swallowed.caller).green(),
+                format!("({}:{})", swallowed.file, swallowed.line).dimmed(),
+                display::name(&swallowed.callee),
+                format!("[{}]", swallowed.handling).yellow()
+            );
+        }
+
+        println!();
+        println!(
+            "Total: {} fallible function(s), {} swallowed call(s)",
+            self.fallible.len(),
+            self.swallowed.len()
+        );
+    }
+}
+
+/// Failure points in a function reachable from the public API
+#[derive(Debug, Serialize)]
+pub struct PanicEntry {
+    pub symbol: String,
+    pub file: String,
+    pub line: usize,
+    pub public: bool,
+    /// Public functions that reach this one through calls, at most a few
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reachable_from: Vec<String>,
+    /// More public functions reach this one than are listed
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub more_roots: bool,
+    /// Count per kind, e.g. {"unwrap": 2, "panic": 1}
+    pub sources: std::collections::BTreeMap<String, usize>,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PanicsReport {
+    pub functions: Vec<PanicEntry>,
+}
+
+impl Report for PanicsReport {
+    fn render_text(&self) {
+        if self.functions.is_empty() {
+            println!(
+                "{}",
+                "No panic or exception sources reachable from the public API.".green()
+            );
+            return;
+        }
+
+        println!("{}", "Panic/Exception Sources".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for entry in &self.functions {
+            let sources = entry
+                .sources
+                .iter()
+                .map(|(kind, count)| format!("{} {}", count, kind))
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!(
+                "{} {} {} {}",
+                format!("{:>3}", entry.total).red(),
+                display::name(&entry.symbol).green(),
+                format!("({}:{})", entry.file, entry.line).dimmed(),
+                sources.yellow()
+            );
+            if !entry.public {
+                let roots: Vec<_> = entry
+                    .reachable_from
+                    .iter()
+                    .map(|root| display::name(root))
+                    .collect();
+                let more = if entry.more_roots { " and others" } else { "" };
+                println!(
+                    "    {} {}{}",
+                    "reachable from".dimmed(),
+                    roots.join(", "),
+                    more
+                );
+            }
+        }
+
+        let total: usize = self.functions.iter().map(|f| f.total).sum();
+        println!();
+        println!(
+            "Total: {} source(s) in {} function(s)",
+            total,
+            self.functions.len()
+        );
+    }
+}
+
+/// Call figures for one MCP tool (or non-tool method)
+#[derive(Debug, Clone, Serialize)]
+pub struct McpToolStats {
+    pub name: String,
+    pub calls: usize,
+    pub errors: usize,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: f64,
+    pub avg_result_bytes: usize,
+}
+
+/// A tool argument value and how often agents passed it
+#[derive(Debug, Clone, Serialize)]
+pub struct McpArgumentCount {
+    pub key: String,
+    pub value: String,
+    pub count: usize,
+}
+
+/// Summary of an MCP request log
+#[derive(Debug, Clone, Serialize)]
+pub struct McpStatsReport {
+    pub requests: usize,
+    pub first_timestamp: Option<u64>,
+    pub last_timestamp: Option<u64>,
+    pub tools: Vec<McpToolStats>,
+    pub top_arguments: Vec<McpArgumentCount>,
+}
+
+impl Report for McpStatsReport {
+    fn render_text(&self) {
+        if self.requests == 0 {
+            println!("{}", "No requests logged.".yellow());
+            return;
+        }
+
+        println!("{}", "MCP Usage".bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for tool in &self.tools {
+            println!(
+                "{} {} {}",
+                format!("{:>5}", tool.calls).yellow(),
+                tool.name.green(),
+                format!(
+                    "(avg {:.1} ms, max {:.1} ms, avg {} bytes)",
+                    tool.avg_latency_ms, tool.max_latency_ms, tool.avg_result_bytes
+                )
+                .dimmed()
+            );
+            if tool.errors > 0 {
+                println!("      {}", format!("{} error(s)", tool.errors).red());
+            }
+        }
+
+        if !self.top_arguments.is_empty() {
+            println!();
+            println!("{}", "Most Queried".bold());
+            for argument in &self.top_arguments {
+                println!(
+                    "{} {}={}",
+                    format!("{:>5}", argument.count).yellow(),
+                    argument.key.dimmed(),
+                    argument.value
+                );
+            }
+        }
+
+        println!();
+        println!(
+            "Total: {} request(s) to {} tool(s)",
+            self.requests,
+            self.tools.len()
+        );
+    }
+}
+
+/// One check run by `localdoc ci`
+#[derive(Debug, Serialize)]
+pub struct CiCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+    /// What made the check fail, e.g. removed symbols
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CiReport {
+    pub docpack: String,
+    pub checks: Vec<CiCheck>,
+}
+
+impl Report for CiReport {
+    fn render_text(&self) {
+        /// Problems listed per failed check
+        const PROBLEMS_SHOWN: usize = 10;
+
+        println!("{}", format!("CI Checks: {}", self.docpack).bold().cyan());
+        display::print_rule('=');
+        println!();
+
+        for check in &self.checks {
+            let status = if check.passed {
+                display::check_mark().green()
+            } else {
+                display::cross().red()
+            };
+            println!("{} {} {}", status, check.name.bold(), check.detail.dimmed());
+            for problem in check.problems.iter().take(PROBLEMS_SHOWN) {
+                println!("    - {}", problem);
+            }
+            if check.problems.len() > PROBLEMS_SHOWN {
+                println!(
+                    "    {}",
+                    format!("... {} more", check.problems.len() - PROBLEMS_SHOWN).dimmed()
+                );
+            }
+        }
+
+        let failed = self.checks.iter().filter(|c| !c.passed).count();
+        println!();
+        if failed == 0 {
+            println!(
+                "{}",
+                format!("All {} check(s) passed", self.checks.len()).green()
+            );
+        } else {
+            println!(
+                "{}",
+                format!("{} of {} check(s) failed", failed, self.checks.len()).red()
+            );
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::config::SearchConfig;
+use crate::docpack::Docpack;
+use crate::models::Symbol;
+use crate::render::OutputFormat;
+use crate::reports::{SymbolDoc, SymbolDocsReport};
+use anyhow::Result;
+use colored::*;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+/// Results listed per command until changed with `set limit`
+const DEFAULT_LIMIT: usize = 20;
+
+const COMMANDS: &[&str] = &[
+    "search", "inspect", "callers", "callees", "tests", "files", "set", "help", "quit",
+];
+
+/// Commands whose argument is a symbol name
+const SYMBOL_COMMANDS: &[&str] = &["inspect", "callers", "callees", "tests"];
+
+const HELP: &str = "\
+search <query>    Search symbols (same syntax as `query search`)
+inspect <name>    Show a symbol's documentation
+callers <name>    Symbols that call a function
+callees <name>    Functions a symbol calls
+tests <name>      Tests that exercise a symbol
+files             Source files in the docpack
+set limit <n>     Results listed per command
+help              Show this help
+quit              Leave the shell (or Ctrl-D)";
+
+/// Explore one docpack interactively, loading it once for every command
+pub fn run(docpack: &Docpack, search: &SearchConfig, format: OutputFormat) -> Result<()> {
+    let mut editor: Editor<ShellHelper, _> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper {
+        ids: docpack.symbols.iter().map(|s| s.id.clone()).collect(),
+    }));
+
+    let project = &docpack.manifest.project;
+    println!(
+        "{} v{} ({} symbols). Type {} for commands, Tab to complete.",
+        project.name.bold(),
+        project.version,
+        docpack.symbols.len(),
+        "help".cyan()
+    );
+
+    let mut limit = DEFAULT_LIMIT;
+    loop {
+        let line = match editor.readline("localdoc> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line)?;
+
+        let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = arg.trim();
+        let outcome = match command {
+            "quit" | "exit" => break,
+            "help" | "?" => {
+                println!("{}", HELP);
+                Ok(())
+            }
+            "set" => set(arg, &mut limit),
+            "search" => search_command(docpack, search, arg, limit),
+            "inspect" | "symbol" => inspect(docpack, arg, format),
+            "callers" => list_related(docpack, arg, limit, "callers", Docpack::callers),
+            "callees" => list_related(docpack, arg, limit, "callees", Docpack::callees),
+            "tests" => list_related(docpack, arg, limit, "tests", Docpack::find_tests_for),
+            "files" => {
+                print_limited(docpack.get_unique_files(), limit);
+                Ok(())
+            }
+            _ => Err(anyhow::anyhow!(
+                "Unknown command '{}'; type help for commands",
+                command
+            )),
+        };
+        if let Err(e) = outcome {
+            eprintln!("{}", format!("{:#}", e).red());
+        }
+    }
+
+    Ok(())
+}
+
+fn set(arg: &str, limit: &mut usize) -> Result<()> {
+    match arg.split_once(' ') {
+        Some(("limit", value)) => {
+            *limit = value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Limit must be a number, got '{}'", value))?;
+            Ok(())
+        }
+        None if arg.is_empty() => {
+            println!("limit = {}", limit);
+            Ok(())
+        }
+        _ => anyhow::bail!("Usage: set limit <n>"),
+    }
+}
+
+fn search_command(
+    docpack: &Docpack,
+    search: &SearchConfig,
+    query: &str,
+    limit: usize,
+) -> Result<()> {
+    if query.is_empty() {
+        anyhow::bail!("Usage: search <query>");
+    }
+    let results = docpack.search_symbols(query, search)?;
+    if results.is_empty() {
+        println!("{}", format!("No results found for '{}'", query).yellow());
+        return Ok(());
+    }
+    let lines = results
+        .iter()
+        .map(|result| symbol_line(&result.symbol, &result.doc.summary))
+        .collect();
+    print_limited(lines, limit);
+    Ok(())
+}
+
+fn inspect(docpack: &Docpack, name: &str, format: OutputFormat) -> Result<()> {
+    let symbol = resolve(docpack, name)?.clone();
+    format.render(&SymbolDocsReport {
+        matches: vec![SymbolDoc {
+            documentation: docpack.get_documentation(&symbol.doc_id)?,
+            metrics: docpack.metrics_for(&symbol),
+            profile: docpack.profile_for(&symbol),
+            calls: None,
+            symbol,
+        }],
+    })
+}
+
+/// List the symbols `related` finds for a symbol, e.g. its callers
+fn list_related<'d>(
+    docpack: &'d Docpack,
+    name: &str,
+    limit: usize,
+    what: &str,
+    related: impl Fn(&'d Docpack, &'d Symbol) -> Vec<&'d Symbol>,
+) -> Result<()> {
+    let symbol = resolve(docpack, name)?;
+    let found = related(docpack, symbol);
+    if found.is_empty() {
+        println!(
+            "{}",
+            format!("No {} found for {}", what, symbol.id).yellow()
+        );
+        return Ok(());
+    }
+    let lines = found
+        .iter()
+        .map(|s| {
+            let summary = docpack
+                .get_documentation(&s.doc_id)
+                .map(|doc| doc.summary)
+                .unwrap_or_default();
+            symbol_line(s, &summary)
+        })
+        .collect();
+    print_limited(lines, limit);
+    Ok(())
+}
+
+/// The best match for a name, noting when others exist
+fn resolve<'d>(docpack: &'d Docpack, name: &str) -> Result<&'d Symbol> {
+    if name.is_empty() {
+        anyhow::bail!("Missing symbol name");
+    }
+    let matches = docpack.find_symbols_by_name(name);
+    let Some(symbol) = matches.first() else {
+        anyhow::bail!("No symbol found matching '{}'", name);
+    };
+    if matches.len() > 1 {
+        println!(
+            "{}",
+            format!(
+                "{} symbols match '{}'; using {} (give the full ID for another)",
+                matches.len(),
+                name,
+                symbol.id
+            )
+            .dimmed()
+        );
+    }
+    Ok(symbol)
+}
+
+fn symbol_line(symbol: &Symbol, summary: &str) -> String {
+    let mut line = format!(
+        "{} {}",
+        format!("[{}]", symbol.kind).yellow(),
+        symbol.id.green()
+    );
+    if !summary.is_empty() {
+        line.push_str(&format!(" - {}", summary));
+    }
+    line
+}
+
+fn print_limited(lines: Vec<String>, limit: usize) {
+    for line in lines.iter().take(limit) {
+        println!("  {}", line);
+    }
+    if lines.len() > limit {
+        println!(
+            "{}",
+            format!(
+                "  ... {} more (set limit {} to see all)",
+                lines.len() - limit,
+                lines.len()
+            )
+            .dimmed()
+        );
+    }
+}
+
+/// Tab completion of command names, and of symbol IDs after commands taking one
+struct ShellHelper {
+    ids: Vec<String>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let Some((command, word)) = line.split_once(' ') else {
+            let commands = COMMANDS.iter().filter(|c| c.starts_with(line));
+            return Ok((0, commands.map(|c| format!("{} ", c)).collect()));
+        };
+        if !SYMBOL_COMMANDS.contains(&command) {
+            return Ok((pos, Vec::new()));
+        }
+
+        let word = word.trim_start();
+        let start = pos - word.len();
+        let mut ids: Vec<String> = self
+            .ids
+            .iter()
+            .filter(|id| id.starts_with(word))
+            .cloned()
+            .collect();
+        if ids.is_empty() {
+            // "par<Tab>" completes "Parser::parse_node" by its last segment
+            let word = word.to_lowercase();
+            ids = self
+                .ids
+                .iter()
+                .filter(|id| {
+                    id.rsplit("::")
+                        .next()
+                        .is_some_and(|name| name.to_lowercase().starts_with(&word))
+                })
+                .cloned()
+                .collect();
+        }
+        Ok((start, ids))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Searches remembered per docpack
+const HISTORY_LIMIT: usize = 50;
+
+/// What localdoc remembers between runs, stored as `state.json` in its data directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    /// Recent search queries keyed by docpack path, oldest first
+    #[serde(default)]
+    pub search_history: BTreeMap<String, Vec<String>>,
+}
+
+fn path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().context("Could not determine user data directory")?;
+    Ok(data_dir.join("localdoc").join("state.json"))
+}
+
+/// Read the saved state; a missing file is treated as empty
+pub fn load() -> Result<State> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(State::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .context(format!("Failed to read state file {}", path.display()))?;
+    serde_json::from_str(&content).context(format!("Failed to parse state file {}", path.display()))
+}
+
+impl State {
+    pub fn save(&self) -> Result<()> {
+        let path = path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .context(format!("Failed to write state file {}", path.display()))
+    }
+
+    /// Recent searches of a docpack, most recent first
+    pub fn searches(&self, docpack: &str) -> Vec<&str> {
+        self.search_history
+            .get(docpack)
+            .map(|queries| queries.iter().rev().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Remember a search, moving a repeated query to the front
+    pub fn record_search(&mut self, docpack: &str, query: &str) {
+        let queries = self.search_history.entry(docpack.to_string()).or_default();
+        queries.retain(|q| q != query);
+        queries.push(query.to_string());
+        if queries.len() > HISTORY_LIMIT {
+            queries.drain(..queries.len() - HISTORY_LIMIT);
+        }
+    }
+
+    /// The query `!!` (the last search) or `!N` (the Nth most recent) refers to
+    pub fn recall_search(&self, docpack: &str, shortcut: &str) -> Result<String> {
+        let n = match shortcut {
+            "!!" => 1,
+            _ => shortcut[1..]
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .context(format!(
+                    "Invalid history shortcut '{}'; use !! or !N",
+                    shortcut
+                ))?,
+        };
+        self.searches(docpack)
+            .get(n - 1)
+            .map(|query| query.to_string())
+            .context(format!(
+                "No search #{} in this docpack's history (see `search --history`)",
+                n
+            ))
+    }
+}
@@ -0,0 +1,67 @@
+/// Split text into lowercase words, breaking identifiers at `snake_case` and
+/// `camelCase` boundaries, e.g. "NodeSerializer::to_json" -> ["node", "serializer", "to", "json"]
+pub fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+
+    for part in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            // "nodeSerializer" breaks before "S"; "HTTPServer" breaks before "Se"
+            let boundary = c.is_uppercase()
+                && prev.is_some_and(|p| {
+                    p.is_lowercase()
+                        || p.is_ascii_digit()
+                        || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+                });
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.extend(c.to_lowercase());
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+
+    words
+}
+
+/// Suffixes stripped by `stem`, longest first
+const SUFFIXES: &[&str] = &[
+    "izations", "isations", "ization", "isation", "izers", "isers", "izing", "ising", "izer",
+    "iser", "ized", "ised", "izes", "ises", "ations", "ation", "ize", "ise", "ments", "ment",
+    "ness", "ings", "ing", "ers", "er", "ed", "es", "s", "e",
+];
+
+/// Light stemming so word forms match each other: "serialization", "serialize",
+/// and "serializer" all become "serial"; "parse", "parser", and "parsing" become "pars".
+/// Stems keep at least three characters.
+pub fn stem(word: &str) -> String {
+    // "entries" -> "entry"
+    if let Some(stem) = word.strip_suffix("ies") {
+        if stem.chars().count() >= 3 {
+            return format!("{}y", stem);
+        }
+    }
+
+    for suffix in SUFFIXES {
+        // "process", "status", and "analysis" aren't plurals
+        if *suffix == "s" && ["ss", "us", "is"].iter().any(|end| word.ends_with(end)) {
+            continue;
+        }
+        if let Some(stem) = word.strip_suffix(suffix) {
+            if stem.chars().count() >= 3 {
+                return stem.to_string();
+            }
+        }
+    }
+    word.to_string()
+}
+
+/// Stems of every word in `text`
+pub fn stems(text: &str) -> Vec<String> {
+    words(text).iter().map(|word| stem(word)).collect()
+}
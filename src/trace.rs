@@ -0,0 +1,314 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A span read from an OTLP/JSON export
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub name: String,
+    /// `code.function`, qualified with `code.namespace` when both are present
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub duration_ms: f64,
+}
+
+impl Span {
+    /// Name to resolve against the docpack, preferring the code attributes
+    pub fn frame_name(&self) -> &str {
+        self.function.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Read every span from an OTLP/JSON trace export (`resourceSpans` ->
+/// `scopeSpans` -> `spans`, as written by the collector's file exporter).
+/// Files holding one export per line are accepted too.
+pub fn load_otlp_spans(path: &str) -> Result<Vec<Span>> {
+    let content =
+        std::fs::read_to_string(path).context(format!("Failed to read spans file {}", path))?;
+
+    let exports: Vec<Value> = match serde_json::from_str(&content) {
+        Ok(value) => vec![value],
+        Err(_) => content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()
+            .context(format!("Failed to parse spans file {}", path))?,
+    };
+
+    let mut spans = Vec::new();
+    for export in &exports {
+        for resource in array(export, "resourceSpans") {
+            // Older exporters used `instrumentationLibrarySpans`
+            let scopes = array(resource, "scopeSpans")
+                .iter()
+                .chain(array(resource, "instrumentationLibrarySpans"));
+            for scope in scopes {
+                spans.extend(array(scope, "spans").iter().map(parse_span));
+            }
+        }
+    }
+
+    Ok(spans)
+}
+
+fn parse_span(span: &Value) -> Span {
+    let attr = |key: &str| {
+        array(span, "attributes")
+            .iter()
+            .find(|a| a["key"] == key)
+            .and_then(|a| attribute_value(&a["value"]))
+    };
+
+    let function = match (attr("code.namespace"), attr("code.function")) {
+        (Some(namespace), Some(function)) => Some(format!("{}::{}", namespace, function)),
+        (_, function) => function,
+    };
+
+    let start = nanos(&span["startTimeUnixNano"]);
+    let end = nanos(&span["endTimeUnixNano"]);
+
+    Span {
+        name: span["name"].as_str().unwrap_or_default().to_string(),
+        function,
+        file: attr("code.filepath"),
+        line: attr("code.lineno").and_then(|l| l.parse().ok()),
+        duration_ms: end.saturating_sub(start) as f64 / 1_000_000.0,
+    }
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value[key].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// OTLP attribute values are wrapped as `{"stringValue": ...}`, `{"intValue": ...}`, etc.
+fn attribute_value(value: &Value) -> Option<String> {
+    let inner = value.get("stringValue").or_else(|| value.get("intValue"))?;
+    match inner {
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// OTLP/JSON encodes 64-bit timestamps as strings
+fn nanos(value: &Value) -> u64 {
+    match value {
+        Value::String(s) => s.parse().unwrap_or(0),
+        other => other.as_u64().unwrap_or(0),
+    }
+}
+
+/// A sampled call stack with its sample count, outermost frame first
+#[derive(Debug, Clone)]
+pub struct Stack {
+    pub frames: Vec<String>,
+    pub count: u64,
+}
+
+/// Sampling profile figures for one symbol
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileStats {
+    /// Samples where the symbol was the innermost frame
+    pub self_samples: u64,
+    /// Samples where the symbol appeared anywhere on the stack
+    pub total_samples: u64,
+    /// `total_samples` as a share of all samples in the profile, 0-100
+    pub total_percent: f64,
+}
+
+/// Read a profile in collapsed-stack format ("main;run;parse 42" per line),
+/// as produced by FlameGraph's stackcollapse scripts or inferno
+pub fn load_collapsed_stacks(path: &str) -> Result<Vec<Stack>> {
+    let content = std::fs::read(path).context(format!("Failed to read profile {}", path))?;
+
+    // pprof files are gzip-compressed protobuf
+    if content.starts_with(&[0x1f, 0x8b]) {
+        anyhow::bail!(
+            "{} looks like a pprof profile; convert it to collapsed stacks first (e.g. with inferno-collapse or FlameGraph's stackcollapse-go.pl)",
+            path
+        );
+    }
+
+    let content = String::from_utf8(content).context(format!("{} is not valid UTF-8", path))?;
+
+    let mut stacks = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (stack, count) = line
+            .rsplit_once(' ')
+            .and_then(|(stack, count)| Some((stack, count.parse().ok()?)))
+            .context(format!(
+                "Line {} of {} is not a collapsed stack",
+                i + 1,
+                path
+            ))?;
+
+        let frames = stack
+            .split(';')
+            // perf prefixes frames with their module, e.g. "libc.so.6`malloc"
+            .map(|frame| frame.rsplit('`').next().unwrap_or(frame).to_string())
+            .collect();
+
+        stacks.push(Stack { frames, count });
+    }
+
+    Ok(stacks)
+}
+
+/// One frame of a stack trace
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Function name as printed; empty for anonymous frames
+    pub function: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
+
+/// Parse the frames of a stack trace, innermost first as printed.
+/// Understands Rust backtraces, Python tracebacks, Java/JavaScript "at ..." lines,
+/// and Go panics; other lines (messages, headers) are skipped.
+pub fn parse_stack_trace(text: &str) -> Vec<Frame> {
+    let python = Regex::new(r#"^\s*File "([^"]+)", line (\d+), in (\S+)"#).unwrap();
+    let at_call =
+        Regex::new(r"^\s*at (?:async )?([^\s(]+)\s*\(([^()]*?):(\d+)(?::\d+)?\)").unwrap();
+    let at_location = Regex::new(r"^\s*at (.+?):(\d+)(?::\d+)?\s*$").unwrap();
+    let rust = Regex::new(r"^\s*\d+:\s+(?:0x[0-9a-fA-F]+ - )?(\S+)").unwrap();
+    let go_call = Regex::new(r"^([\w./*()\-]+)\(.*\)$").unwrap();
+    let go_location = Regex::new(r"^\s+(\S+\.go):(\d+)").unwrap();
+
+    let mut frames: Vec<Frame> = Vec::new();
+    // Rust and Go print a frame's location on the line after its function
+    let mut awaiting_location = false;
+
+    for line in text.lines() {
+        if let Some(caps) = python.captures(line) {
+            frames.push(Frame {
+                function: caps[3].to_string(),
+                file: Some(caps[1].to_string()),
+                line: caps[2].parse().ok(),
+            });
+            awaiting_location = false;
+        } else if let Some(caps) = at_call.captures(line) {
+            frames.push(Frame {
+                function: caps[1].to_string(),
+                file: Some(caps[2].to_string()),
+                line: caps[3].parse().ok(),
+            });
+            awaiting_location = false;
+        } else if let Some(caps) = go_location
+            .captures(line)
+            .or_else(|| at_location.captures(line))
+        {
+            let file = Some(caps[1].to_string());
+            let line = caps[2].parse().ok();
+            match frames.last_mut() {
+                Some(frame) if awaiting_location => {
+                    frame.file = file;
+                    frame.line = line;
+                }
+                _ => frames.push(Frame {
+                    function: String::new(),
+                    file,
+                    line,
+                }),
+            }
+            awaiting_location = false;
+        } else if let Some(caps) = rust.captures(line) {
+            frames.push(Frame {
+                function: caps[1].to_string(),
+                file: None,
+                line: None,
+            });
+            awaiting_location = true;
+        } else if let Some(caps) = go_call.captures(line) {
+            // "main.(*Parser).parse" -> "main.Parser.parse"
+            frames.push(Frame {
+                function: caps[1].replace("(*", "").replace(").", "."),
+                file: None,
+                line: None,
+            });
+            awaiting_location = true;
+        }
+    }
+
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames as (function, file, line)
+    fn parse(text: &str) -> Vec<(String, Option<String>, Option<usize>)> {
+        parse_stack_trace(text)
+            .into_iter()
+            .map(|frame| (frame.function, frame.file, frame.line))
+            .collect()
+    }
+
+    fn frame(function: &str, file: &str, line: usize) -> (String, Option<String>, Option<usize>) {
+        (function.to_string(), Some(file.to_string()), Some(line))
+    }
+
+    #[test]
+    fn rust_backtrace() {
+        let trace = "\
+thread 'main' panicked at src/parser.rs:42:9:
+index out of bounds
+stack backtrace:
+   0: rust_begin_unwind
+             at /rustc/0123abc/library/std/src/panicking.rs:645:5
+   1: app::parser::Parser::parse
+             at ./src/parser.rs:42:9
+   2: app::main
+             at ./src/main.rs:10:5
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.";
+        assert_eq!(
+            parse(trace),
+            vec![
+                frame(
+                    "rust_begin_unwind",
+                    "/rustc/0123abc/library/std/src/panicking.rs",
+                    645
+                ),
+                frame("app::parser::Parser::parse", "./src/parser.rs", 42),
+                frame("app::main", "./src/main.rs", 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn rust_frame_without_location() {
+        let trace = "   0: app::run\n   1: app::main\n             at ./src/main.rs:3:5";
+        assert_eq!(
+            parse(trace),
+            vec![
+                ("app::run".to_string(), None, None),
+                frame("app::main", "./src/main.rs", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_traceback() {
+        let trace = r#"Traceback (most recent call last):
+  File "app/main.py", line 10, in <module>
+    run()
+  File "app/parser.py", line 42, in parse
+    raise ValueError("bad token")
+ValueError: bad token"#;
+        assert_eq!(
+            parse(trace),
+            vec![
+                frame("<module>", "app/main.py", 10),
+                frame("parse", "app/parser.py", 42),
+            ]
+        );
+    }
+}